@@ -1,4 +1,5 @@
 use std::fmt;
+use crate::token;
 use crate::token::Statements;
 
 pub trait Node {
@@ -24,9 +25,292 @@ impl fmt::Display for Program {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for statement in self.statements.iter() {
             fmt::Display::fmt(&statement, f)?;
-            writeln!(f, "{}", "")?
+            writeln!(f)?
         }
 
         Ok(())
     }
 }
+
+// Rewrites the tree bottom up: children are modified first, then
+// the node itself. Used by quote/unquote and macro expansion.
+pub trait Modifier {
+    // Nodes for which it returns `false` are left as they are,
+    // together with everything inside of them.
+    fn enter(&mut self, _expression: &token::Expression) -> bool {
+        true
+    }
+
+    fn expression(&mut self, expression: token::Expression) -> token::Expression {
+        expression
+    }
+
+    fn pattern(&mut self, pattern: token::Pattern) -> token::Pattern {
+        pattern
+    }
+}
+
+pub fn modify_program(program: Program, modifier: &mut dyn Modifier) -> Program {
+    Program {
+        statements: program
+            .statements
+            .into_iter()
+            .map(|statement| modify_statement(statement, modifier))
+            .collect(),
+    }
+}
+
+pub fn modify_statement(statement: Statements, modifier: &mut dyn Modifier) -> Statements {
+    match statement {
+        Statements::LetStatement(ls) => Statements::LetStatement(modify_let(ls, modifier)),
+        Statements::ReturnStatement(mut rs) => {
+            rs.return_value = modify_expression(rs.return_value, modifier);
+            Statements::ReturnStatement(rs)
+        }
+        Statements::ExpressionStatement(mut es) => {
+            es.expression = modify_expression(es.expression, modifier);
+            Statements::ExpressionStatement(es)
+        }
+        Statements::ImplStatement(mut is) => {
+            is.methods = is
+                .methods
+                .into_iter()
+                .map(|method| modify_let(method, modifier))
+                .collect();
+            Statements::ImplStatement(is)
+        }
+        statement @ Statements::StructStatement(_) => statement,
+        statement @ Statements::EnumStatement(_) => statement,
+        statement @ Statements::ImportStatement(_) => statement,
+        Statements::ExportStatement(mut es) => {
+            es.statement = Box::new(modify_statement(*es.statement, modifier));
+            Statements::ExportStatement(es)
+        }
+    }
+}
+
+fn modify_let(mut ls: token::LetStatement, modifier: &mut dyn Modifier) -> token::LetStatement {
+    ls.pattern = modify_pattern(ls.pattern, modifier);
+    ls.value = modify_expression(ls.value, modifier);
+    ls
+}
+
+pub fn modify_block(
+    mut block: token::BlockStatement,
+    modifier: &mut dyn Modifier,
+) -> token::BlockStatement {
+    block.statements = block
+        .statements
+        .into_iter()
+        .map(|statement| modify_statement(statement, modifier))
+        .collect();
+    block
+}
+
+pub fn modify_pattern(pattern: token::Pattern, modifier: &mut dyn Modifier) -> token::Pattern {
+    use token::Pattern;
+
+    let pattern = match pattern {
+        Pattern::Literal(literal) => Pattern::Literal(modify_expression(literal, modifier)),
+        Pattern::ArrayPattern(mut ap) => {
+            ap.elements = modify_patterns(ap.elements, modifier);
+            Pattern::ArrayPattern(ap)
+        }
+        Pattern::HashPattern(mut hp) => {
+            hp.entries = hp
+                .entries
+                .into_iter()
+                .map(|(key, pattern)| (key, modify_pattern(pattern, modifier)))
+                .collect();
+            Pattern::HashPattern(hp)
+        }
+        Pattern::VariantPattern(mut vp) => {
+            vp.fields = modify_patterns(vp.fields, modifier);
+            Pattern::VariantPattern(vp)
+        }
+        pattern @ Pattern::Identifier(_) => pattern,
+        pattern @ Pattern::Wildcard(_) => pattern,
+    };
+
+    modifier.pattern(pattern)
+}
+
+fn modify_patterns(
+    patterns: Vec<token::Pattern>,
+    modifier: &mut dyn Modifier,
+) -> Vec<token::Pattern> {
+    patterns
+        .into_iter()
+        .map(|pattern| modify_pattern(pattern, modifier))
+        .collect()
+}
+
+pub fn modify_expression(
+    expression: token::Expression,
+    modifier: &mut dyn Modifier,
+) -> token::Expression {
+    use token::Expression;
+
+    if !modifier.enter(&expression) {
+        return expression;
+    }
+
+    let expression = match expression {
+        Expression::ArrayLiteral(mut al) => {
+            al.elements = modify_expressions(al.elements, modifier);
+            Expression::ArrayLiteral(al)
+        }
+        Expression::HashLiteral(mut hl) => {
+            hl.pairs = hl
+                .pairs
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = modify_expression(key, modifier);
+                    (key, modify_expression(value, modifier))
+                })
+                .collect();
+            Expression::HashLiteral(hl)
+        }
+        Expression::InterpolatedString(mut is) => {
+            is.parts = is
+                .parts
+                .into_iter()
+                .map(|part| match part {
+                    token::InterpolationPart::Expression(expression) => {
+                        token::InterpolationPart::Expression(modify_expression(expression, modifier))
+                    }
+                    text => text,
+                })
+                .collect();
+            Expression::InterpolatedString(is)
+        }
+        Expression::PrefixExpression(mut pe) => {
+            pe.right = modify_expression(pe.right, modifier);
+            Expression::PrefixExpression(pe)
+        }
+        Expression::InfixExpression(mut ie) => {
+            ie.left = modify_expression(ie.left, modifier);
+            ie.right = modify_expression(ie.right, modifier);
+            Expression::InfixExpression(ie)
+        }
+        Expression::IfExpression(mut ie) => {
+            ie.condition = modify_expression(ie.condition, modifier);
+            ie.consequence = modify_block(ie.consequence, modifier);
+            ie.alternative = ie.alternative.map(|block| modify_block(block, modifier));
+            Expression::IfExpression(ie)
+        }
+        Expression::FunctionLiteral(mut fl) => {
+            fl.parameters = fl.parameters.map(|parameters| {
+                parameters
+                    .into_iter()
+                    .map(|mut parameter| {
+                        parameter.pattern = modify_pattern(parameter.pattern, modifier);
+                        parameter.default = parameter
+                            .default
+                            .map(|default| modify_expression(default, modifier));
+                        parameter
+                    })
+                    .collect()
+            });
+            fl.body = modify_block(fl.body, modifier);
+            Expression::FunctionLiteral(fl)
+        }
+        Expression::MacroLiteral(mut ml) => {
+            ml.body = modify_block(ml.body, modifier);
+            Expression::MacroLiteral(ml)
+        }
+        Expression::CallExpression(mut ce) => {
+            ce.function = modify_expression(ce.function, modifier);
+            ce.arguments = ce
+                .arguments
+                .map(|arguments| modify_expressions(arguments, modifier));
+            ce.named_arguments = ce
+                .named_arguments
+                .into_iter()
+                .map(|(name, value)| (name, modify_expression(value, modifier)))
+                .collect();
+            Expression::CallExpression(ce)
+        }
+        Expression::IndexExpression(mut ie) => {
+            ie.left = modify_expression(ie.left, modifier);
+            ie.index = modify_expression(ie.index, modifier);
+            Expression::IndexExpression(ie)
+        }
+        Expression::PipeExpression(mut pe) => {
+            pe.left = modify_expression(pe.left, modifier);
+            pe.right = modify_expression(pe.right, modifier);
+            Expression::PipeExpression(pe)
+        }
+        Expression::MatchExpression(mut me) => {
+            me.subject = modify_expression(me.subject, modifier);
+            me.arms = me
+                .arms
+                .into_iter()
+                .map(|mut arm| {
+                    arm.pattern = modify_pattern(arm.pattern, modifier);
+                    arm.guard = arm.guard.map(|guard| modify_expression(guard, modifier));
+                    arm.body = modify_block(arm.body, modifier);
+                    arm
+                })
+                .collect();
+            Expression::MatchExpression(me)
+        }
+        Expression::FieldAccess(mut fa) => {
+            fa.left = modify_expression(fa.left, modifier);
+            Expression::FieldAccess(fa)
+        }
+        Expression::WithExpression(mut we) => {
+            we.left = modify_expression(we.left, modifier);
+            we.fields = we
+                .fields
+                .into_iter()
+                .map(|(name, value)| (name, modify_expression(value, modifier)))
+                .collect();
+            Expression::WithExpression(we)
+        }
+        Expression::ThrowExpression(mut te) => {
+            te.value = modify_expression(te.value, modifier);
+            Expression::ThrowExpression(te)
+        }
+        Expression::TryExpression(mut te) => {
+            te.body = modify_block(te.body, modifier);
+            te.catch = te.catch.map(|(pattern, block)| {
+                let pattern = modify_pattern(pattern, modifier);
+                (pattern, modify_block(block, modifier))
+            });
+            te.finally = te.finally.map(|block| modify_block(block, modifier));
+            Expression::TryExpression(te)
+        }
+        Expression::PropagateExpression(mut pe) => {
+            pe.value = modify_expression(pe.value, modifier);
+            Expression::PropagateExpression(pe)
+        }
+        Expression::YieldExpression(mut ye) => {
+            ye.value = modify_expression(ye.value, modifier);
+            Expression::YieldExpression(ye)
+        }
+        Expression::ForExpression(mut fe) => {
+            fe.pattern = modify_pattern(fe.pattern, modifier);
+            fe.iterable = modify_expression(fe.iterable, modifier);
+            fe.body = modify_block(fe.body, modifier);
+            Expression::ForExpression(fe)
+        }
+        expression @ Expression::Identifier(_) => expression,
+        expression @ Expression::IntegerLiteral(_) => expression,
+        expression @ Expression::StringLiteral(_) => expression,
+        expression @ Expression::CharLiteral(_) => expression,
+        expression @ Expression::Boolean(_) => expression,
+    };
+
+    modifier.expression(expression)
+}
+
+fn modify_expressions(
+    expressions: Vec<token::Expression>,
+    modifier: &mut dyn Modifier,
+) -> Vec<token::Expression> {
+    expressions
+        .into_iter()
+        .map(|expression| modify_expression(expression, modifier))
+        .collect()
+}
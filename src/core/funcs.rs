@@ -1,124 +1,707 @@
+use crate::core::registry::{NativeFunction, Registry, Type};
+use crate::evaluation::environment;
+use crate::evaluation::evaluator;
 use crate::evaluation::evaluator::new_error;
+use crate::evaluation::generators;
 use crate::evaluation::object;
-use crate::evaluation::object::ObjectT;
+use crate::evaluation::seqs;
+use crate::evaluation::seqs::Stage;
 use lazy_static::lazy_static;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
 pub type FunctionName = String;
-pub type Arity = u8;
 
-// To register new function in the system we have to add it to
-// two different places.
-// First add it here, by registering its arity.
+const ARRAY: Type = Type::Of("ARRAY");
+const STRING: Type = Type::Of("STRING");
+const HASH: Type = Type::Of("HASH");
+const CHAR: Type = Type::Of("CHAR");
+const INTEGER: Type = Type::Of("INTEGER");
+const GENERATOR: Type = Type::Of("GENERATOR");
+const CORE_FUNCTION: Type = Type::Of("CORE_FUNCTION");
+const RESULT: Type = Type::Of("Result");
+const OPTION: Type = Type::Of("Option");
+
+// Where a core function is called from. Lets it call back into
+// the evaluator, e.g. to apply a function given as an argument.
+pub struct Context<'a> {
+    pub env: &'a environment::Environment,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(env: &'a environment::Environment) -> Self {
+        Context { env }
+    }
+
+    pub fn apply(
+        &self,
+        fun: &object::Object,
+        args: Vec<object::Object>,
+    ) -> Result<object::Object, object::Object> {
+        let result = evaluator::apply_function(fun.clone(), args, Vec::new(), self.env);
+
+        if evaluator::is_error(&result) {
+            Err(result)
+        } else {
+            Ok(result)
+        }
+    }
+
+    // Values of anything `for` can go through, taken one by one.
+    pub fn sequence(&self, value: object::Object) -> Result<seqs::Sequence, object::Object> {
+        seqs::Sequence::new(value, self.env)
+    }
+
+    pub fn next(
+        &self,
+        sequence: &mut seqs::Sequence,
+    ) -> Result<Option<object::Object>, object::Object> {
+        sequence.next(self.env)
+    }
+
+    // Order of values as `<` sees it, `Ord` protocol included.
+    pub fn compare(
+        &self,
+        left: &object::Object,
+        right: &object::Object,
+    ) -> Result<Ordering, object::Object> {
+        let less = |left: &object::Object, right: &object::Object| {
+            match evaluator::eval_infix_expression("<", left.clone(), right.clone(), self.env) {
+                object::Object::Boolean(boolean) => Ok(boolean.value),
+                err => Err(err),
+            }
+        };
+
+        if less(left, right)? {
+            Ok(Ordering::Less)
+        } else if less(right, left)? {
+            Ok(Ordering::Greater)
+        } else {
+            Ok(Ordering::Equal)
+        }
+    }
+}
+
+// Every core function is registered here, with its parameters and
+// documentation. Arguments are checked by the registry, so bodies
+// get the number and types of arguments they declare.
 lazy_static! {
-    pub static ref CORE_REGISTRY: HashMap<FunctionName, Arity> = [
-        ("length".to_string(), 1),
-        ("first".to_string(), 1),
-        ("last".to_string(), 1),
-        ("rest".to_string(), 1),
-        ("push".to_string(), 2),
+    pub static ref CORE_REGISTRY: Registry = {
+        let mut registry = Registry::default();
+
+        for function in core_functions() {
+            registry.register(function);
+        }
+
+        registry
+    };
+}
+
+fn core_functions() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction::new("length")
+            .doc("Number of characters of a string, elements of an array or pairs of a hash.")
+            .param("value", &[STRING, ARRAY, HASH])
+            .method()
+            .protocol("Length")
+            .collects()
+            .body(|args, _| length_(args[0].clone())),
+        NativeFunction::new("first")
+            .doc("First element of an array or character of a string, null if it's empty.")
+            .param("values", &[ARRAY, STRING])
+            .method()
+            .lazy(|args, ctx| first_of_(args[0].clone(), ctx).unwrap_or_else(|err| err))
+            .body(|args, _| first_(args[0].clone())),
+        NativeFunction::new("last")
+            .doc("Last element of an array or character of a string, null if it's empty.")
+            .param("values", &[ARRAY, STRING])
+            .method()
+            .collects()
+            .body(|args, _| last_(args[0].clone())),
+        NativeFunction::new("rest")
+            .doc("Array or string without its first element, lazy sequence for other values.")
+            .param("values", &[ARRAY, STRING])
+            .method()
+            .lazy(|args, _| seqs::seq(seqs::drop(seqs::stage(args[0].clone()), 1)))
+            .body(|args, _| rest_(args[0].clone())),
+        NativeFunction::new("push")
+            .doc("Array with the values added at its end.")
+            .param("array", &[ARRAY])
+            .param("value", &[Type::Any])
+            .variadic("values", &[Type::Any])
+            .method()
+            .collects()
+            .body(|mut args, _| {
+                let array = args.remove(0);
+                push_(array, args)
+            }),
+        NativeFunction::new("chars")
+            .doc("Characters of a string.")
+            .param("string", &[STRING])
+            .method()
+            .body(|args, _| chars_(args[0].clone())),
+        NativeFunction::new("ord")
+            .doc("Code of a character.")
+            .param("char", &[CHAR])
+            .method()
+            .body(|args, _| ord_(args[0].clone())),
+        NativeFunction::new("chr")
+            .doc("Character with the code.")
+            .param("code", &[INTEGER])
+            .method()
+            .body(|args, _| chr_(args[0].clone())),
+        NativeFunction::new("upper")
+            .doc("String in upper case.")
+            .param("string", &[STRING])
+            .method()
+            .body(|args, _| upper_(args[0].clone())),
+        NativeFunction::new("lower")
+            .doc("String in lower case.")
+            .param("string", &[STRING])
+            .method()
+            .body(|args, _| lower_(args[0].clone())),
+        NativeFunction::new("keys")
+            .doc("Keys of a hash.")
+            .param("hash", &[HASH])
+            .method()
+            .body(|args, _| keys_(args[0].clone())),
+        NativeFunction::new("values")
+            .doc("Values of a hash.")
+            .param("hash", &[HASH])
+            .method()
+            .body(|args, _| values_(args[0].clone())),
+        NativeFunction::new("is_ok")
+            .doc("Whether a Result is `Ok`.")
+            .param("result", &[RESULT])
+            .method()
+            .body(|args, _| is_variant_(args[0].clone(), "Ok")),
+        NativeFunction::new("is_err")
+            .doc("Whether a Result is `Err`.")
+            .param("result", &[RESULT])
+            .method()
+            .body(|args, _| is_variant_(args[0].clone(), "Err")),
+        NativeFunction::new("is_some")
+            .doc("Whether an Option is `Some`.")
+            .param("option", &[OPTION])
+            .method()
+            .body(|args, _| is_variant_(args[0].clone(), "Some")),
+        NativeFunction::new("is_none")
+            .doc("Whether an Option is `None`.")
+            .param("option", &[OPTION])
+            .method()
+            .body(|args, _| is_variant_(args[0].clone(), "None")),
+        NativeFunction::new("unwrap_or")
+            .doc("Value of `Ok` or `Some`, the default for `Err` and `None`.")
+            .param("value", &[RESULT, OPTION])
+            .param("default", &[Type::Any])
+            .method()
+            .body(|args, _| unwrap_or_(args[0].clone(), args[1].clone())),
+        NativeFunction::new("map_err")
+            .doc("Result with the function applied to its error, `Ok` stays as it is.")
+            .param("result", &[RESULT])
+            .param("f", &[Type::Function])
+            .method()
+            .body(|args, ctx| map_err_(args[0].clone(), &args[1], ctx).unwrap_or_else(|err| err)),
+        NativeFunction::new("next")
+            .doc("Next value of a generator as `Some(value)`, `None` once it's over.")
+            .param("generator", &[GENERATOR])
+            .method()
+            .body(|args, _| next_(args[0].clone())),
+        // Lazy sequences, see `evaluation::seqs`.
+        NativeFunction::new("seq")
+            .doc("Lazy sequence of the values.")
+            .param("values", &[Type::Iterable])
+            .body(|args, _| seqs::seq(seqs::stage(args[0].clone()))),
+        NativeFunction::new("map")
+            .doc("Lazy sequence of the values with the function applied to them.")
+            .param("values", &[Type::Iterable])
+            .param("f", &[Type::Function])
+            .body(|args, _| seqs::seq(Stage::Map(seqs::stage(args[0].clone()), args[1].clone()).into())),
+        NativeFunction::new("filter")
+            .doc("Lazy sequence of the values the predicate holds for.")
+            .param("values", &[Type::Iterable])
+            .param("predicate", &[Type::Function])
+            .body(|args, _| {
+                seqs::seq(Stage::Filter(seqs::stage(args[0].clone()), args[1].clone()).into())
+            }),
+        NativeFunction::new("take")
+            .doc("Lazy sequence of the first `count` values.")
+            .param("values", &[Type::Iterable])
+            .param("count", &[INTEGER])
+            .body(|args, _| match seqs::count("take", &args[1]) {
+                Ok(count) => seqs::seq(Stage::Take(seqs::stage(args[0].clone()), count).into()),
+                Err(err) => err,
+            }),
+        NativeFunction::new("drop")
+            .doc("Lazy sequence of the values without the first `count` ones.")
+            .param("values", &[Type::Iterable])
+            .param("count", &[INTEGER])
+            .body(|args, _| match seqs::count("drop", &args[1]) {
+                Ok(count) => seqs::seq(seqs::drop(seqs::stage(args[0].clone()), count)),
+                Err(err) => err,
+            }),
+        NativeFunction::new("take_while")
+            .doc("Lazy sequence of the values up to the first one the predicate fails for.")
+            .param("values", &[Type::Iterable])
+            .param("predicate", &[Type::Function])
+            .body(|args, _| {
+                seqs::seq(Stage::TakeWhile(seqs::stage(args[0].clone()), args[1].clone()).into())
+            }),
+        NativeFunction::new("zip")
+            .doc("Lazy sequence of `[value, other]` pairs, as long as the shorter one.")
+            .param("values", &[Type::Iterable])
+            .param("others", &[Type::Iterable])
+            .body(|args, _| {
+                let others = seqs::stage(args[1].clone());
+                seqs::seq(Stage::Zip(seqs::stage(args[0].clone()), others).into())
+            }),
+        NativeFunction::new("enumerate")
+            .doc("Lazy sequence of `[index, value]` pairs, indices count from `start` or 0.")
+            .param("values", &[Type::Iterable])
+            .optional("start", &[INTEGER])
+            .body(|args, _| {
+                let start = match args.get(1) {
+                    Some(object::Object::Integer(start)) => start.value,
+                    _ => 0,
+                };
+                seqs::seq(Stage::Enumerate(seqs::stage(args[0].clone()), start).into())
+            }),
+        NativeFunction::new("chain")
+            .doc("Lazy sequence of the values followed by the values of the others.")
+            .param("values", &[Type::Iterable])
+            .variadic("others", &[Type::Iterable])
+            .body(|args, _| {
+                let mut args = args.into_iter().map(seqs::stage);
+                let first = args.next().expect("arguments are checked by the registry");
+                seqs::seq(args.fold(first, |chained, other| Stage::Chain(chained, other).into()))
+            }),
+        NativeFunction::new("flat_map")
+            .doc("Lazy sequence of the values of what the function gives for each value.")
+            .param("values", &[Type::Iterable])
+            .param("f", &[Type::Function])
+            .body(|args, _| {
+                seqs::seq(Stage::FlatMap(seqs::stage(args[0].clone()), args[1].clone()).into())
+            }),
+        NativeFunction::new("range")
+            .doc("Lazy sequence of integers from `start` up to `end`, endless without `end`.")
+            .param("start", &[INTEGER])
+            .optional("end", &[INTEGER])
+            .body(|args, _| match args.as_slice() {
+                [object::Object::Integer(start)] => seqs::seq(Stage::Range(start.value, None).into()),
+                [object::Object::Integer(start), object::Object::Integer(end)] => {
+                    seqs::seq(Stage::Range(start.value, Some(end.value)).into())
+                }
+                _ => unreachable!("argument types are checked by the registry"),
+            }),
+        NativeFunction::new("iterate")
+            .doc("Endless lazy sequence of the value, `f(value)`, `f(f(value))` and so on.")
+            .param("value", &[Type::Any])
+            .param("f", &[Type::Function])
+            .body(|args, _| seqs::seq(Stage::Iterate(args[0].clone(), args[1].clone()).into())),
+        NativeFunction::new("repeat")
+            .doc("Endless lazy sequence of the value.")
+            .param("value", &[Type::Any])
+            .body(|args, _| seqs::seq(Stage::Repeat(args[0].clone()).into())),
+        NativeFunction::new("collect")
+            .doc("Array of the values.")
+            .param("values", &[Type::Iterable])
+            .body(|args, ctx| collect_(args[0].clone(), ctx).unwrap_or_else(|err| err)),
+        // Higher-order functions.
+        NativeFunction::new("reduce")
+            .doc("Accumulator after `f(accumulator, value)` for every value.")
+            .param("values", &[Type::Iterable])
+            .param("initial", &[Type::Any])
+            .param("f", &[Type::Function])
+            .body(|args, ctx| {
+                reduce_(args[0].clone(), args[1].clone(), &args[2], ctx).unwrap_or_else(|err| err)
+            }),
+        NativeFunction::new("sort_by")
+            .doc("Array of the values ordered by their keys, equal ones keep their order.")
+            .param("values", &[Type::Iterable])
+            .param("key", &[Type::Function])
+            .body(|args, ctx| sort_by_(args[0].clone(), &args[1], ctx).unwrap_or_else(|err| err)),
+        NativeFunction::new("each")
+            .doc("Calls the function with every value.")
+            .param("values", &[Type::Iterable])
+            .param("f", &[Type::Function])
+            .body(|args, ctx| each_(args[0].clone(), &args[1], ctx).unwrap_or_else(|err| err)),
+        NativeFunction::new("any")
+            .doc("Whether the predicate holds for any of the values.")
+            .param("values", &[Type::Iterable])
+            .param("predicate", &[Type::Function])
+            .body(|args, ctx| any_(args[0].clone(), &args[1], true, ctx).unwrap_or_else(|err| err)),
+        NativeFunction::new("all")
+            .doc("Whether the predicate holds for all of the values.")
+            .param("values", &[Type::Iterable])
+            .param("predicate", &[Type::Function])
+            .body(|args, ctx| any_(args[0].clone(), &args[1], false, ctx).unwrap_or_else(|err| err)),
+        NativeFunction::new("find")
+            .doc("First value the predicate holds for as `Some(value)`, `None` if there's none.")
+            .param("values", &[Type::Iterable])
+            .param("predicate", &[Type::Function])
+            .body(|args, ctx| find_(args[0].clone(), &args[1], ctx).unwrap_or_else(|err| err)),
+        NativeFunction::new("doc")
+            .doc("Signature and documentation of a core function.")
+            .param("function", &[CORE_FUNCTION])
+            .body(|args, _| doc_(&args[0])),
+    ]
+}
+
+// Protocols let user types take part in built-in operations,
+// e.g. `impl Add for Point` makes `+` work for points.
+// Every protocol lists its methods with arity, receiver included.
+lazy_static! {
+    pub static ref PROTOCOL_REGISTRY: HashMap<String, Vec<(FunctionName, usize)>> = [
+        ("Display", vec![("show", 1)]),     // string interpolation and REPL output
+        ("Eq", vec![("eq", 2)]),            // == and !=
+        ("Ord", vec![("compare", 2)]),      // < > <= >=, returns negative, zero or positive
+        ("Add", vec![("add", 2)]),          // +
+        ("Sub", vec![("sub", 2)]),          // -
+        ("Mul", vec![("mul", 2)]),          // *
+        ("Div", vec![("div", 2)]),          // /
+        ("Length", vec![("length", 1)]),    // length
+        ("Iterable", vec![("iter", 1)]),    // array functions and `for`, gets converted by `iter`
     ]
     .iter()
-    .cloned()
+    .map(|(protocol, methods)| {
+        let methods = methods
+            .iter()
+            .map(|(name, arity)| (name.to_string(), *arity))
+            .collect();
+        (protocol.to_string(), methods)
+    })
     .collect();
 }
 
-// Next we have to update this call function.
-// In the future object system will be redesigned (don't know how exactly, though)
-pub fn call(function_name: FunctionName, args: Vec<object::Object>) -> object::Object {
-    match function_name.as_ref() {
-        "length" if Some(&(args.len() as u8)) == CORE_REGISTRY.get(&function_name) => {
-            length_(args[0].clone())
-        }
-        "first" if Some(&(args.len() as u8)) == CORE_REGISTRY.get(&function_name) => {
-            first_(args[0].clone())
+// Enums every program starts with, so failure can be passed
+// around as a value: `Ok(value)`, `Err(error)`, `Some(value)`, `None`.
+lazy_static! {
+    pub static ref BUILTIN_ENUMS: Vec<object::EnumType> = vec![
+        object::EnumType {
+            name: "Result".to_string(),
+            variants: vec![
+                ("Ok".to_string(), vec!["value".to_string()]),
+                ("Err".to_string(), vec!["error".to_string()]),
+            ],
+            file: None,
+        },
+        object::EnumType {
+            name: "Option".to_string(),
+            variants: vec![
+                ("Some".to_string(), vec!["value".to_string()]),
+                ("None".to_string(), vec![]),
+            ],
+            file: None,
+        },
+    ];
+}
+
+pub fn length_(value: object::Object) -> object::Object {
+    let length = match value {
+        object::Object::Stringl(str) => str.value.chars().count(),
+        object::Object::Array(arr) => arr.elements.len(),
+        object::Object::Hash(hash) => hash.pairs.len(),
+        _ => unreachable!("argument types are checked by the registry"),
+    };
+
+    object::Object::Integer(object::Integer {
+        value: length as i32,
+    })
+}
+
+pub fn first_(values: object::Object) -> object::Object {
+    let first = match values {
+        object::Object::Array(arr) => arr.elements.front().cloned(),
+        object::Object::Stringl(str) => str.value.chars().next().map(char_),
+        _ => unreachable!("argument types are checked by the registry"),
+    };
+
+    first.unwrap_or(evaluator::NIL)
+}
+
+pub fn last_(values: object::Object) -> object::Object {
+    let last = match values {
+        object::Object::Array(arr) => arr.elements.back().cloned(),
+        object::Object::Stringl(str) => str.value.chars().last().map(char_),
+        _ => unreachable!("argument types are checked by the registry"),
+    };
+
+    last.unwrap_or(evaluator::NIL)
+}
+
+pub fn rest_(values: object::Object) -> object::Object {
+    match values {
+        // Shares the elements with the original array.
+        object::Object::Array(arr) => {
+            let elements = arr.elements.skip(1.min(arr.elements.len()));
+            object::Object::Array(object::Array { elements })
         }
-        "last" if Some(&(args.len() as u8)) == CORE_REGISTRY.get(&function_name) => {
-            last_(args[0].clone())
+        object::Object::Stringl(str) => object::Object::Stringl(object::Stringl {
+            value: str.value.chars().skip(1).collect(),
+        }),
+        _ => unreachable!("argument types are checked by the registry"),
+    }
+}
+
+pub fn push_(array: object::Object, values: Vec<object::Object>) -> object::Object {
+    match array {
+        object::Object::Array(mut arr) => {
+            arr.elements.extend(values);
+            object::Object::Array(arr)
         }
-        "rest" if Some(&(args.len() as u8)) == CORE_REGISTRY.get(&function_name) => {
-            rest_(args[0].clone())
+        _ => unreachable!("argument types are checked by the registry"),
+    }
+}
+
+pub fn chars_(string: object::Object) -> object::Object {
+    let elements = string_(&string).chars().map(char_).collect();
+    object::Object::Array(object::Array { elements })
+}
+
+pub fn ord_(ch: object::Object) -> object::Object {
+    match ch {
+        object::Object::Char(ch) => object::Object::Integer(object::Integer {
+            value: ch.value as i32,
+        }),
+        _ => unreachable!("argument types are checked by the registry"),
+    }
+}
+
+pub fn chr_(code: object::Object) -> object::Object {
+    match code {
+        object::Object::Integer(code) => match std::char::from_u32(code.value as u32) {
+            Some(value) if code.value >= 0 => char_(value),
+            _ => new_error(format!("invalid character code: {}", code.value)),
+        },
+        _ => unreachable!("argument types are checked by the registry"),
+    }
+}
+
+pub fn upper_(string: object::Object) -> object::Object {
+    object::Object::Stringl(object::Stringl {
+        value: string_(&string).to_uppercase(),
+    })
+}
+
+pub fn lower_(string: object::Object) -> object::Object {
+    object::Object::Stringl(object::Stringl {
+        value: string_(&string).to_lowercase(),
+    })
+}
+
+pub fn keys_(hash: object::Object) -> object::Object {
+    match hash {
+        object::Object::Hash(hash) => {
+            let elements = hash.pairs.values().map(|pair| pair.key.clone()).collect();
+            object::Object::Array(object::Array { elements })
         }
-        "push" if Some(&(args.len() as u8)) == CORE_REGISTRY.get(&function_name) => {
-            push_(args[0].clone(), args[1].clone())
+        _ => unreachable!("argument types are checked by the registry"),
+    }
+}
+
+pub fn values_(hash: object::Object) -> object::Object {
+    match hash {
+        object::Object::Hash(hash) => {
+            let elements = hash.pairs.values().map(|pair| pair.value.clone()).collect();
+            object::Object::Array(object::Array { elements })
         }
-        _ => new_error(format!(
-            "wrong number of arguments: got={}, expected={}",
-            args.len(),
-            CORE_REGISTRY
-                .get(&function_name)
-                .expect("Cannot find function in CORE_REGISTRY, TO_GREP: 74392761423")
-        )),
+        _ => unreachable!("argument types are checked by the registry"),
     }
 }
 
-pub fn length_(str: object::Object) -> object::Object {
-    match str {
-        object::Object::Stringl(str) => object::Object::Integer(object::Integer {
-            value: str.value.len() as i32,
+pub fn is_variant_(value: object::Object, tag: &str) -> object::Object {
+    match value {
+        object::Object::Variant(variant) => object::Object::Boolean(object::Boolean {
+            value: variant.tag == tag,
         }),
-        object::Object::Array(arr) => object::Object::Integer(object::Integer {
-            value: arr.elements.len() as i32,
-        }),
-        _ => new_error(format!(
-            "argument to `length` not supported, got {}",
-            str.object_type()
-        )),
+        _ => unreachable!("argument types are checked by the registry"),
     }
 }
 
-pub fn first_(arr: object::Object) -> object::Object {
-    match arr {
-        object::Object::Array(arr) => {
-            if arr.elements.len() > 0 {
-                arr.elements[0].clone()
-            } else {
-                crate::evaluation::evaluator::NIL
+// `Ok(value)` and `Some(value)` give the value, anything else of
+// Result or Option gives the default.
+pub fn unwrap_or_(value: object::Object, default: object::Object) -> object::Object {
+    match value {
+        object::Object::Variant(mut variant) => match variant.tag.as_ref() {
+            "Ok" | "Some" => variant.values.remove(0),
+            _ => default,
+        },
+        _ => unreachable!("argument types are checked by the registry"),
+    }
+}
+
+pub fn map_err_(
+    value: object::Object,
+    fun: &object::Object,
+    ctx: &Context,
+) -> Result<object::Object, object::Object> {
+    match value {
+        object::Object::Variant(mut variant) => {
+            if variant.tag == "Err" {
+                let error = variant.values.remove(0);
+                variant.values.push(ctx.apply(fun, vec![error])?);
             }
+            Ok(object::Object::Variant(variant))
         }
-        _ => new_error(format!(
-            "argument to `first` must be array, got {}",
-            arr.object_type()
-        )),
+        _ => unreachable!("argument types are checked by the registry"),
     }
 }
 
-pub fn last_(arr: object::Object) -> object::Object {
-    match arr {
-        object::Object::Array(arr) => match arr.elements.last() {
-            Some(elem) => elem.clone(),
-            None => crate::evaluation::evaluator::NIL,
+// Next value of a generator as `Some(value)`, `None` once it's over.
+pub fn next_(generator: object::Object) -> object::Object {
+    match generator {
+        object::Object::Generator(generator) => match generators::resume(&generator) {
+            Ok(value) => option(value),
+            Err(err) => err,
         },
-        _ => new_error(format!(
-            "argument to `last` must be array, got {}",
-            arr.object_type()
-        )),
+        _ => unreachable!("argument types are checked by the registry"),
     }
 }
 
-pub fn rest_(arr: object::Object) -> object::Object {
-    match arr {
-        object::Object::Array(arr) => {
-            let elements = arr.elements.clone().into_iter().skip(1).collect();
-            object::Object::Array(object::Array { elements })
+// First value of a generator or a lazy sequence, the rest
+// of it is not evaluated.
+pub fn first_of_(values: object::Object, ctx: &Context) -> Result<object::Object, object::Object> {
+    let mut values = ctx.sequence(values)?;
+    Ok(ctx.next(&mut values)?.unwrap_or(evaluator::NIL))
+}
+
+pub fn collect_(values: object::Object, ctx: &Context) -> Result<object::Object, object::Object> {
+    let elements = ctx.sequence(values)?.collect(ctx.env)?;
+    Ok(object::Object::Array(object::Array {
+        elements: elements.into(),
+    }))
+}
+
+pub fn reduce_(
+    values: object::Object,
+    initial: object::Object,
+    fun: &object::Object,
+    ctx: &Context,
+) -> Result<object::Object, object::Object> {
+    let mut values = ctx.sequence(values)?;
+
+    let mut accumulator = initial;
+    while let Some(value) = ctx.next(&mut values)? {
+        accumulator = ctx.apply(fun, vec![accumulator, value])?;
+    }
+    Ok(accumulator)
+}
+
+// Values ordered by the keys the function gives for them,
+// values with equal keys keep their order.
+pub fn sort_by_(
+    values: object::Object,
+    fun: &object::Object,
+    ctx: &Context,
+) -> Result<object::Object, object::Object> {
+    let mut values = ctx.sequence(values)?;
+
+    let mut keyed = Vec::new();
+    while let Some(value) = ctx.next(&mut values)? {
+        keyed.push((ctx.apply(fun, vec![value.clone()])?, value));
+    }
+
+    // Comparison may fail, the first error is kept and returned.
+    let mut error = None;
+    keyed.sort_by(|(left, _), (right, _)| {
+        if error.is_some() {
+            return Ordering::Equal;
         }
-        _ =>new_error(format!(
-            "argument to `rest` must be array, got {}",
-            arr.object_type()
-        )),
+        ctx.compare(left, right).unwrap_or_else(|err| {
+            error = Some(err);
+            Ordering::Equal
+        })
+    });
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(object::Object::Array(object::Array {
+            elements: keyed.into_iter().map(|(_, value)| value).collect(),
+        })),
     }
 }
 
-pub fn push_(arr: object::Object, elem: object::Object) -> object::Object {
-    match arr {
-        object::Object::Array(arr) => {
-            let mut new_arr = arr.clone();
-            new_arr.elements.push(elem);
-            object::Object::Array(new_arr)
-        },
-        _ => new_error(format!(
-            "argument to `push` must be array, got {}",
-            arr.object_type()
-        )),
+pub fn each_(
+    values: object::Object,
+    fun: &object::Object,
+    ctx: &Context,
+) -> Result<object::Object, object::Object> {
+    let mut values = ctx.sequence(values)?;
+
+    while let Some(value) = ctx.next(&mut values)? {
+        ctx.apply(fun, vec![value])?;
+    }
+    Ok(evaluator::NIL)
+}
+
+// `any` looks for a value the predicate holds for, `all` for one it
+// doesn't hold for. Both stop at the first one found.
+pub fn any_(
+    values: object::Object,
+    fun: &object::Object,
+    expected: bool,
+    ctx: &Context,
+) -> Result<object::Object, object::Object> {
+    let mut values = ctx.sequence(values)?;
+
+    while let Some(value) = ctx.next(&mut values)? {
+        if evaluator::is_truthy(ctx.apply(fun, vec![value])?) == expected {
+            return Ok(object::Object::Boolean(object::Boolean { value: expected }));
+        }
+    }
+    Ok(object::Object::Boolean(object::Boolean { value: !expected }))
+}
+
+// First value the predicate holds for, as Option.
+pub fn find_(
+    values: object::Object,
+    fun: &object::Object,
+    ctx: &Context,
+) -> Result<object::Object, object::Object> {
+    let mut values = ctx.sequence(values)?;
+
+    while let Some(value) = ctx.next(&mut values)? {
+        if evaluator::is_truthy(ctx.apply(fun, vec![value.clone()])?) {
+            return Ok(option(Some(value)));
+        }
+    }
+    Ok(option(None))
+}
+
+// "push(array, value, ...values)" and its documentation below.
+pub fn doc_(function: &object::Object) -> object::Object {
+    match function {
+        object::Object::CoreFunc(fun) => object::Object::Stringl(object::Stringl {
+            value: format!("{}\n{}", fun.native().signature(), fun.native().doc),
+        }),
+        _ => unreachable!("argument types are checked by the registry"),
+    }
+}
+
+fn string_(string: &object::Object) -> &str {
+    match string {
+        object::Object::Stringl(string) => &string.value,
+        _ => unreachable!("argument types are checked by the registry"),
+    }
+}
+
+fn char_(value: char) -> object::Object {
+    object::Object::Char(object::Char { value })
+}
+
+fn option(value: Option<object::Object>) -> object::Object {
+    match value {
+        Some(value) => object::Object::Variant(object::Variant {
+            type_name: "Option".to_string(),
+            tag: "Some".to_string(),
+            values: vec![value],
+            file: None,
+        }),
+        None => object::Object::Variant(object::Variant {
+            type_name: "Option".to_string(),
+            tag: "None".to_string(),
+            values: vec![],
+            file: None,
+        }),
     }
 }
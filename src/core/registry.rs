@@ -0,0 +1,355 @@
+use crate::core::funcs;
+use crate::core::funcs::Context;
+use crate::evaluation::evaluator;
+use crate::evaluation::evaluator::new_error;
+use crate::evaluation::object;
+use crate::evaluation::object::ObjectT;
+use crate::evaluation::seqs;
+use std::collections::HashMap;
+
+pub type Body = Box<dyn Fn(Vec<object::Object>, &Context) -> object::Object + Send + Sync>;
+
+// What a parameter of a native function accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Any,
+    // Values of this `object_type()`, e.g. "ARRAY" or "Result".
+    Of(&'static str),
+    // Anything `for` can go through.
+    Iterable,
+    // Anything that can be called.
+    Function,
+}
+
+impl Type {
+    fn accepts(self, value: &object::Object, ctx: &Context) -> bool {
+        match self {
+            Type::Any => true,
+            Type::Of(object_type) => value.object_type() == object_type,
+            Type::Iterable => match value {
+                object::Object::Array(_) | object::Object::Stringl(_) => true,
+                value => is_sequence(value, ctx),
+            },
+            Type::Function => matches!(
+                value,
+                object::Object::Function(_)
+                    | object::Object::CoreFunc(_)
+                    | object::Object::StructType(_)
+                    | object::Object::VariantConstructor(_)
+            ),
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            Type::Any => "anything".to_string(),
+            Type::Of(object_type) => object_type.to_string(),
+            Type::Iterable => "iterable".to_string(),
+            Type::Function => "a function".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: &'static str,
+    pub types: Vec<Type>,
+}
+
+// How many arguments a native function takes, in the same terms
+// as errors of user functions: `2`, `1..2` or `at least 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Range(usize, usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn accepts(self, count: usize) -> bool {
+        match self {
+            Arity::Range(min, max) => min <= count && count <= max,
+            Arity::AtLeast(min) => min <= count,
+        }
+    }
+}
+
+impl std::fmt::Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Arity::Range(min, max) if min == max => write!(f, "{}", min),
+            Arity::Range(min, max) => write!(f, "{}..{}", min, max),
+            Arity::AtLeast(min) => write!(f, "at least {}", min),
+        }
+    }
+}
+
+// Generators, lazy sequences and user types implementing `Iterable`
+// may never end, so they are not taken as arrays by default.
+pub enum Sequences {
+    Rejected,
+    // Values left in them are collected into an array first.
+    Collected,
+    // Body of its own, which takes just the values it needs.
+    Lazy(Body),
+}
+
+// Function of the standard library: its parameters, documentation
+// and the Rust code behind it. Made with the builder methods:
+//
+//     NativeFunction::new("push")
+//         .doc("Array with the values added at the end.")
+//         .param("array", &[Type::Of("ARRAY")])
+//         .variadic("values", &[Type::Any])
+//         .method()
+//         .collects()
+//         .body(|args, _| ...)
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub doc: &'static str,
+    // Mandatory parameters come first, then optional ones.
+    pub params: Vec<Param>,
+    pub optional: usize,
+    // Takes any number of arguments after `params`, all of these types.
+    pub variadic: Option<Param>,
+    // Method of the values its first parameter takes: `arr.push(1)`
+    // is `push(arr, 1)`.
+    pub method: bool,
+    // User types implementing the protocol have their method of the
+    // same name called instead, e.g. `length` of `Length`.
+    pub protocol: Option<&'static str>,
+    pub sequences: Sequences,
+    body: Option<Body>,
+}
+
+impl NativeFunction {
+    pub fn new(name: &'static str) -> Self {
+        NativeFunction {
+            name,
+            doc: "",
+            params: Vec::new(),
+            optional: 0,
+            variadic: None,
+            method: false,
+            protocol: None,
+            sequences: Sequences::Rejected,
+            body: None,
+        }
+    }
+
+    pub fn doc(mut self, doc: &'static str) -> Self {
+        self.doc = doc;
+        self
+    }
+
+    pub fn param(mut self, name: &'static str, types: &[Type]) -> Self {
+        assert!(self.optional == 0, "`{}`: mandatory parameter after optional", self.name);
+        self.params.push(Param { name, types: types.to_vec() });
+        self
+    }
+
+    pub fn optional(mut self, name: &'static str, types: &[Type]) -> Self {
+        self.params.push(Param { name, types: types.to_vec() });
+        self.optional += 1;
+        self
+    }
+
+    pub fn variadic(mut self, name: &'static str, types: &[Type]) -> Self {
+        assert!(self.optional == 0, "`{}`: variadic and optional parameters", self.name);
+        self.variadic = Some(Param { name, types: types.to_vec() });
+        self
+    }
+
+    pub fn method(mut self) -> Self {
+        self.method = true;
+        self
+    }
+
+    pub fn protocol(mut self, protocol: &'static str) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    pub fn collects(mut self) -> Self {
+        self.sequences = Sequences::Collected;
+        self
+    }
+
+    pub fn lazy<F>(mut self, body: F) -> Self
+    where
+        F: Fn(Vec<object::Object>, &Context) -> object::Object + Send + Sync + 'static,
+    {
+        self.sequences = Sequences::Lazy(Box::new(body));
+        self
+    }
+
+    pub fn body<F>(mut self, body: F) -> Self
+    where
+        F: Fn(Vec<object::Object>, &Context) -> object::Object + Send + Sync + 'static,
+    {
+        self.body = Some(Box::new(body));
+        self
+    }
+
+    pub fn arity(&self) -> Arity {
+        let mandatory = self.params.len() - self.optional;
+
+        match self.variadic {
+            Some(_) => Arity::AtLeast(mandatory),
+            None => Arity::Range(mandatory, self.params.len()),
+        }
+    }
+
+    // First parameter takes anything iterable: lazy sequences and
+    // higher-order functions. They are methods of iterable types.
+    pub fn iterates(&self) -> bool {
+        self.params
+            .first()
+            .is_some_and(|param| param.types == [Type::Iterable])
+    }
+
+    pub fn is_method_of(&self, receiver: &object::Object, ctx: &Context) -> bool {
+        if !self.method && !self.iterates() {
+            return false;
+        }
+
+        let takes = |param: &Param| param.types.iter().any(|t| t.accepts(receiver, ctx));
+        self.params.first().is_some_and(takes)
+            || !matches!(self.sequences, Sequences::Rejected) && is_sequence(receiver, ctx)
+    }
+
+    // `push(array, ...values)`
+    pub fn signature(&self) -> String {
+        let mandatory = self.params.len() - self.optional;
+        let params = self
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, param)| {
+                if i < mandatory {
+                    param.name.to_string()
+                } else {
+                    format!("{}?", param.name)
+                }
+            })
+            .chain(self.variadic.iter().map(|param| format!("...{}", param.name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("{}({})", self.name, params)
+    }
+
+    // Arguments are checked before the body runs, so it can rely
+    // on their number and types.
+    pub fn call(&self, mut args: Vec<object::Object>, ctx: &Context) -> object::Object {
+        if let (Some(protocol), Some(receiver)) = (self.protocol, args.first()) {
+            if let Some(method) =
+                evaluator::find_protocol_method(receiver, protocol, self.name, ctx.env)
+            {
+                return ctx.apply(&method, args).unwrap_or_else(|err| err);
+            }
+        }
+
+        let arity = self.arity();
+        if !arity.accepts(args.len()) {
+            return new_error(format!(
+                "wrong number of arguments to `{}`: got={}, expected={}",
+                self.name,
+                args.len(),
+                arity
+            ));
+        }
+
+        // User types are taken as what their `iter` gives.
+        let takes_sequences = !matches!(self.sequences, Sequences::Rejected);
+        if let Some(first) = args.first_mut() {
+            if takes_sequences && is_user_iterable(first, ctx) {
+                match seqs::iterated(first.clone(), ctx.env) {
+                    Ok(iterated) => *first = iterated,
+                    Err(err) => return err,
+                }
+            }
+        }
+
+        let sequence = matches!(
+            args.first(),
+            Some(object::Object::Generator(_)) | Some(object::Object::Seq(_))
+        );
+        let body = match &self.sequences {
+            Sequences::Lazy(body) if sequence => body,
+            Sequences::Collected if sequence => {
+                match funcs::collect_(args[0].clone(), ctx) {
+                    Ok(array) => args[0] = array,
+                    Err(err) => return err,
+                }
+                self.own_body()
+            }
+            _ => self.own_body(),
+        };
+
+        // Lazy body takes any sequence as the first argument.
+        let skipped = usize::from(matches!(self.sequences, Sequences::Lazy(_)) && sequence);
+        let params = self.params.iter().chain(self.variadic.iter().cycle());
+        for (arg, param) in args.iter().zip(params).skip(skipped) {
+            if !param.types.iter().any(|t| t.accepts(arg, ctx)) {
+                return new_error(format!(
+                    "argument to `{}` must be {}, got {}",
+                    self.name,
+                    describe(&param.types),
+                    arg.object_type()
+                ));
+            }
+        }
+
+        body(args, ctx)
+    }
+
+    fn own_body(&self) -> &Body {
+        match &self.body {
+            Some(body) => body,
+            None => unreachable!("registry only takes functions with a body"),
+        }
+    }
+}
+
+// Iterable values other than arrays and strings.
+fn is_sequence(value: &object::Object, ctx: &Context) -> bool {
+    match value {
+        object::Object::Generator(_) | object::Object::Seq(_) => true,
+        value => is_user_iterable(value, ctx),
+    }
+}
+
+fn is_user_iterable(value: &object::Object, ctx: &Context) -> bool {
+    ctx.env.implements(&value.type_key(), "Iterable")
+}
+
+// "ARRAY", "ARRAY or STRING", "STRING, ARRAY or HASH"
+fn describe(types: &[Type]) -> String {
+    let mut described = types.iter().map(|t| t.describe()).collect::<Vec<_>>();
+
+    match described.pop() {
+        Some(last) if described.is_empty() => last,
+        Some(last) => format!("{} or {}", described.join(", "), last),
+        None => String::new(),
+    }
+}
+
+#[derive(Default)]
+pub struct Registry {
+    functions: HashMap<&'static str, NativeFunction>,
+}
+
+impl Registry {
+    pub fn register(&mut self, function: NativeFunction) {
+        let name = function.name;
+        assert!(function.body.is_some(), "native function `{}` has no body", name);
+
+        if self.functions.insert(name, function).is_some() {
+            panic!("native function `{}` is registered twice", name);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NativeFunction> {
+        self.functions.get(name)
+    }
+}
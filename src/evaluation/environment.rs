@@ -1,16 +1,58 @@
-use std::collections::HashMap;
+use im_rc::HashMap as Store;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
+use crate::evaluation::modules;
 use crate::evaluation::object;
 
+// Methods are kept by receiver type and method name.
+type MethodTable = HashMap<(object::TypeKey, String), object::Object>;
+
 #[derive(Debug, Clone)]
 pub struct Environment {
-    store: HashMap<String, object::Object>,
+    // Functions capture the scope by cloning it, a persistent map
+    // makes the clone cheap, the bindings are shared.
+    store: Store<String, object::Object>,
     outer: Option<Box<Environment>>,
+    // Unlike bindings, methods are shared by every copy of the environment.
+    // Functions capture a copy of the scope they're defined in, so a method
+    // added later (or the method itself) would be invisible inside of them.
+    methods: Rc<RefCell<MethodTable>>,
+    // Protocols implemented by a type: (type, protocol name).
+    protocols: Rc<RefCell<HashSet<(object::TypeKey, String)>>>,
+    // Every file is loaded once per program, whoever imports it.
+    modules: Rc<RefCell<modules::ModuleCache>>,
+    // File being evaluated, None for the REPL. Imports are resolved
+    // relative to it and errors raised in it are located in it.
+    file: Option<PathBuf>,
 }
 
 impl Environment {
     pub fn new() -> Self {
-        Environment { store: HashMap::new(), outer: None }
+        Environment {
+            store: Store::new(),
+            outer: None,
+            methods: Rc::new(RefCell::new(HashMap::new())),
+            protocols: Rc::new(RefCell::new(HashSet::new())),
+            modules: Rc::new(RefCell::new(modules::ModuleCache::default())),
+            file: None,
+        }
+    }
+
+    // Top-level environment of a file. It has no bindings of its own,
+    // but methods and loaded modules are shared. Types declared
+    // in the file are told from others by its name.
+    pub fn new_file_environment(&self, file: PathBuf) -> Self {
+        Environment {
+            store: Store::new(),
+            outer: None,
+            methods: self.methods.clone(),
+            protocols: self.protocols.clone(),
+            modules: self.modules.clone(),
+            file: Some(file),
+        }
     }
 
     // New enclosed environment accepts a copy of outer environment.
@@ -18,7 +60,11 @@ impl Environment {
     // is described you cannot modify outer scope inside it.
     // Because you access your own copy of the outer scope.
     pub fn new_enclosed_environment(outer: Self) -> Self {
-        Environment { store: HashMap::new(), outer: Some(Box::new(outer)) }
+        let methods = outer.methods.clone();
+        let protocols = outer.protocols.clone();
+        let modules = outer.modules.clone();
+        let file = outer.file.clone();
+        Environment { store: Store::new(), outer: Some(Box::new(outer)), methods, protocols, modules, file }
     }
 
     pub fn get(&self, name: String) -> Option<&object::Object> {
@@ -37,4 +83,48 @@ impl Environment {
                                 // because we inserted element on previous line
                                 // TODO: might be a good idea to check this one more time
     }
+
+    pub fn get_method(&self, type_key: &object::TypeKey, name: &str) -> Option<object::Object> {
+        self.methods
+            .borrow()
+            .get(&(type_key.clone(), name.to_string()))
+            .cloned()
+    }
+
+    pub fn set_method(&self, type_key: object::TypeKey, name: String, method: object::Object) {
+        self.methods.borrow_mut().insert((type_key, name), method);
+    }
+
+    pub fn implements(&self, type_key: &object::TypeKey, protocol: &str) -> bool {
+        self.protocols
+            .borrow()
+            .contains(&(type_key.clone(), protocol.to_string()))
+    }
+
+    pub fn set_protocol(&self, type_key: object::TypeKey, protocol: String) {
+        self.protocols.borrow_mut().insert((type_key, protocol));
+    }
+
+    // Key of the types declared here.
+    pub fn type_key(&self, type_name: String) -> object::TypeKey {
+        (self.file.as_ref().map(|file| file.display().to_string()), type_name)
+    }
+
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    pub fn modules(&self) -> &RefCell<modules::ModuleCache> {
+        &self.modules
+    }
+
+    // Redeclared type starts with no methods and protocols.
+    pub fn clear_methods(&self, type_key: &object::TypeKey) {
+        self.methods
+            .borrow_mut()
+            .retain(|(method_type, _), _| method_type != type_key);
+        self.protocols
+            .borrow_mut()
+            .retain(|(protocol_type, _)| protocol_type != type_key);
+    }
 }
@@ -1,8 +1,13 @@
 use crate::ast;
+use crate::core;
 use crate::evaluation::environment;
+use crate::evaluation::generators;
+use crate::evaluation::modules;
 use crate::evaluation::object;
 use crate::evaluation::object::ObjectT;
+use crate::evaluation::seqs;
 use crate::token;
+use im_rc::OrdMap;
 
 // TODO: Consider this to be a hack.
 //
@@ -14,7 +19,9 @@ use crate::token;
 // To generalize eval API we would wrap program in a special
 // wrapper enum, which knows how to handle every unit.
 
+// Nodes are unwrapped right away by eval, so they aren't boxed.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum WrappedNode {
     P(ast::Program),
     S(token::Statements),
@@ -51,7 +58,10 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                 if is_error(&val) {
                     return val;
                 };
-                env.set(ls.name.value, val).clone() // Hmmmmmmmmmmmm, change signature? To avoid cloning? Should work.
+                match bind_pattern(ls.pattern, val.clone(), env) {
+                    Ok(()) => val,
+                    Err(err) => err,
+                }
             }
             token::Statements::ReturnStatement(rs) => {
                 let val = eval(WN::E(rs.return_value), env);
@@ -62,6 +72,100 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                 }
                 object::Object::ReturnValue(Box::new(object::ReturnValue { value: val }))
             }
+            token::Statements::ImplStatement(is) => {
+                let mut methods = Vec::new();
+
+                for method in is.methods {
+                    let name = match method.pattern {
+                        token::Pattern::Identifier(identifier) => identifier.value,
+                        pattern => unreachable!("parser only allows names in impl, got {}", pattern),
+                    };
+
+                    let value = eval(WN::E(method.value), env);
+                    if is_error(&value) {
+                        return value;
+                    }
+
+                    match value {
+                        object::Object::Function(_) | object::Object::CoreFunc(_) => {
+                            methods.push((name, value))
+                        }
+                        _ => {
+                            return new_error(format!(
+                                "method `{}` of {} must be a function, got {}",
+                                name,
+                                is.name.value,
+                                value.object_type()
+                            ))
+                        }
+                    }
+                }
+
+                // Methods belong to the type of this name in scope,
+                // it may be declared in an imported file.
+                let type_key = match env.get(is.name.value.clone()) {
+                    Some(object::Object::StructType(st)) => (st.file.clone(), st.name.clone()),
+                    Some(object::Object::EnumType(et)) => (et.file.clone(), et.name.clone()),
+                    _ => (None, is.name.value.clone()),
+                };
+
+                // Nothing is registered unless the whole protocol is implemented.
+                if let Some(protocol) = &is.protocol {
+                    if let Err(err) = check_protocol_impl(&protocol.value, &is.name.value, &methods) {
+                        return err;
+                    }
+                    env.set_protocol(type_key.clone(), protocol.value.clone());
+                }
+
+                for (name, value) in methods {
+                    env.set_method(type_key.clone(), name, value);
+                }
+                NIL
+            }
+            token::Statements::ImportStatement(is) => {
+                let module = modules::import(&is.path, env);
+                if is_error(&module) {
+                    return module;
+                }
+                env.set(is.name.value, module.clone());
+                module
+            }
+            token::Statements::ExportStatement(es) => eval(WN::S(*es.statement), env),
+            token::Statements::StructStatement(ss) => {
+                let type_key = env.type_key(ss.name.value.clone());
+                let struct_type = object::Object::StructType(object::StructType {
+                    name: ss.name.value.clone(),
+                    fields: ss.fields.into_iter().map(|field| field.value).collect(),
+                    file: type_key.0.clone(),
+                });
+                env.clear_methods(&type_key);
+                env.set(ss.name.value, struct_type.clone());
+                struct_type
+            }
+            token::Statements::EnumStatement(es) => {
+                let type_name = es.name.value;
+                let type_key = env.type_key(type_name.clone());
+                let mut variants = Vec::new();
+
+                for variant in es.variants {
+                    let tag = variant.name.value;
+                    let fields: Vec<String> =
+                        variant.fields.into_iter().map(|field| field.value).collect();
+
+                    env.set(tag.clone(), variant_value(&type_key, &tag, &fields));
+
+                    variants.push((tag, fields));
+                }
+
+                let enum_type = object::Object::EnumType(object::EnumType {
+                    name: type_name.clone(),
+                    variants,
+                    file: type_key.0.clone(),
+                });
+                env.clear_methods(&type_key);
+                env.set(type_name, enum_type.clone());
+                enum_type
+            }
         },
         WN::B(block) => eval_block_statement(block.statements, env),
         WN::E(expression) => match expression {
@@ -71,6 +175,83 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
             token::Expression::StringLiteral(sl) => {
                 object::Object::Stringl(object::Stringl { value: sl.value })
             }
+            token::Expression::FieldAccess(fa) => {
+                let left = eval(WN::E(fa.left), env);
+                if is_error(&left) {
+                    return left;
+                }
+                match left {
+                    object::Object::Struct(st) => match st.get(&fa.field.value) {
+                        Some(value) => value.clone(),
+                        None => new_error(format!(
+                            "unknown field `{}` for {}",
+                            fa.field.value, st.name
+                        )),
+                    },
+                    object::Object::Module(module) => module_member(&module, &fa.field.value),
+                    _ => new_error(format!(
+                        "field access not supported: {}",
+                        left.object_type()
+                    )),
+                }
+            }
+            token::Expression::WithExpression(we) => {
+                let left = eval(WN::E(we.left), env);
+                if is_error(&left) {
+                    return left;
+                }
+                let mut updated = match left {
+                    object::Object::Struct(st) => st,
+                    _ => {
+                        return new_error(format!(
+                            "`with` is not supported for {}",
+                            left.object_type()
+                        ))
+                    }
+                };
+
+                for (name, value) in we.fields {
+                    let value = eval(WN::E(value), env);
+                    if is_error(&value) {
+                        return value;
+                    }
+                    match updated.fields.iter_mut().find(|(field, _)| *field == name.value) {
+                        Some((_, field_value)) => *field_value = value,
+                        None => {
+                            return new_error(format!(
+                                "unknown field `{}` for {}",
+                                name.value, updated.name
+                            ))
+                        }
+                    }
+                }
+
+                object::Object::Struct(updated)
+            }
+            token::Expression::CharLiteral(cl) => {
+                object::Object::Char(object::Char { value: cl.value })
+            }
+            token::Expression::InterpolatedString(is) => {
+                let mut value = String::new();
+
+                for part in is.parts {
+                    match part {
+                        token::InterpolationPart::Text(text) => value.push_str(&text),
+                        token::InterpolationPart::Expression(expression) => {
+                            let evaluated = eval(WN::E(expression), env);
+                            if is_error(&evaluated) {
+                                return evaluated;
+                            }
+                            match display(&evaluated, env) {
+                                Ok(shown) => value.push_str(&shown),
+                                Err(err) => return err,
+                            }
+                        }
+                    }
+                }
+
+                object::Object::Stringl(object::Stringl { value })
+            }
             token::Expression::ArrayLiteral(al) => {
                 // first, eval arguments
                 let elements = eval_expressions(al.elements, env);
@@ -79,7 +260,38 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                     return elements[0].clone();
                 }
 
-                object::Object::Array(object::Array { elements })
+                object::Object::Array(object::Array {
+                    elements: elements.into(),
+                })
+            }
+            token::Expression::HashLiteral(hl) => {
+                let mut pairs = OrdMap::new();
+
+                for (key_expression, value_expression) in hl.pairs {
+                    let key = eval(WN::E(key_expression), env);
+                    if is_error(&key) {
+                        return key;
+                    }
+
+                    let hash_key = match key.hash_key() {
+                        Some(hash_key) => hash_key,
+                        None => {
+                            return new_error(format!(
+                                "unusable as hash key: {}",
+                                key.object_type()
+                            ))
+                        }
+                    };
+
+                    let value = eval(WN::E(value_expression), env);
+                    if is_error(&value) {
+                        return value;
+                    }
+
+                    pairs.insert(hash_key, object::HashPair { key, value });
+                }
+
+                object::Object::Hash(object::Hash { pairs })
             }
             token::Expression::IndexExpression(ie) => {
                 let left = eval(WN::E(ie.left), env);
@@ -91,7 +303,7 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                     return index;
                 }
                 if let (object::Object::Array(array), object::Object::Integer(i)) =
-                    (left.clone(), index)
+                    (left.clone(), index.clone())
                 {
                     let elements = array.elements;
                     let i = i.value as usize;
@@ -99,6 +311,26 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                         Some(element) => element.clone(),
                         None => NIL,
                     }
+                } else if let (object::Object::Stringl(string), object::Object::Integer(i)) =
+                    (left.clone(), index.clone())
+                {
+                    // Strings are indexed by characters, not bytes.
+                    let i = i.value as usize;
+                    match string.value.chars().nth(i) {
+                        Some(value) => object::Object::Char(object::Char { value }),
+                        None => NIL,
+                    }
+                } else if let object::Object::Hash(hash) = left.clone() {
+                    match index.hash_key() {
+                        Some(key) => match hash.pairs.get(&key) {
+                            Some(pair) => pair.value.clone(),
+                            None => NIL,
+                        },
+                        None => new_error(format!(
+                            "unusable as hash key: {}",
+                            index.object_type()
+                        )),
+                    }
                 } else {
                     new_error(format!(
                         "index operator not supported: {}",
@@ -111,7 +343,10 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                     Some(value) => value.clone(), // Cloning one more time... Signature, sir?
                     None => match object::CoreFunc::try_new(i.value.clone()) {
                         Some(val) => val.clone(),
-                        None => new_error(format!("identifier not found: {}", i.value)),
+                        None => match builtin_enum_value(&i.value) {
+                            Some(val) => val,
+                            None => new_error(format!("identifier not found: {}", i.value)),
+                        },
                     },
                 }
             }
@@ -128,11 +363,10 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                         _ => FALSE,
                     },
                     "-" => match right {
-                        object::Object::Integer(int_obj) => {
-                            object::Object::Integer(object::Integer {
-                                value: -int_obj.value,
-                            })
-                        }
+                        object::Object::Integer(int_obj) => match int_obj.value.checked_neg() {
+                            Some(value) => object::Object::Integer(object::Integer { value }),
+                            None => new_error(format!("integer overflow: -({})", int_obj.value)),
+                        },
                         _ => new_error(format!("unknown operator: -{}", right.object_type())),
                     },
                     _ => new_error(format!(
@@ -147,86 +381,21 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                 if is_error(&left) {
                     return left;
                 }
+                // Logical operators are short-circuiting, so the right
+                // operand is evaluated only when the left one cannot decide
+                // the result. Like in Lua we return the deciding operand
+                // itself, not a boolean.
+                match ie.operator.as_ref() {
+                    "&&" if !is_truthy(left.clone()) => return left,
+                    "||" if is_truthy(left.clone()) => return left,
+                    "&&" | "||" => return eval(WN::E(ie.right), env),
+                    _ => (),
+                }
                 let right = eval(WN::E(ie.right), env);
                 if is_error(&right) {
                     return right;
                 }
-                if let (object::Object::Integer(left_obj), object::Object::Integer(right_obj)) =
-                    (left.clone(), right.clone())
-                {
-                    match ie.operator.as_ref() {
-                        "+" => object::Object::Integer(object::Integer {
-                            value: left_obj.value + right_obj.value,
-                        }),
-                        "-" => object::Object::Integer(object::Integer {
-                            value: left_obj.value - right_obj.value,
-                        }),
-                        "*" => object::Object::Integer(object::Integer {
-                            value: left_obj.value * right_obj.value,
-                        }),
-                        "/" => object::Object::Integer(object::Integer {
-                            value: left_obj.value / right_obj.value,
-                        }),
-                        "<" => object::Object::Boolean(object::Boolean {
-                            value: left_obj.value < right_obj.value,
-                        }),
-                        ">" => object::Object::Boolean(object::Boolean {
-                            value: left_obj.value > right_obj.value,
-                        }),
-                        "==" => object::Object::Boolean(object::Boolean {
-                            value: left_obj.value == right_obj.value,
-                        }),
-                        "!=" => object::Object::Boolean(object::Boolean {
-                            value: left_obj.value != right_obj.value,
-                        }),
-                        _ => {
-                            new_error(format!("unknown operator: INTEGER {} INTEGER", ie.operator))
-                        }
-                    }
-                } else if let (
-                    object::Object::Boolean(left_obj),
-                    object::Object::Boolean(right_obj),
-                ) = (left.clone(), right.clone())
-                {
-                    match ie.operator.as_ref() {
-                        "==" => object::Object::Boolean(object::Boolean {
-                            value: left_obj.value == right_obj.value,
-                        }),
-                        "!=" => object::Object::Boolean(object::Boolean {
-                            value: left_obj.value != right_obj.value,
-                        }),
-                        _ => {
-                            new_error(format!("unknown operator: BOOLEAN {} BOOLEAN", ie.operator))
-                        }
-                    }
-                } else if let (
-                    object::Object::Stringl(left_str),
-                    object::Object::Stringl(right_str),
-                ) = (left.clone(), right.clone())
-                {
-                    match ie.operator.as_ref() {
-                        "+" => object::Object::Stringl(object::Stringl {
-                            value: format!("{}{}", left_str.value, right_str.value),
-                        }),
-                        _ => new_error(format!("unknown operator: STRING {} STRING", ie.operator)),
-                    }
-                } else {
-                    if left.same_tag(&right) {
-                        new_error(format!(
-                            "unknown operator: {} {} {}",
-                            left.object_type(),
-                            ie.operator,
-                            right.object_type()
-                        ))
-                    } else {
-                        new_error(format!(
-                            "type mismatch: {} {} {}",
-                            left.object_type(),
-                            ie.operator,
-                            right.object_type()
-                        ))
-                    }
-                }
+                eval_infix_expression(&ie.operator, left, right, env)
             }
             token::Expression::Boolean(b) => {
                 // TODO: Check possible perf optimization? Needed?
@@ -252,14 +421,100 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                 let parameters = fl.parameters;
                 let body = fl.body;
                 // should I link to to this env or use copy of it
-                object::Object::Function(object::Function {
+                object::Object::Function(Box::new(object::Function {
                     parameters,
                     body,
                     env: env.clone(),
-                })
+                    generator: fl.generator,
+                }))
+            }
+            // Located at the `yield`, not at the statement around it.
+            token::Expression::YieldExpression(ye) => object::Object::Error(object::Error {
+                message: concat!(
+                    "`yield` can only be a statement of a generator, ",
+                    "or of `if`, `match`, `for` and `try` in it"
+                )
+                .to_string(),
+                kind: "RuntimeError".to_string(),
+                location: Some(file_location(&ye.location, env)),
+                value: None,
+            }),
+            token::Expression::ForExpression(fe) => {
+                let iterable = eval(WN::E(fe.iterable), env);
+                if is_error(&iterable) {
+                    return iterable;
+                }
+
+                let mut sequence = match seqs::Sequence::new(iterable, env) {
+                    Ok(sequence) => sequence,
+                    Err(err) => return err,
+                };
+
+                loop {
+                    let item = match sequence.next(env) {
+                        Ok(Some(item)) => item,
+                        Ok(None) => break NIL,
+                        Err(err) => return err,
+                    };
+
+                    // Every iteration gets its own bindings.
+                    let mut loop_env = environment::Environment::new_enclosed_environment(env.clone());
+                    if let Err(err) = bind_pattern(fe.pattern.clone(), item, &mut loop_env) {
+                        return err;
+                    }
+
+                    let result = eval(WN::B(fe.body.clone()), &mut loop_env);
+                    if is_error(&result) {
+                        return result;
+                    }
+                }
+            }
+            token::Expression::MacroLiteral(_) => {
+                new_error("macro can only be defined by a top-level `let`".to_string())
             }
             token::Expression::CallExpression(ce) => {
-                let fun = eval(WN::E(ce.function), env);
+                // `quote` gets its argument as code, not as a value.
+                let expression = token::Expression::CallExpression(ce);
+                if let Some(node) = special_form_argument(&expression, "quote") {
+                    return quote(node.clone(), env);
+                }
+                let ce = match expression {
+                    token::Expression::CallExpression(ce) => ce,
+                    _ => unreachable!(),
+                };
+
+                // `receiver.name(args)` is a method call, not a call of a field.
+                let (fun, receiver) = match ce.function {
+                    token::Expression::FieldAccess(fa) => {
+                        let receiver = eval(WN::E(fa.left), env);
+                        if is_error(&receiver) {
+                            return receiver;
+                        }
+                        // Functions of a module are called as they are.
+                        if let object::Object::Module(module) = &receiver {
+                            (module_member(module, &fa.field.value), None)
+                        } else if let Some(method) = find_method(&receiver, &fa.field.value, env) {
+                            (method, Some(receiver))
+                        } else {
+                            // Struct field may hold a function as well.
+                            let field = match &receiver {
+                                object::Object::Struct(st) => st.get(&fa.field.value).cloned(),
+                                _ => None,
+                            };
+                            match field {
+                                Some(field) => (field, None),
+                                None => {
+                                    return new_error(format!(
+                                        "unknown method `{}` for {}",
+                                        fa.field.value,
+                                        receiver.object_type()
+                                    ))
+                                }
+                            }
+                        }
+                    }
+                    function => (eval(WN::E(function), env), None),
+                };
                 if is_error(&fun) {
                     return fun;
                 }
@@ -274,7 +529,149 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
                     return args[0].clone();
                 }
 
-                apply_function(fun, args)
+                let named_args = match eval_named_arguments(ce.named_arguments, env) {
+                    Ok(named_args) => named_args,
+                    Err(err) => return err,
+                };
+
+                let args = match receiver {
+                    Some(receiver) => std::iter::once(receiver).chain(args).collect(),
+                    None => args,
+                };
+
+                apply_function(fun, args, named_args, env)
+            }
+            token::Expression::MatchExpression(me) => {
+                let subject = eval(WN::E(me.subject), env);
+                if is_error(&subject) {
+                    return subject;
+                }
+
+                for arm in me.arms {
+                    // Every arm gets its own scope, so bindings from the arm
+                    // which didn't match never leak into the next one.
+                    let mut arm_env = environment::Environment::new_enclosed_environment(env.clone());
+
+                    if bind_pattern(arm.pattern, subject.clone(), &mut arm_env).is_err() {
+                        continue;
+                    }
+
+                    if let Some(guard) = arm.guard {
+                        let passed = eval(WN::E(guard), &mut arm_env);
+                        if is_error(&passed) {
+                            return passed;
+                        }
+                        if !is_truthy(passed) {
+                            continue;
+                        }
+                    }
+
+                    return eval(WN::B(arm.body), &mut arm_env);
+                }
+
+                new_error(format!("no match arm for value: {}", subject.inspect()))
+            }
+            token::Expression::ThrowExpression(te) => {
+                let value = eval(WN::E(te.value), env);
+                if is_error(&value) {
+                    return value;
+                }
+
+                match thrown_error(value, env) {
+                    Ok(mut err) => {
+                        if err.location.is_none() {
+                            err.location = Some(file_location(&te.location, env));
+                        }
+                        object::Object::Error(err)
+                    }
+                    Err(err) => err,
+                }
+            }
+            token::Expression::TryExpression(te) => {
+                let mut result = eval(WN::B(te.body), env);
+
+                if let (object::Object::Error(err), Some((pattern, block))) = (&result, te.catch) {
+                    // Catch block has its own scope for the caught error.
+                    let mut catch_env = environment::Environment::new_enclosed_environment(env.clone());
+                    result = match bind_pattern(pattern, caught_error(err.clone()), &mut catch_env) {
+                        Ok(()) => eval(WN::B(block), &mut catch_env),
+                        Err(err) => err,
+                    };
+                }
+
+                // Finally runs on errors and `return` as well. Its value is
+                // dropped, unless it fails or returns by itself.
+                if let Some(block) = te.finally {
+                    match eval(WN::B(block), env) {
+                        err @ object::Object::Error(_) => return err,
+                        val @ object::Object::ReturnValue(_) => return val,
+                        _ => (),
+                    }
+                }
+
+                result
+            }
+            token::Expression::PropagateExpression(pe) => {
+                let value = eval(WN::E(pe.value), env);
+                if is_error(&value) {
+                    return value;
+                }
+
+                match value {
+                    object::Object::Variant(mut variant)
+                        if variant.type_name == "Result" || variant.type_name == "Option" =>
+                    {
+                        match variant.tag.as_ref() {
+                            "Ok" | "Some" => variant.values.remove(0),
+                            // Goes up like `return` until the enclosing function.
+                            _ => object::Object::ReturnValue(Box::new(object::ReturnValue {
+                                value: object::Object::Variant(variant),
+                            })),
+                        }
+                    }
+                    _ => new_error(format!(
+                        "`?` expects Result or Option, got {}",
+                        value.object_type()
+                    )),
+                }
+            }
+            token::Expression::PipeExpression(pe) => {
+                let piped = eval(WN::E(pe.left), env);
+                if is_error(&piped) {
+                    return piped;
+                }
+
+                // `x |> f(a, b)` is applied as `f(x, a, b)`, anything
+                // else on the right is called with `x` alone.
+                let (function, arguments, named_arguments) = match pe.right {
+                    token::Expression::CallExpression(ce) => {
+                        (ce.function, ce.arguments, ce.named_arguments)
+                    }
+                    function => (function, None, Vec::new()),
+                };
+
+                let fun = eval(WN::E(function), env);
+                if is_error(&fun) {
+                    return fun;
+                }
+
+                let mut args = match arguments {
+                    Some(args) => eval_expressions(args, env),
+                    None => Vec::new(),
+                };
+
+                if args.len() == 1 && is_error(&args[0]) {
+                    return args[0].clone();
+                }
+
+                args.insert(0, piped);
+
+                let named_args = match eval_named_arguments(named_arguments, env) {
+                    Ok(named_args) => named_args,
+                    Err(err) => return err,
+                };
+
+                apply_function(fun, args, named_args, env)
             }
         },
     }
@@ -286,7 +683,7 @@ pub fn eval(node: WN, env: &mut environment::Environment) -> object::Object {
 // ************************************************
 // ************************************************
 
-fn is_truthy(cond: object::Object) -> bool {
+pub fn is_truthy(cond: object::Object) -> bool {
     match cond {
         NIL => false,
         TRUE => true,
@@ -295,48 +692,87 @@ fn is_truthy(cond: object::Object) -> bool {
     }
 }
 
-fn is_error(potential_error: &object::Object) -> bool {
-    match potential_error {
-        object::Object::Error(_) => true,
-        _ => false,
-    }
+// Return value made by `?` interrupts evaluation of an expression
+// the same way error does, so it's treated as one on its way up
+// to the enclosing function.
+pub fn is_error(potential_error: &object::Object) -> bool {
+    matches!(potential_error, object::Object::Error(_) | object::Object::ReturnValue(_))
 }
 
 pub fn new_error(formated_string: String) -> object::Object {
     object::Object::Error(object::Error {
         message: formated_string,
+        kind: "RuntimeError".to_string(),
+        location: None,
+        value: None,
     })
 }
 
+// "line:column" of the statement, with the file name if there is one.
+fn statement_location(
+    statement: &token::Statements,
+    env: &environment::Environment,
+) -> Option<String> {
+    statement.location().map(|location| file_location(location, env))
+}
+
+pub fn file_location(location: &str, env: &environment::Environment) -> String {
+    match env.file() {
+        Some(file) => format!("{}:{}", file.display(), location),
+        None => location.to_string(),
+    }
+}
+
+fn module_member(module: &object::Module, name: &str) -> object::Object {
+    match module.members.get(name) {
+        Some(member) => member.clone(),
+        None => new_error(format!(
+            "module \"{}\" does not export `{}`",
+            module.path, name
+        )),
+    }
+}
+
+// Error remembers the first statement it went through.
+fn locate_error(mut err: object::Error, location: Option<String>) -> object::Object {
+    if err.location.is_none() {
+        err.location = location;
+    }
+    object::Object::Error(err)
+}
+
 pub fn eval_program(program: ast::Program, env: &mut environment::Environment) -> object::Object {
     // TODO: wow, impressive, I see your skill
     let statements = program.statements;
+    if statements.is_empty() {
+        return NIL;
+    }
+
     let mut statements = statements.into_iter();
     let mut size = statements.len();
 
-    let result = loop {
-        size = size - 1;
+    loop {
+        size -= 1;
         let statement = match statements.next() {
             Some(statement) => statement,
             None => panic!("eval_statement is badly broken"),
         };
 
+        let location = statement_location(&statement, env);
         let result = eval(WN::S(statement), env);
 
         // if statement is rendered into Return Value we have to
         // interupt the execution and return this value.
         match result.clone() {
             object::Object::ReturnValue(ret_val) => break ret_val.value,
-            err @ object::Object::Error(_) => break err,
+            object::Object::Error(err) => break locate_error(err, location),
             _ => (),
         };
 
         if size == 0 {
             break result;
         };
-    };
-
-    return result;
+    }
 }
 
 pub fn eval_block_statement(
@@ -345,16 +781,21 @@ pub fn eval_block_statement(
 ) -> object::Object {
     // TODO: wow, impressive, I see your skill
     // Maybe smth like this `iter.take_while(Result::is_ok).last().map(Result::unwrap)` ?
+    if statements.is_empty() {
+        return NIL;
+    }
+
     let mut statements = statements.into_iter();
     let mut size = statements.len();
 
-    let result = loop {
-        size = size - 1;
+    loop {
+        size -= 1;
         let statement = match statements.next() {
             Some(statement) => statement,
             None => panic!("eval_statement is badly broken"),
         };
 
+        let location = statement_location(&statement, env);
         let result = eval(WN::S(statement), env);
 
         // if statement is rendered into Return Value we have to
@@ -362,16 +803,163 @@ pub fn eval_block_statement(
         match result.clone() {
             // Do not unwrap return value. It will be unwraped at highest scope.
             val @ object::Object::ReturnValue(_) => break val,
-            err @ object::Object::Error(_) => break err,
+            object::Object::Error(err) => break locate_error(err, location),
             _ => (),
         };
 
         if size == 0 {
             break result;
         };
+    }
+}
+
+pub fn eval_infix_expression(
+    operator: &str,
+    left: object::Object,
+    right: object::Object,
+    env: &environment::Environment,
+) -> object::Object {
+    // User types implement operators with protocols,
+    // the left operand decides which implementation is used.
+    let protocol = match operator {
+        "+" => Some(("Add", "add")),
+        "-" => Some(("Sub", "sub")),
+        "*" => Some(("Mul", "mul")),
+        "/" => Some(("Div", "div")),
+        "==" | "!=" => Some(("Eq", "eq")),
+        "<" | ">" | "<=" | ">=" => Some(("Ord", "compare")),
+        _ => None,
+    };
+    let method = protocol.and_then(|(protocol, name)| find_protocol_method(&left, protocol, name, env));
+
+    if let Some(method) = method {
+        let type_name = left.object_type();
+        let result = apply_function(method, vec![left, right], Vec::new(), env);
+        if is_error(&result) {
+            return result;
+        }
+
+        let boolean = |value| object::Object::Boolean(object::Boolean { value });
+
+        return match (operator, result) {
+            ("==", result) => boolean(is_truthy(result)),
+            ("!=", result) => boolean(!is_truthy(result)),
+            ("<", object::Object::Integer(ordering)) => boolean(ordering.value < 0),
+            (">", object::Object::Integer(ordering)) => boolean(ordering.value > 0),
+            ("<=", object::Object::Integer(ordering)) => boolean(ordering.value <= 0),
+            (">=", object::Object::Integer(ordering)) => boolean(ordering.value >= 0),
+            ("<", result) | (">", result) | ("<=", result) | (">=", result) => new_error(format!(
+                "`compare` for {} must return INTEGER, got {}",
+                type_name,
+                result.object_type()
+            )),
+            (_, result) => result,
+        };
+    }
+
+    // Equality is defined for every pair of values. Values of different
+    // types are never equal, so `1 == "1"` is just `false`.
+    match operator {
+        "==" => return object::Object::Boolean(object::Boolean { value: left == right }),
+        "!=" => return object::Object::Boolean(object::Boolean { value: left != right }),
+        _ => (),
+    }
+
+    match (left, right) {
+        (object::Object::Integer(left_obj), object::Object::Integer(right_obj)) => {
+            eval_integer_infix_expression(operator, left_obj.value, right_obj.value)
+        }
+        (object::Object::Stringl(left_str), object::Object::Stringl(right_str)) => {
+            match operator {
+                "+" => object::Object::Stringl(object::Stringl {
+                    value: format!("{}{}", left_str.value, right_str.value),
+                }),
+                "<" => object::Object::Boolean(object::Boolean {
+                    value: left_str.value < right_str.value,
+                }),
+                ">" => object::Object::Boolean(object::Boolean {
+                    value: left_str.value > right_str.value,
+                }),
+                "<=" => object::Object::Boolean(object::Boolean {
+                    value: left_str.value <= right_str.value,
+                }),
+                ">=" => object::Object::Boolean(object::Boolean {
+                    value: left_str.value >= right_str.value,
+                }),
+                _ => new_error(format!("unknown operator: STRING {} STRING", operator)),
+            }
+        }
+        (object::Object::Char(left_char), object::Object::Char(right_char)) => match operator {
+            "<" => object::Object::Boolean(object::Boolean {
+                value: left_char.value < right_char.value,
+            }),
+            ">" => object::Object::Boolean(object::Boolean {
+                value: left_char.value > right_char.value,
+            }),
+            "<=" => object::Object::Boolean(object::Boolean {
+                value: left_char.value <= right_char.value,
+            }),
+            ">=" => object::Object::Boolean(object::Boolean {
+                value: left_char.value >= right_char.value,
+            }),
+            _ => new_error(format!("unknown operator: CHAR {} CHAR", operator)),
+        },
+        (left, right) => {
+            if left.same_tag(&right) {
+                new_error(format!(
+                    "unknown operator: {} {} {}",
+                    left.object_type(),
+                    operator,
+                    right.object_type()
+                ))
+            } else {
+                new_error(format!(
+                    "type mismatch: {} {} {}",
+                    left.object_type(),
+                    operator,
+                    right.object_type()
+                ))
+            }
+        }
+    }
+}
+
+fn eval_integer_infix_expression(operator: &str, left: i32, right: i32) -> object::Object {
+    let integer = |value| object::Object::Integer(object::Integer { value });
+    let boolean = |value| object::Object::Boolean(object::Boolean { value });
+    let checked = |value: Option<i32>| match value {
+        Some(value) => integer(value),
+        None => new_error(format!("integer overflow: {} {} {}", left, operator, right)),
     };
 
-    return result;
+    match operator {
+        "+" => checked(left.checked_add(right)),
+        "-" => checked(left.checked_sub(right)),
+        "*" => checked(left.checked_mul(right)),
+        "/" | "%" if right == 0 => new_error("division by zero".to_string()),
+        // `i32::MIN / -1` doesn't fit either.
+        "/" => checked(left.checked_div(right)),
+        "%" => checked(left.checked_rem(right)),
+        "**" => {
+            if right < 0 {
+                return new_error(format!("negative exponent: {} ** {}", left, right));
+            }
+            checked(left.checked_pow(right as u32))
+        }
+        "&" => integer(left & right),
+        "|" => integer(left | right),
+        "^" => integer(left ^ right),
+        "<<" | ">>" if !(0..32).contains(&right) => {
+            new_error(format!("shift out of range: {} {} {}", left, operator, right))
+        }
+        "<<" => integer(left << right),
+        ">>" => integer(left >> right),
+        "<" => boolean(left < right),
+        ">" => boolean(left > right),
+        "<=" => boolean(left <= right),
+        ">=" => boolean(left >= right),
+        _ => new_error(format!("unknown operator: INTEGER {} INTEGER", operator)),
+    }
 }
 
 fn eval_expressions(
@@ -397,51 +985,759 @@ fn eval_expressions(
     }
 }
 
-fn apply_function(fun: object::Object, args: Vec<object::Object>) -> object::Object {
+fn eval_named_arguments(
+    named_arguments: Vec<(token::Identifier, token::Expression)>,
+    env: &mut environment::Environment,
+) -> Result<Vec<(String, object::Object)>, object::Object> {
+    let mut evaluated = Vec::new();
+
+    for (name, expression) in named_arguments {
+        let value = eval(WN::E(expression), env);
+        if is_error(&value) {
+            return Err(value);
+        }
+        evaluated.push((name.value, value));
+    }
+
+    Ok(evaluated)
+}
+
+pub fn apply_function(
+    fun: object::Object,
+    args: Vec<object::Object>,
+    named_args: Vec<(String, object::Object)>,
+    env: &environment::Environment,
+) -> object::Object {
     match fun {
         object::Object::Function(fun) => {
-            let mut extended_env = extend_function_env(fun.clone(), args);
+            let mut extended_env = match extend_function_env((*fun).clone(), args, named_args) {
+                Ok(env) => env,
+                Err(err) => return err,
+            };
+            if fun.generator {
+                return generators::new_generator(fun.body, extended_env);
+            }
             let evaluated = eval(WN::B(fun.body), &mut extended_env);
             unwrap_return_value(evaluated)
         }
-        object::Object::CoreFunc(fun) => fun.call(args),
+        object::Object::CoreFunc(fun) => {
+            if !named_args.is_empty() {
+                return new_error(format!(
+                    "named arguments are not supported by `{}`",
+                    fun.function_name
+                ));
+            }
+            fun.call(args, &core::funcs::Context::new(env))
+        }
+        object::Object::StructType(struct_type) => construct_struct(struct_type, args, named_args),
+        // Variant fields are filled the same way as struct ones.
+        object::Object::VariantConstructor(constructor) => {
+            let struct_type = object::StructType {
+                name: constructor.tag.clone(),
+                fields: constructor.fields,
+                file: constructor.file.clone(),
+            };
+            match construct_struct(struct_type, args, named_args) {
+                object::Object::Struct(st) => object::Object::Variant(object::Variant {
+                    type_name: constructor.type_name,
+                    tag: constructor.tag,
+                    values: st.fields.into_iter().map(|(_, value)| value).collect(),
+                    file: constructor.file,
+                }),
+                err => err,
+            }
+        }
         _ => new_error(format!("not a function: {}", fun.object_type())),
     }
 }
 
-fn extend_function_env(
-    fun: object::Function,
-    args: Vec<object::Object>,
-) -> environment::Environment {
-    let mut env = environment::Environment::new_enclosed_environment(fun.env);
-    match fun.parameters {
-        Some(params) => {
-            for (param, arg) in params.into_iter().zip(args.into_iter()) {
-                env.set(param.value, arg);
-            }
-            env
-        }
-        None => env,
+// Methods are looked up by the receiver's type: first the ones
+// attached with `impl`, then core functions taking the receiver.
+fn find_method(
+    receiver: &object::Object,
+    name: &str,
+    env: &environment::Environment,
+) -> Option<object::Object> {
+    if let Some(method) = env.get_method(&receiver.type_key(), name) {
+        return Some(method);
     }
+
+    let ctx = core::funcs::Context::new(env);
+    core::funcs::CORE_REGISTRY
+        .get(name)
+        .filter(|native| native.is_method_of(receiver, &ctx))
+        .and_then(|_| object::CoreFunc::try_new(name.to_string()))
 }
 
-fn unwrap_return_value(obj: object::Object) -> object::Object {
-    match obj {
-        object::Object::ReturnValue(ret_val) => ret_val.value,
-        _ => obj,
+pub fn find_protocol_method(
+    object: &object::Object,
+    protocol: &str,
+    name: &str,
+    env: &environment::Environment,
+) -> Option<object::Object> {
+    let type_key = object.type_key();
+
+    if env.implements(&type_key, protocol) {
+        env.get_method(&type_key, name)
+    } else {
+        None
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::evaluation;
-    use crate::lexer;
-    use crate::parser;
-    use std::collections::HashMap;
+// Impl of a protocol must define exactly the methods of the protocol.
+fn check_protocol_impl(
+    protocol: &str,
+    type_name: &str,
+    methods: &[(String, object::Object)],
+) -> Result<(), object::Object> {
+    let required = match core::funcs::PROTOCOL_REGISTRY.get(protocol) {
+        Some(required) => required,
+        None => return Err(new_error(format!("unknown protocol `{}`", protocol))),
+    };
 
-    #[test]
-    fn test_eval_integer_expression() {
-        let pairs = vec![("1".to_string(), 1), ("2".to_string(), 2)];
+    for (name, method) in methods {
+        let arity = match required.iter().find(|(required_name, _)| required_name == name) {
+            Some((_, arity)) => *arity,
+            None => {
+                return Err(new_error(format!(
+                    "`{}` is not a method of protocol {}",
+                    name, protocol
+                )))
+            }
+        };
+
+        let accepts_arity = match method {
+            object::Object::Function(fun) => {
+                let parameters = fun.parameters.clone().unwrap_or_default();
+                let mandatory = parameters
+                    .iter()
+                    .filter(|parameter| parameter.default.is_none() && !parameter.variadic)
+                    .count();
+                let variadic = parameters.iter().any(|parameter| parameter.variadic);
+                mandatory <= arity && (variadic || arity <= parameters.len())
+            }
+            object::Object::CoreFunc(fun) => fun.native().arity().accepts(arity),
+            _ => false,
+        };
+
+        if !accepts_arity {
+            return Err(new_error(format!(
+                "method `{}` of {} must take {} arguments",
+                name, protocol, arity
+            )));
+        }
+    }
+
+    for (name, _) in required {
+        if !methods.iter().any(|(method_name, _)| method_name == name) {
+            return Err(new_error(format!(
+                "missing method `{}` in impl {} for {}",
+                name, protocol, type_name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Text of a value as shown to the user, `Display` protocol
+// replaces the default `inspect`, also for values inside
+// arrays, hashes, structs and variants.
+pub fn display(
+    object: &object::Object,
+    env: &environment::Environment,
+) -> Result<String, object::Object> {
+    let method = match find_protocol_method(object, "Display", "show", env) {
+        Some(method) => method,
+        None => return display_parts(object, env),
+    };
+
+    match apply_function(method, vec![object.clone()], Vec::new(), env) {
+        object::Object::Stringl(shown) => Ok(shown.value),
+        err @ object::Object::Error(_) => Err(err),
+        other => Err(new_error(format!(
+            "`show` for {} must return STRING, got {}",
+            object.object_type(),
+            other.object_type()
+        ))),
+    }
+}
+
+// Default text of a value, with its parts shown by `display`.
+fn display_parts(
+    object: &object::Object,
+    env: &environment::Environment,
+) -> Result<String, object::Object> {
+    let join = |values: Vec<String>| values.join(", ");
+    let all = |values: &mut dyn Iterator<Item = &object::Object>| {
+        values
+            .map(|value| display(value, env))
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    match object {
+        object::Object::Array(array) => Ok(format!("[{}]", join(all(&mut array.elements.iter())?))),
+        object::Object::Hash(hash) => {
+            let pairs = hash
+                .pairs
+                .values()
+                .map(|pair| {
+                    let value = display(&pair.value, env)?;
+                    Ok(format!("{}: {}", pair.key.inspect(), value))
+                })
+                .collect::<Result<Vec<_>, object::Object>>()?;
+            Ok(format!("{{{}}}", join(pairs)))
+        }
+        object::Object::Struct(st) if !st.fields.is_empty() => {
+            let fields = st
+                .fields
+                .iter()
+                .map(|(name, value)| Ok(format!("{}: {}", name, display(value, env)?)))
+                .collect::<Result<Vec<_>, object::Object>>()?;
+            Ok(format!("{} {{ {} }}", st.name, join(fields)))
+        }
+        object::Object::Variant(variant) if !variant.values.is_empty() => {
+            Ok(format!("{}({})", variant.tag, join(all(&mut variant.values.iter())?)))
+        }
+        object => Ok(object.inspect()),
+    }
+}
+
+// Argument of `name(argument)`, for calls which are handled
+// before evaluation: `quote` and `unquote`.
+pub fn special_form_argument<'a>(
+    expression: &'a token::Expression,
+    name: &str,
+) -> Option<&'a token::Expression> {
+    match expression {
+        token::Expression::CallExpression(ce) if ce.named_arguments.is_empty() => {
+            match (&ce.function, &ce.arguments) {
+                (token::Expression::Identifier(function), Some(arguments))
+                    if function.value == name && arguments.len() == 1 =>
+                {
+                    Some(&arguments[0])
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// Quote keeps its argument as code, only `unquote(...)` calls
+// inside of it are evaluated and their values put back as code.
+fn quote(node: token::Expression, env: &mut environment::Environment) -> object::Object {
+    let mut unquoter = Unquoter { env, error: None };
+    let node = ast::modify_expression(node, &mut unquoter);
+
+    match unquoter.error {
+        Some(err) => err,
+        None => object::Object::Quote(Box::new(object::Quote { node })),
+    }
+}
+
+struct Unquoter<'a> {
+    env: &'a mut environment::Environment,
+    error: Option<object::Object>,
+}
+
+impl ast::Modifier for Unquoter<'_> {
+    fn expression(&mut self, expression: token::Expression) -> token::Expression {
+        let argument = match special_form_argument(&expression, "unquote") {
+            Some(argument) if self.error.is_none() => argument.clone(),
+            _ => return expression,
+        };
+
+        let value = eval(WN::E(argument), self.env);
+        if is_error(&value) {
+            self.error = Some(value);
+            return expression;
+        }
+
+        match value_to_expression(value) {
+            Ok(node) => node,
+            Err(err) => {
+                self.error = Some(err);
+                expression
+            }
+        }
+    }
+}
+
+// Turns unquoted value back into code.
+fn value_to_expression(value: object::Object) -> Result<token::Expression, object::Object> {
+    let token = |token_type: &str, literal: String| token::Token {
+        token_type: token_type.to_string(),
+        literal,
+    };
+
+    let expression = match value {
+        object::Object::Quote(quote) => quote.node,
+        object::Object::Integer(integer) => {
+            token::Expression::IntegerLiteral(token::IntegerLiteral {
+                token: token(token::INT, integer.value.to_string()),
+                value: integer.value,
+            })
+        }
+        object::Object::Boolean(boolean) => token::Expression::Boolean(token::Boolean {
+            token: token(
+                if boolean.value { token::TRUE } else { token::FALSE },
+                boolean.value.to_string(),
+            ),
+            value: boolean.value,
+        }),
+        object::Object::Stringl(string) => token::Expression::StringLiteral(token::StringLiteral {
+            token: token(token::STRING, string.value.clone()),
+            value: string.value,
+        }),
+        object::Object::Char(ch) => token::Expression::CharLiteral(token::CharLiteral {
+            token: token(token::CHAR, ch.value.to_string()),
+            value: ch.value,
+        }),
+        object::Object::Array(array) => {
+            let elements = array
+                .elements
+                .into_iter()
+                .map(value_to_expression)
+                .collect::<Result<Vec<_>, _>>()?;
+            token::Expression::ArrayLiteral(token::ArrayLiteral {
+                token: token(token::LBRACKET, "[".to_string()),
+                elements,
+            })
+        }
+        value => {
+            return Err(new_error(format!(
+                "cannot unquote {}",
+                value.object_type()
+            )))
+        }
+    };
+
+    Ok(expression)
+}
+
+// Variants without fields are values themselves.
+fn variant_value(type_key: &object::TypeKey, tag: &str, fields: &[String]) -> object::Object {
+    let (file, type_name) = type_key.clone();
+
+    if fields.is_empty() {
+        object::Object::Variant(object::Variant {
+            type_name,
+            tag: tag.to_string(),
+            values: vec![],
+            file,
+        })
+    } else {
+        object::Object::VariantConstructor(object::VariantConstructor {
+            type_name,
+            tag: tag.to_string(),
+            fields: fields.to_vec(),
+            file,
+        })
+    }
+}
+
+// `Result`, `Option` and their variants, unless the name is taken.
+fn builtin_enum_value(name: &str) -> Option<object::Object> {
+    for enum_type in core::funcs::BUILTIN_ENUMS.iter() {
+        if enum_type.name == name {
+            return Some(object::Object::EnumType(enum_type.clone()));
+        }
+
+        for (tag, fields) in enum_type.variants.iter() {
+            if tag == name {
+                return Some(variant_value(&(None, enum_type.name.clone()), tag, fields));
+            }
+        }
+    }
+
+    None
+}
+
+// Caught error is seen by the program as `Error { message: "...",
+// kind: "...", location: "line:column", value: <thrown value> }`.
+pub fn caught_error(err: object::Error) -> object::Object {
+    let string = |value| object::Object::Stringl(object::Stringl { value });
+
+    object::Object::Struct(object::Struct {
+        name: "Error".to_string(),
+        fields: vec![
+            ("message".to_string(), string(err.message)),
+            ("kind".to_string(), string(err.kind)),
+            ("location".to_string(), err.location.map_or(NIL, string)),
+            ("value".to_string(), err.value.map_or(NIL, |value| *value)),
+        ],
+        file: None,
+    })
+}
+
+// Thrown string is the message, caught error is thrown again
+// as it was, anything else becomes an error of its own type.
+fn thrown_error(
+    value: object::Object,
+    env: &environment::Environment,
+) -> Result<object::Error, object::Object> {
+    if let object::Object::Stringl(message) = &value {
+        return Ok(object::Error {
+            message: message.value.clone(),
+            kind: "Error".to_string(),
+            location: None,
+            value: Some(Box::new(value)),
+        });
+    }
+
+    if let object::Object::Struct(st) = &value {
+        if let (
+            "Error",
+            [(_, object::Object::Stringl(message)), (_, object::Object::Stringl(kind)), (_, location), (_, thrown)],
+        ) = (st.name.as_str(), st.fields.as_slice())
+        {
+            return Ok(object::Error {
+                message: message.value.clone(),
+                kind: kind.value.clone(),
+                location: match location {
+                    object::Object::Stringl(location) => Some(location.value.clone()),
+                    _ => None,
+                },
+                value: match thrown {
+                    object::Object::Nil(_) => None,
+                    thrown => Some(Box::new(thrown.clone())),
+                },
+            });
+        }
+    }
+
+    Ok(object::Error {
+        message: display(&value, env)?,
+        kind: value.object_type(),
+        location: None,
+        value: Some(Box::new(value)),
+    })
+}
+
+// Struct constructor takes fields in the declaration order,
+// by name or both: `Point(1, y: 2)`. Every field must be set.
+fn construct_struct(
+    struct_type: object::StructType,
+    args: Vec<object::Object>,
+    named_args: Vec<(String, object::Object)>,
+) -> object::Object {
+    if args.len() > struct_type.fields.len() {
+        return new_error(format!(
+            "wrong number of arguments: got={}, expected={}",
+            args.len() + named_args.len(),
+            struct_type.fields.len()
+        ));
+    }
+
+    let mut values: Vec<Option<object::Object>> = args.into_iter().map(Some).collect();
+    values.resize(struct_type.fields.len(), None);
+
+    for (name, value) in named_args {
+        match struct_type.fields.iter().position(|field| *field == name) {
+            Some(i) if values[i].is_some() => {
+                return new_error(format!("field `{}` passed twice", name))
+            }
+            Some(i) => values[i] = Some(value),
+            None => {
+                return new_error(format!(
+                    "unknown field `{}` for {}",
+                    name, struct_type.name
+                ))
+            }
+        }
+    }
+
+    let mut fields = Vec::with_capacity(values.len());
+    for (name, value) in struct_type.fields.into_iter().zip(values) {
+        match value {
+            Some(value) => fields.push((name, value)),
+            None => {
+                return new_error(format!(
+                    "missing field `{}` for {}",
+                    name, struct_type.name
+                ))
+            }
+        }
+    }
+
+    object::Object::Struct(object::Struct {
+        name: struct_type.name,
+        fields,
+        file: struct_type.file,
+    })
+}
+
+// Arguments are bound to parameters in this order:
+// 1. positional arguments fill parameters from left to right,
+//    leftovers go to the rest parameter (if there is one);
+// 2. named arguments fill parameters with the same name;
+// 3. parameters which are still empty get their default value,
+//    defaults are evaluated in the function scope, so they can
+//    refer to the parameters before them.
+fn extend_function_env(
+    fun: object::Function,
+    args: Vec<object::Object>,
+    named_args: Vec<(String, object::Object)>,
+) -> Result<environment::Environment, object::Object> {
+    let mut env = environment::Environment::new_enclosed_environment(fun.env);
+    let mut params = fun.parameters.unwrap_or_default();
+
+    let rest = match params.last() {
+        Some(param) if param.variadic => params.pop(),
+        _ => None,
+    };
+
+    let required = params.iter().filter(|p| p.default.is_none()).count();
+    let got = args.len() + named_args.len();
+    let expected = match (&rest, required == params.len()) {
+        (Some(_), _) => format!("at least {}", required),
+        (None, true) => required.to_string(),
+        (None, false) => format!("{}..{}", required, params.len()),
+    };
+    let wrong_number_of_arguments = || {
+        new_error(format!(
+            "wrong number of arguments: got={}, expected={}",
+            got, expected
+        ))
+    };
+
+    if rest.is_none() && args.len() > params.len() {
+        return Err(wrong_number_of_arguments());
+    }
+
+    let mut args = args.into_iter();
+    let mut slots: Vec<Option<object::Object>> = args.by_ref().take(params.len()).map(Some).collect();
+    slots.resize(params.len(), None);
+
+    for (name, value) in named_args {
+        let position = params.iter().position(|param| match &param.pattern {
+            token::Pattern::Identifier(identifier) => identifier.value == name,
+            _ => false,
+        });
+
+        match position {
+            Some(i) if slots[i].is_some() => {
+                return Err(new_error(format!("argument `{}` passed twice", name)))
+            }
+            Some(i) => slots[i] = Some(value),
+            None => return Err(new_error(format!("unknown named argument `{}`", name))),
+        }
+    }
+
+    if params
+        .iter()
+        .zip(slots.iter())
+        .any(|(param, slot)| slot.is_none() && param.default.is_none())
+    {
+        return Err(wrong_number_of_arguments());
+    }
+
+    for (param, slot) in params.into_iter().zip(slots) {
+        let value = match (slot, param.default) {
+            (Some(value), _) => value,
+            (None, Some(default)) => {
+                let value = eval(WN::E(default), &mut env);
+                if is_error(&value) {
+                    return Err(value);
+                }
+                value
+            }
+            (None, None) => unreachable!("missing arguments are checked above"),
+        };
+
+        bind_pattern(param.pattern, value, &mut env)?;
+    }
+
+    if let Some(rest) = rest {
+        let elements = args.collect();
+        bind_pattern(
+            rest.pattern,
+            object::Object::Array(object::Array { elements }),
+            &mut env,
+        )?;
+    }
+
+    Ok(env)
+}
+
+// Binds every name mentioned in the pattern to the matching part of the value.
+// If the value doesn't have the shape pattern expects we return an error object,
+// names bound before the mismatch stay in the environment.
+pub fn bind_pattern(
+    pattern: token::Pattern,
+    value: object::Object,
+    env: &mut environment::Environment,
+) -> Result<(), object::Object> {
+    match pattern {
+        token::Pattern::Identifier(identifier) => {
+            env.set(identifier.value, value);
+            Ok(())
+        }
+        token::Pattern::Wildcard(_) => Ok(()),
+        token::Pattern::Literal(literal) => {
+            let description = literal.to_string();
+            let expected = eval(WN::E(literal), env);
+            if expected == value {
+                Ok(())
+            } else {
+                Err(new_error(format!(
+                    "pattern {} doesn't match value {}",
+                    description,
+                    value.inspect()
+                )))
+            }
+        }
+        token::Pattern::ArrayPattern(ap) => {
+            let description = ap.to_string();
+            let elements = match value {
+                object::Object::Array(array) => array.elements,
+                _ => {
+                    return Err(new_error(format!(
+                        "cannot destructure {} with array pattern {}",
+                        value.object_type(),
+                        description
+                    )))
+                }
+            };
+
+            let expected = ap.elements.len();
+            if elements.len() < expected || (ap.rest.is_none() && elements.len() > expected) {
+                let at_least = if ap.rest.is_some() { "at least " } else { "" };
+                return Err(new_error(format!(
+                    "array pattern {} expects {}{} elements, got {}",
+                    description,
+                    at_least,
+                    expected,
+                    elements.len()
+                )));
+            }
+
+            for (element_pattern, element) in ap.elements.into_iter().zip(elements.iter()) {
+                bind_pattern(element_pattern, element.clone(), env)?;
+            }
+
+            // Shares the elements, so `[x, ...xs]` doesn't copy the array.
+            if let Some(rest) = ap.rest {
+                let elements = elements.skip(expected);
+                env.set(rest.value, object::Object::Array(object::Array { elements }));
+            }
+
+            Ok(())
+        }
+        token::Pattern::VariantPattern(vp) => {
+            let description = vp.to_string();
+            // Type the name stands for here, values of another type with
+            // the same name don't match. Names of other modules are not
+            // in scope, their values are matched by the name alone.
+            let resolved = match env.get(vp.name.value.clone()) {
+                Some(value) => Some(value.clone()),
+                None => builtin_enum_value(&vp.name.value),
+            };
+            let expected = match resolved {
+                Some(object::Object::VariantConstructor(vc)) if vc.tag == vp.name.value => {
+                    Some((vc.file, vc.type_name))
+                }
+                Some(object::Object::Variant(variant)) if variant.tag == vp.name.value => {
+                    Some((variant.file, variant.type_name))
+                }
+                Some(object::Object::StructType(st)) if st.name == vp.name.value => Some((st.file, st.name)),
+                _ => None,
+            };
+            let same_type = expected.is_none_or(|expected| expected == value.type_key());
+
+            let values = match value {
+                object::Object::Variant(variant) if same_type && variant.tag == vp.name.value => {
+                    variant.values
+                }
+                object::Object::Struct(st) if same_type && st.name == vp.name.value => {
+                    st.fields.into_iter().map(|(_, value)| value).collect()
+                }
+                _ => {
+                    return Err(new_error(format!(
+                        "pattern {} doesn't match value {}",
+                        description,
+                        value.inspect()
+                    )))
+                }
+            };
+
+            if values.len() != vp.fields.len() {
+                return Err(new_error(format!(
+                    "pattern {} expects {} fields, got {}",
+                    description,
+                    vp.fields.len(),
+                    values.len()
+                )));
+            }
+
+            for (field_pattern, field_value) in vp.fields.into_iter().zip(values) {
+                bind_pattern(field_pattern, field_value, env)?;
+            }
+
+            Ok(())
+        }
+        token::Pattern::HashPattern(hp) => {
+            let description = hp.to_string();
+            let mut pairs = match value {
+                object::Object::Hash(hash) => hash.pairs,
+                // Struct fields are matched by their names.
+                object::Object::Struct(st) => st
+                    .fields
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let key = object::Object::Stringl(object::Stringl { value: name });
+                        let hash_key = key.hash_key().expect("strings are hashable");
+                        (hash_key, object::HashPair { key, value })
+                    })
+                    .collect(),
+                _ => {
+                    return Err(new_error(format!(
+                        "cannot destructure {} with hash pattern {}",
+                        value.object_type(),
+                        description
+                    )))
+                }
+            };
+
+            for (key, entry_pattern) in hp.entries {
+                match pairs.remove(&object::HashKey::Stringl(key.value.clone())) {
+                    Some(pair) => bind_pattern(entry_pattern, pair.value, env)?,
+                    None => {
+                        return Err(new_error(format!(
+                            "key `{}` not found for hash pattern {}",
+                            key.value, description
+                        )))
+                    }
+                }
+            }
+
+            if let Some(rest) = hp.rest {
+                env.set(rest.value, object::Object::Hash(object::Hash { pairs }));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+pub fn unwrap_return_value(obj: object::Object) -> object::Object {
+    match obj {
+        object::Object::ReturnValue(ret_val) => ret_val.value,
+        _ => obj,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation;
+    use crate::evaluation::object::ObjectT;
+    use crate::parser;
+    use im_rc::vector;
+
+    #[test]
+    fn test_eval_integer_expression() {
+        let pairs = vec![("1".to_string(), 1), ("2".to_string(), 2)];
 
         pairs.into_iter().for_each(|(value, expected)| {
             let evaluated = run_eval(value);
@@ -512,6 +1808,100 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_logical_operators() {
+        let pairs = vec![
+            ("true && true".to_string(), true),
+            ("true && false".to_string(), false),
+            ("false || true".to_string(), true),
+            ("false || false".to_string(), false),
+            ("1 < 2 && 2 < 3".to_string(), true),
+            ("1 > 2 || 2 > 3".to_string(), false),
+            // right side is never evaluated, so no error is produced
+            ("false && unknown_bebe".to_string(), false),
+            ("true || unknown_bebe".to_string(), true),
+        ];
+
+        for (value, expected) in pairs {
+            assert_boolean_object(run_eval(value), expected)
+        }
+
+        // the deciding operand is returned as is
+        let pairs = vec![
+            ("1 && 2".to_string(), 2),
+            ("1 || 2".to_string(), 1),
+            ("if (false) { 1 } || 3".to_string(), 3),
+        ];
+
+        for (value, expected) in pairs {
+            assert_integer_object(run_eval(value), expected)
+        }
+    }
+
+    #[test]
+    fn test_integer_literal_syntax() {
+        let pairs = vec![
+            ("0xFF".to_string(), 255),
+            ("0o755".to_string(), 493),
+            ("0b1010 | 0b0101".to_string(), 15),
+            ("1_000_000 / 1_000".to_string(), 1000),
+            ("-2147483648".to_string(), i32::MIN),
+            ("-2147483647 - 1".to_string(), i32::MIN),
+            ("-0x10".to_string(), -16),
+        ];
+
+        for (expression, expected) in pairs {
+            assert_integer_object(run_eval(expression), expected)
+        }
+    }
+
+    #[test]
+    fn test_extended_integer_operators() {
+        let pairs = vec![
+            ("7 % 3".to_string(), 1),
+            ("2 ** 10".to_string(), 1024),
+            ("2 ** 3 ** 2".to_string(), 512),
+            ("-2 ** 2".to_string(), -4),
+            ("6 & 3".to_string(), 2),
+            ("6 | 3".to_string(), 7),
+            ("6 ^ 3".to_string(), 5),
+            ("1 << 4".to_string(), 16),
+            ("-16 >> 2".to_string(), -4),
+            ("1 + 2 << 1".to_string(), 6),
+        ];
+
+        for (value, expected) in pairs {
+            assert_integer_object(run_eval(value), expected)
+        }
+    }
+
+    #[test]
+    fn test_comparison_operators() {
+        let pairs = vec![
+            ("1 <= 1".to_string(), true),
+            ("2 <= 1".to_string(), false),
+            ("1 >= 1".to_string(), true),
+            ("1 >= 2".to_string(), false),
+            (r###""abc" < "abd""###.to_string(), true),
+            (r###""b" > "abc""###.to_string(), true),
+            (r###""abc" <= "abc""###.to_string(), true),
+            (r###""abc" >= "b""###.to_string(), false),
+            (r###""bebe" == "bebe""###.to_string(), true),
+            (r###""bebe" != "milobe""###.to_string(), true),
+            ("[1, [2, 3]] == [1, [2, 3]]".to_string(), true),
+            ("[1, 2] != [1, 2, 3]".to_string(), true),
+            ("if (false) { 1 } == if (false) { 2 }".to_string(), true),
+            // values of different types are never equal
+            (r###"1 == "1""###.to_string(), false),
+            ("1 != true".to_string(), true),
+            ("[] == if (false) { 1 }".to_string(), false),
+        ];
+
+        for (value, expected) in pairs {
+            assert_boolean_object(run_eval(value), expected)
+        }
+    }
+
     #[test]
     fn test_if_expression() {
         let pairs = vec![
@@ -655,6 +2045,40 @@ mod tests {
                 r###" "Hey" - "Bebe" "###.to_string(),
                 "unknown operator: STRING - STRING".to_string(),
             ),
+            ("1 / 0".to_string(), "division by zero".to_string()),
+            ("1 % 0".to_string(), "division by zero".to_string()),
+            (
+                "2 ** -1".to_string(),
+                "negative exponent: 2 ** -1".to_string(),
+            ),
+            (
+                "2 ** 40".to_string(),
+                "integer overflow: 2 ** 40".to_string(),
+            ),
+            (
+                "let x = -2147483648; x / -1".to_string(),
+                "integer overflow: -2147483648 / -1".to_string(),
+            ),
+            (
+                "let x = -2147483648; x % -1".to_string(),
+                "integer overflow: -2147483648 % -1".to_string(),
+            ),
+            (
+                "-(-2147483647 - 1)".to_string(),
+                "integer overflow: -(-2147483648)".to_string(),
+            ),
+            (
+                "2147483647 + 1".to_string(),
+                "integer overflow: 2147483647 + 1".to_string(),
+            ),
+            (
+                "1 << 32".to_string(),
+                "shift out of range: 1 << 32".to_string(),
+            ),
+            (
+                r###""a" < 1"###.to_string(),
+                "type mismatch: STRING < INTEGER".to_string(),
+            ),
         ];
 
         for (expression, expected) in pairs {
@@ -681,14 +2105,166 @@ mod tests {
     }
 
     #[test]
-    fn test_function_object() {
-        let input = "fn(x) { x + 1; }".to_string();
+    fn test_hash_literal() {
+        let pairs = vec![
+            (r###"{"one": 1, "two": 2}["two"]"###.to_string(), Some(2)),
+            (r###"let key = "one"; {"one": 1}[key]"###.to_string(), Some(1)),
+            ("{1: 10, true: 20}[1]".to_string(), Some(10)),
+            ("{1: 10, true: 20}[true]".to_string(), Some(20)),
+            (r###"{"one": 1}["three"]"###.to_string(), None),
+        ];
 
-        let evaluated = run_eval(input);
+        for (expression, expected) in pairs {
+            let evaluated = run_eval(expression);
+            match expected {
+                Some(val) => assert_integer_object(evaluated, val),
+                None => assert_eq!(evaluated, evaluation::evaluator::NIL),
+            }
+        }
 
-        let fun = match evaluated {
-            evaluation::object::Object::Function(fo) => fo,
-            _ => panic!("expected function object, got {:?}", evaluated),
+        assert_eq!(
+            run_eval(r###"{"b": 2, "a": [1]}"###.to_string()).inspect(),
+            "{a: [1], b: 2}".to_string()
+        );
+    }
+
+    #[test]
+    fn test_destructuring() {
+        let pairs = vec![
+            ("let [a, b] = [1, 2]; a + b".to_string(), 3),
+            ("let [a, ...rest] = [1, 2, 3]; length(rest)".to_string(), 2),
+            ("let [a, ...rest] = [1]; length(rest)".to_string(), 0),
+            ("let [a, [b, c]] = [1, [2, 3]]; a + b + c".to_string(), 6),
+            (
+                r###"let {name, age} = {"name": "bebe", "age": 3}; age"###.to_string(),
+                3,
+            ),
+            (
+                r###"let {age: years} = {"age": 3}; years"###.to_string(),
+                3,
+            ),
+            (
+                r###"let {tags: [first, ...others]} = {"tags": [1, 2, 3]}; first"###.to_string(),
+                1,
+            ),
+            (
+                r###"let {a, ...rest} = {"a": 1, "b": 2}; rest["b"]"###.to_string(),
+                2,
+            ),
+            ("let sum = fn([a, b]) { a + b }; sum([1, 2])".to_string(), 3),
+            (
+                r###"let age = fn({age}) { age }; age({"age": 10})"###.to_string(),
+                10,
+            ),
+        ];
+
+        for (expression, expected) in pairs {
+            assert_integer_object(run_eval(expression), expected)
+        }
+    }
+
+    #[test]
+    fn test_destructuring_errors() {
+        let pairs = vec![
+            (
+                "let [a, b] = 1;".to_string(),
+                "cannot destructure INTEGER with array pattern [a, b]".to_string(),
+            ),
+            (
+                "let [a, b] = [1];".to_string(),
+                "array pattern [a, b] expects 2 elements, got 1".to_string(),
+            ),
+            (
+                "let [a] = [1, 2];".to_string(),
+                "array pattern [a] expects 1 elements, got 2".to_string(),
+            ),
+            (
+                "let [a, b, ...c] = [1];".to_string(),
+                "array pattern [a, b, ...c] expects at least 2 elements, got 1".to_string(),
+            ),
+            (
+                "let {name} = [1];".to_string(),
+                "cannot destructure ARRAY with hash pattern {name}".to_string(),
+            ),
+            (
+                r###"let {name} = {"age": 1};"###.to_string(),
+                "key `name` not found for hash pattern {name}".to_string(),
+            ),
+            (
+                "let f = fn([a, b]) { a }; f([1, 2, 3]);".to_string(),
+                "array pattern [a, b] expects 2 elements, got 3".to_string(),
+            ),
+            (
+                "{[1]: 2}".to_string(),
+                "unusable as hash key: ARRAY".to_string(),
+            ),
+            (
+                "let [1, a] = [2, 3];".to_string(),
+                "pattern 1 doesn't match value 2".to_string(),
+            ),
+        ];
+
+        for (expression, expected) in pairs {
+            match run_eval(expression) {
+                evaluation::object::Object::Error(err) => assert_eq!(err.message, expected),
+                otherwise => panic!("expected error message, got {:?}", otherwise),
+            }
+        }
+    }
+
+    #[test]
+    fn test_match_expression() {
+        let pairs = vec![
+            ("match (1) { 1 => 10, _ => 20 }".to_string(), 10),
+            ("match (2) { 1 => 10, _ => 20 }".to_string(), 20),
+            ("match (-1) { 1 => 10, -1 => 30, _ => 20 }".to_string(), 30),
+            ("match (-2147483648) { -2147483648 => 1, _ => 2 }".to_string(), 1),
+            ("match (255) { 0xFE => 1, 0xFF => 2, _ => 3 }".to_string(), 2),
+            (r###"match ("b") { "a" => 1, "b" => 2, _ => 3 }"###.to_string(), 2),
+            ("match (false) { true => 1, false => 2 }".to_string(), 2),
+            ("match (5) { x => x * 2 }".to_string(), 10),
+            ("match ([1, 2]) { [a] => a, [a, b] => a + b }".to_string(), 3),
+            ("match ([1, 2, 3]) { [] => 0, [first, ...rest] => length(rest) }".to_string(), 2),
+            ("match ([0, 5]) { [0, y] => y, [x, 0] => x, _ => 0 }".to_string(), 5),
+            (
+                r###"match ({"kind": "circle", "r": 3}) {
+                       {kind: "square", side} => side * side,
+                       {kind: "circle", r} => 3 * r * r,
+                     }"###
+                    .to_string(),
+                27,
+            ),
+            ("match (7) { x if x > 10 => 1, x if x > 5 => 2, _ => 3 }".to_string(), 2),
+            ("match (3) { x => { let y = x + 1; y * 2 } }".to_string(), 8),
+            // bindings from unmatched arms don't leak
+            ("let a = 1; match ([2, 3]) { [a, 0] => a, _ => a }".to_string(), 1),
+            (
+                "let f = fn(x) { match (x) { 0 => { return 100; }, _ => 1 }; 2 }; f(0)".to_string(),
+                100,
+            ),
+        ];
+
+        for (expression, expected) in pairs {
+            assert_integer_object(run_eval(expression), expected)
+        }
+
+        match run_eval("match ([1, 2]) { [a] => a, 1 => 2 }".to_string()) {
+            evaluation::object::Object::Error(err) => {
+                assert_eq!(err.message, "no match arm for value: [1, 2]".to_string())
+            }
+            otherwise => panic!("expected error, got {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn test_function_object() {
+        let input = "fn(x) { x + 1; }".to_string();
+
+        let evaluated = run_eval(input);
+
+        let fun = match evaluated {
+            evaluation::object::Object::Function(fo) => fo,
+            _ => panic!("expected function object, got {:?}", evaluated),
         };
 
         let params = match fun.parameters {
@@ -707,8 +2283,101 @@ mod tests {
             ("let id = fn(a) { a; }; id(1);".to_string(), 1),
             ("let id = fn(a) { return a; }; id(1);".to_string(), 1),
             ("let id = fn(a) { a; }(1);".to_string(), 1),
-            // TODO: not a function called
-            // TODO: wrong number of arguments passed
+            ("let add = fn(x, y = 10) { x + y }; add(1)".to_string(), 11),
+            ("let add = fn(x, y = 10) { x + y }; add(1, 2)".to_string(), 3),
+            ("let add = fn(x, y = x * 2) { x + y }; add(3)".to_string(), 9),
+            ("let count = fn(first, ...others) { length(others) }; count(1)".to_string(), 0),
+            ("let count = fn(first, ...others) { length(others) }; count(1, 2, 3)".to_string(), 2),
+            ("let sub = fn(x, y) { x - y }; sub(y: 2, x: 10)".to_string(), 8),
+            ("let sub = fn(x, y) { x - y }; sub(10, y: 2)".to_string(), 8),
+            ("let f = fn(a, b = 2, c = 3) { a * 100 + b * 10 + c }; f(1, c: 9)".to_string(), 129),
+            ("let sub = fn(x, y) { x - y }; 10 |> sub(y: 3)".to_string(), 7),
+        ];
+
+        for (expression, expected) in pairs {
+            assert_integer_object(run_eval(expression), expected)
+        }
+
+        let pairs = vec![
+            (
+                "fn(a) { a }(1, 2)".to_string(),
+                "wrong number of arguments: got=2, expected=1".to_string(),
+            ),
+            (
+                "fn(a, b) { a }(1)".to_string(),
+                "wrong number of arguments: got=1, expected=2".to_string(),
+            ),
+            (
+                "fn(a, b = 1) { a }()".to_string(),
+                "wrong number of arguments: got=0, expected=1..2".to_string(),
+            ),
+            (
+                "fn(a, ...b) { a }()".to_string(),
+                "wrong number of arguments: got=0, expected=at least 1".to_string(),
+            ),
+            (
+                "fn(a, b) { a }(1, a: 2)".to_string(),
+                "argument `a` passed twice".to_string(),
+            ),
+            (
+                "fn(a) { a }(b: 2)".to_string(),
+                "unknown named argument `b`".to_string(),
+            ),
+            (
+                "fn(a = unknown_bebe) { a }()".to_string(),
+                "identifier not found: unknown_bebe".to_string(),
+            ),
+            (
+                "length(x: [])".to_string(),
+                "named arguments are not supported by `length`".to_string(),
+            ),
+            ("1()".to_string(), "not a function: INTEGER".to_string()),
+        ];
+
+        for (expression, expected) in pairs {
+            match run_eval(expression) {
+                evaluation::object::Object::Error(err) => assert_eq!(err.message, expected),
+                otherwise => panic!("expected error message, got {:?}", otherwise),
+            }
+        }
+    }
+
+    #[test]
+    fn test_pipe_expression() {
+        let pairs = vec![
+            ("[1, 2, 3] |> length".to_string(), 3),
+            ("[1, 2, 3] |> rest() |> length()".to_string(), 2),
+            ("[1, 2] |> push(3) |> last".to_string(), 3),
+            ("let add = fn(a, b) { a + b }; 1 |> add(2) |> add(3)".to_string(), 6),
+            ("let inc = fn(x) { x + 1 }; 1 + 1 |> inc".to_string(), 3),
+            ("let arr = [1, 2]; arr |> push(length(arr)) |> last".to_string(), 2),
+        ];
+
+        for (expression, expected) in pairs {
+            assert_integer_object(run_eval(expression), expected)
+        }
+
+        match run_eval("1 |> 2".to_string()) {
+            evaluation::object::Object::Error(err) => {
+                assert_eq!(err.message, "not a function: INTEGER".to_string())
+            }
+            otherwise => panic!("expected error, got {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn test_short_function_syntax() {
+        let pairs = vec![
+            ("let inc = x => x + 1; inc(1)".to_string(), 2),
+            ("let add = (a, b) => a + b; add(1, 2)".to_string(), 3),
+            ("let answer = () => 42; answer()".to_string(), 42),
+            ("let add = x => y => x + y; add(1)(2)".to_string(), 3),
+            ("let f = ([a, b]) => { let c = a * b; c + 1 }; f([2, 3])".to_string(), 7),
+            ("let double = #(_ * 2); double(21)".to_string(), 42),
+            ("let sub = #(_ - _); sub(10, 3)".to_string(), 7),
+            ("[1, 2, 3] |> push(4) |> (arr => length(arr))()".to_string(), 4),
+            ("let apply = fn(f, x) { f(x) }; apply(#(_ + 1), 1)".to_string(), 2),
+            ("match (5) { x if (x > 1) => x, _ => 0 }".to_string(), 5),
         ];
 
         for (expression, expected) in pairs {
@@ -729,6 +2398,739 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_string_interpolation() {
+        let pairs = vec![
+            (r#"let name = "Bebe"; "Hello, ${name}!""#, "Hello, Bebe!"),
+            (r#"let age = 41; "You are ${age + 1}""#, "You are 42"),
+            (r#""n=" + "${1}""#, "n=1"),
+            (r#""${[1, 2]} ${true} ${{"a": 1}}""#, "[1, 2] true {a: 1}"),
+            (r#""${"nested ${1 + 1}"}""#, "nested 2"),
+            (r#"let f = x => "<${x}>"; f("a")"#, "<a>"),
+            (r#""no parts""#, "no parts"),
+            (r##"r#"${raw} "quoted""#"##, r#"${raw} "quoted""#),
+            ("let n = 2; \"\"\"\n    a\n      ${n}\n    \"\"\"", "a\n  2"),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.to_string());
+            match evaluated {
+                evaluation::object::Object::Stringl(string) => {
+                    assert_eq!(string.value, expected.to_string())
+                }
+                _ => panic!("Expected string literal, got {:?}", evaluated),
+            }
+        }
+
+        let evaluated = run_eval(r#""${missing}""#.to_string());
+        match evaluated {
+            evaluation::object::Object::Error(err) => {
+                assert_eq!(err.message, "identifier not found: missing".to_string())
+            }
+            _ => panic!("Expected error, got {:?}", evaluated),
+        }
+    }
+
+    #[test]
+    fn test_chars() {
+        let pairs = vec![
+            (r#"'a'"#, Ok("a")),
+            (r#""héllo"[1]"#, Ok("é")),
+            (r#""abc"[3]"#, Ok("null")),
+            (r#"first("abc")"#, Ok("a")),
+            (r#"last("abc")"#, Ok("c")),
+            (r#"rest("abc")"#, Ok("bc")),
+            (r#"first("")"#, Ok("null")),
+            (r#"chars("añb")"#, Ok("[a, ñ, b]")),
+            (r#"length("añb")"#, Ok("3")),
+            (r#"ord('a')"#, Ok("97")),
+            (r#"chr(0x44)"#, Ok("D")),
+            (r#"chr(ord('a') + 1)"#, Ok("b")),
+            (r#""abc"[0] == 'a'"#, Ok("true")),
+            (r#"'a' == "a""#, Ok("false")),
+            (r#"'a' < 'b'"#, Ok("true")),
+            (r#"{'a': 1}['a']"#, Ok("1")),
+            (r#"match ('\n') { '\t' => 1, '\n' => 2, _ => 3 }"#, Ok("2")),
+            (r#""${'x'}y""#, Ok("xy")),
+            (r#"ord("a")"#, Err("argument to `ord` must be CHAR, got STRING")),
+            (r#"chr(-1)"#, Err("invalid character code: -1")),
+            (r#"chr(55296)"#, Err("invalid character code: 55296")),
+            (r#"'a' + 'b'"#, Err("unknown operator: CHAR + CHAR")),
+            (r#"first(1)"#, Err("argument to `first` must be ARRAY or STRING, got INTEGER")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.to_string());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_structs() {
+        let pairs = vec![
+            ("struct Point { x, y }; Point(1, 2)", Ok("Point { x: 1, y: 2 }")),
+            ("struct Point { x, y }", Ok("struct Point { x, y }")),
+            ("struct Unit {}; Unit()", Ok("Unit {}")),
+            ("struct Point { x, y }; Point(y: 2, x: 1)", Ok("Point { x: 1, y: 2 }")),
+            ("struct Point { x, y }; let p = Point(1, y: 2); p.x + p.y", Ok("3")),
+            ("struct Point { x, y }; let p = Point(1, 2); p with { x: 3 }", Ok("Point { x: 3, y: 2 }")),
+            ("struct Point { x, y }; let p = Point(1, 2); let q = p with { x: 3 }; p.x", Ok("1")),
+            ("struct Point { x, y }; Point(1, 2) == Point(1, 2)", Ok("true")),
+            ("struct Point { x, y }; Point(1, 2) == Point(2, 1)", Ok("false")),
+            ("struct A { x }; struct B { x }; A(1) == B(1)", Ok("false")),
+            ("struct Line { from, to }; struct Point { x, y }; Line(Point(0, 0), Point(1, 1)).to.x", Ok("1")),
+            ("struct Point { x, y }; let {x, y} = Point(3, 4); x * y", Ok("12")),
+            ("struct Point { x, y }; Point(1, 2) |> #(_.y)()", Ok("2")),
+            ("struct Point { x, y }; Point(1)", Err("missing field `y` for Point")),
+            ("struct Point { x, y }; Point(1, 2, 3)", Err("wrong number of arguments: got=3, expected=2")),
+            ("struct Point { x, y }; Point(1, x: 2)", Err("field `x` passed twice")),
+            ("struct Point { x, y }; Point(1, z: 2)", Err("unknown field `z` for Point")),
+            ("struct Point { x, y }; Point(1, 2).z", Err("unknown field `z` for Point")),
+            ("struct Point { x, y }; Point(1, 2) with { z: 1 }", Err("unknown field `z` for Point")),
+            ("[1].x", Err("field access not supported: ARRAY")),
+            ("1 with { x: 1 }", Err("`with` is not supported for INTEGER")),
+            ("struct Point { x, y }; Point(1, 2) + 1", Err("type mismatch: Point + INTEGER")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.to_string());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_enums() {
+        let shapes = "enum Shape { Circle(r), Rect(w, h), Empty };
+            let area = fn(shape) {
+                match (shape) { Circle(r) => 3 * r * r, Rect(w, h) => w * h, Empty => 0 }
+            };";
+
+        let pairs = vec![
+            ("Circle(2)", Ok("Circle(2)")),
+            ("Rect(h: 3, w: 2)", Ok("Rect(2, 3)")),
+            ("Empty", Ok("Empty")),
+            ("Shape", Ok("enum Shape { Circle(r), Rect(w, h), Empty }")),
+            ("Circle", Ok("Shape.Circle(r)")),
+            ("area(Circle(2))", Ok("12")),
+            ("area(Rect(2, 3))", Ok("6")),
+            ("area(Empty)", Ok("0")),
+            ("[Circle(1), Rect(1, 2)] |> length", Ok("2")),
+            ("Circle(1) == Circle(1)", Ok("true")),
+            ("Circle(1) == Circle(2)", Ok("false")),
+            ("Empty == Empty", Ok("true")),
+            ("let Rect(w, h) = Rect(4, 5); w + h", Ok("9")),
+            ("match (Circle(0)) { Circle(0) => \"dot\", Circle(_) => \"circle\" }", Ok("dot")),
+            ("match ([Empty, Circle(3)]) { [Empty, Circle(r)] => r }", Ok("3")),
+            ("struct Point { x, y }; match (Point(1, 2)) { Point(x, y) => x + y }", Ok("3")),
+            ("Circle(1) + 1", Err("type mismatch: Shape + INTEGER")),
+            ("Circle(1) + Empty", Err("unknown operator: Shape + Shape")),
+            ("Circle()", Err("missing field `r` for Circle")),
+            ("Circle(1, 2)", Err("wrong number of arguments: got=2, expected=1")),
+            ("Empty(1)", Err("not a function: Shape")),
+            ("match (Rect(1, 2)) { Circle(r) => r }", Err("no match arm for value: Rect(1, 2)")),
+            ("let Circle(r) = Empty; r", Err("pattern Circle(r) doesn't match value Empty")),
+            ("let Circle(a, b) = Circle(1); a", Err("pattern Circle(a, b) expects 2 fields, got 1")),
+            // Patterns match variants of the enum their name stands for.
+            ("let r = Ok(1); let f = fn(x) { match (x) { Ok(v) => v, _ => 0 } }; enum Other { Ok(v) };
+              [f(r), f(Ok(2)), match (r) { Ok(v) => v, _ => 0 }, match (Ok(3)) { Ok(v) => v }]", Ok("[1, 0, 0, 3]")),
+            ("let e = Empty; enum Other { Empty }; [match (e) { Empty => 1, _ => 0 }, match (Empty) { Empty => 1 }]",
+                Ok("[0, 1]")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(format!("{} {}", shapes, input));
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_method_calls() {
+        let pairs = vec![
+            ("[1, 2, 3].length()", Ok("3")),
+            ("[1, 2, 3].push(4).rest().first()", Ok("2")),
+            ("\"abc\".upper()", Ok("ABC")),
+            ("\"AbC\".lower().chars().last()", Ok("c")),
+            ("{\"a\": 1, \"b\": 2}.keys()", Ok("[a, b]")),
+            ("{\"a\": 1, \"b\": 2}.values()", Ok("[1, 2]")),
+            ("{\"a\": 1}.length()", Ok("1")),
+            ("'a'.ord()", Ok("97")),
+            ("98.chr()", Ok("b")),
+            ("struct Point { x, y };
+              impl Point {
+                  let norm = fn(self) { self.x * self.x + self.y * self.y };
+                  let shift = fn(self, dx, dy = 0) { self with { x: self.x + dx, y: self.y + dy } };
+              };
+              Point(1, 2).shift(2).shift(dy: 1, dx: 0).norm()", Ok("18")),
+            ("struct Point { x, y };
+              impl Point { let double = fn(self) { self.scale(2) } };
+              impl Point { let scale = fn(self, k) { Point(self.x * k, self.y * k) } };
+              Point(1, 2).double()", Ok("Point { x: 2, y: 4 }")),
+            ("enum Shape { Circle(r), Square(a) };
+              impl Shape {
+                  let area = fn(self) { match (self) { Circle(r) => 3 * r * r, Square(a) => a * a } }
+              };
+              [Circle(1).area(), Square(2).area()]", Ok("[3, 4]")),
+            ("struct Point { x, y }; impl Point { let length = fn(self) { 42 } }; Point(1, 2).length()", Ok("42")),
+            ("struct Counter { step }; Counter(x => x + 1).step(1)", Ok("2")),
+            ("struct Point { x, y }; impl Point { let f = fn(self) { 1 } }; struct Point { x, y }; Point(1, 2).f()",
+                Err("unknown method `f` for Point")),
+            ("[1].upper()", Err("unknown method `upper` for ARRAY")),
+            ("1.length()", Err("unknown method `length` for INTEGER")),
+            ("struct Point { x }; impl Point { let origin = 0 }", Err("method `origin` of Point must be a function, got INTEGER")),
+            ("[1].push()", Err("wrong number of arguments to `push`: got=1, expected=at least 2")),
+            // Methods are the functions taking the receiver first.
+            ("[Ok(1).is_ok(), None.unwrap_or(2), seq([1, 2]).push(3), range(0).rest().first()]", Ok("[true, 2, [1, 2, 3], 1]")),
+            ("\"ab\".push(1)", Err("unknown method `push` for STRING")),
+            ("Some(1).is_ok()", Err("unknown method `is_ok` for Option")),
+            ("range(0).repeat()", Err("unknown method `repeat` for SEQ")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.to_string());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_protocols() {
+        let vector = "struct Vector { x, y };
+            impl Add for Vector { let add = fn(a, b) { Vector(a.x + b.x, a.y + b.y) } };
+            impl Mul for Vector { let mul = fn(a, k) { Vector(a.x * k, a.y * k) } };
+            impl Eq for Vector { let eq = fn(a, b) { a.x + a.y == b.x + b.y } };
+            impl Ord for Vector { let compare = fn(a, b) { a.x - b.x } };
+            impl Display for Vector { let show = fn(v) { \"<${v.x}, ${v.y}>\" } };
+            impl Length for Vector { let length = fn(v) { 2 } };";
+        let range = "struct Range { from, to };
+            impl Iterable for Range { let iter = fn(r) { if (r.from < r.to) { [r.from, r.to] } else { [] } } };";
+
+        let pairs = vec![
+            (format!("{} Vector(1, 2) + Vector(3, 4)", vector), Ok("Vector { x: 4, y: 6 }")),
+            (format!("{} Vector(1, 2) * 3", vector), Ok("Vector { x: 3, y: 6 }")),
+            (format!("{} Vector(1, 2) == Vector(2, 1)", vector), Ok("true")),
+            (format!("{} Vector(1, 2) != Vector(2, 1)", vector), Ok("false")),
+            (format!("{} [Vector(1, 2) < Vector(2, 0), Vector(1, 2) >= Vector(2, 0)]", vector), Ok("[true, false]")),
+            (format!("{} \"v = ${{Vector(1, 2) + Vector(1, 1)}}\"", vector), Ok("v = <2, 3>")),
+            (format!("{} [length(Vector(0, 0)), Vector(0, 0).length()]", vector), Ok("[2, 2]")),
+            (format!("{} Vector(1, 2) - Vector(1, 2)", vector), Err("unknown operator: Vector - Vector")),
+            (format!("{} [length(Range(1, 5)), first(Range(1, 5)), Range(1, 5).last()]", range), Ok("[2, 1, 5]")),
+            (format!("{} Range(1, 5) |> rest", range), Ok("[5]")),
+            (format!("{} Range(5, 1).first()", range), Ok("null")),
+            ("enum Sign { Plus, Minus };
+              impl Display for Sign { let show = fn(s) { match (s) { Plus => \"+\", Minus => \"-\" } } };
+              \"${Plus}${Minus}\"".to_string(), Ok("+-")),
+            (format!("{} let h = {{\"a\": Vector(1, 1)}}; \"${{[Vector(1, 2), Some(Vector(0, 0))]}} ${{h}}\"", vector),
+                Ok("[<1, 2>, Some(<0, 0>)] {a: <1, 1>}")),
+            (format!("{} struct Line {{ from, to }}; \"${{Line(Vector(0, 0), Vector(1, 1))}}\"", vector),
+                Ok("Line { from: <0, 0>, to: <1, 1> }")),
+            ("struct P { x }; impl Eq for P { let eq = fn(a, b) { true } }; struct P { x }; P(1) == P(2)".to_string(),
+                Ok("false")),
+            ("struct P { x }; impl Ord for P { let compare = fn(a, b) { true } }; P(1) < P(2)".to_string(),
+                Err("`compare` for P must return INTEGER, got BOOLEAN")),
+            ("struct P { x }; impl Display for P { let show = fn(p) { p.x } }; \"${P(1)}\"".to_string(),
+                Err("`show` for P must return STRING, got INTEGER")),
+            ("struct P { x }; impl Iterable for P { let iter = fn(p) { p.x } }; first(P(1))".to_string(),
+                Err("`iter` for P must return ARRAY, GENERATOR or SEQ, got INTEGER")),
+            ("struct P { x }; impl Hash for P { let hash = fn(p) { 1 } }".to_string(),
+                Err("unknown protocol `Hash`")),
+            ("struct P { x }; impl Add for P { let plus = fn(a, b) { a } }".to_string(),
+                Err("`plus` is not a method of protocol Add")),
+            ("struct P { x }; impl Add for P {}".to_string(),
+                Err("missing method `add` in impl Add for P")),
+            ("struct P { x }; impl Add for P { let add = fn(a) { a } }".to_string(),
+                Err("method `add` of Add must take 2 arguments")),
+            ("struct P { x }; impl Add for P { let add = fn(a, b, c = 0) { a.x + b } }; P(1) + 2".to_string(),
+                Ok("3")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.clone());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_exceptions() {
+        let pairs = vec![
+            ("try { throw \"boom\" } catch (e) { e.message }", Ok("boom")),
+            ("try { 1 } catch (e) { 2 }", Ok("1")),
+            ("try { [1][\"a\"] } catch (e) { [e.kind, e.message] }",
+                Ok("[RuntimeError, index operator not supported: ARRAY]")),
+            ("try { throw \"boom\" } catch ({kind, location}) { [kind, location] }", Ok("[Error, 1:7]")),
+            ("let f = fn() {\n  let a = 1;\n  a + true\n};\ntry { f() } catch (e) { e.location }", Ok("3:3")),
+            ("try { try { throw \"inner\" } catch (e) { throw e } } catch (e) { [e.message, e.location] }",
+                Ok("[inner, 1:13]")),
+            ("struct NotFound { key }; try { throw NotFound(\"a\") } catch (e) { [e.kind, e.message, e.value.key] }",
+                Ok("[NotFound, NotFound { key: a }, a]")),
+            ("struct NotFound { key }; try { try { throw NotFound(\"a\") } catch (e) { throw e } } catch ({value}) { value }",
+                Ok("NotFound { key: a }")),
+            ("try { throw \"boom\" } catch (e) { e.value }", Ok("boom")),
+            ("try { 1 / 0 } catch (e) { e.value }", Ok("null")),
+            ("let f = fn(x) { if (x < 0) { throw \"negative\" } else { x } };
+              [f(1), try { f(-1) } catch (e) { 0 }]", Ok("[1, 0]")),
+            ("let f = fn() { try { return 1 } finally { throw \"cleanup\" } }; try { f() } catch (e) { e.message }",
+                Ok("cleanup")),
+            ("let f = fn() { try { return 1 } finally { 2 }; 3 }; f()", Ok("1")),
+            ("let f = fn() { try { throw \"a\" } catch (e) { return e.message } finally { 0 }; 3 }; f()", Ok("a")),
+            ("let f = fn() { try { throw \"a\" } finally { return 2 } }; f()", Ok("2")),
+            ("try { 1 } finally { 2 }", Ok("1")),
+            ("try { } catch (e) { 1 }", Ok("null")),
+            ("let e = 1; try { throw \"x\" } catch (e) { e }; e", Ok("1")),
+            ("try { throw \"boom\" } finally { 1 }", Err("boom")),
+            ("throw 1 + 2", Err("3")),
+            ("try { throw \"a\" } catch ([x]) { x }", Err("cannot destructure Error with array pattern [x]")),
+            ("try { throw \"a\" } catch (e) { throw e.message + \"b\" }", Err("ab")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.to_string());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_result_and_option() {
+        let parse = "let parse = fn(s) { match (s) { \"1\" => Ok(1), \"2\" => Ok(2), _ => Err(\"bad: ${s}\") } };";
+        let lookup = "let lookup = fn(h, k) { if (h[k]) { Some(h[k]) } else { None } };";
+
+        let pairs = vec![
+            (format!("{} [parse(\"1\"), parse(\"x\")]", parse), Ok("[Ok(1), Err(bad: x)]")),
+            (format!("{} let sum = fn(a, b) {{ Ok(parse(a)? + parse(b)?) }}; [sum(\"1\", \"2\"), sum(\"1\", \"z\")]", parse),
+                Ok("[Ok(3), Err(bad: z)]")),
+            (format!("{} let f = fn(h) {{ let a = lookup(h, \"a\")?; Some(a * 10) }}; [f({{\"a\": 1}}), f({{}})]", lookup),
+                Ok("[Some(10), None]")),
+            (format!("{} let f = fn(a) {{ let xs = [parse(a)?, 0]; xs }}; f(\"q\")", parse), Ok("Err(bad: q)")),
+            (format!("{} parse(\"x\")?; 5", parse), Ok("Err(bad: x)")),
+            (format!("{} parse(\"2\")? * 5", parse), Ok("10")),
+            ("[is_ok(Ok(1)), is_ok(Err(1)), Err(1).is_err(), Some(1).is_some(), None.is_none(), is_none(Some(1))]".to_string(),
+                Ok("[true, false, true, true, true, false]")),
+            ("[Ok(1).unwrap_or(0), Err(1).unwrap_or(0), unwrap_or(Some(2), 0), None.unwrap_or(0)]".to_string(),
+                Ok("[1, 0, 2, 0]")),
+            ("[Err(1).map_err(fn(e) { e + 1 }), Ok(1).map_err(fn(e) { e + 1 })]".to_string(), Ok("[Err(2), Ok(1)]")),
+            ("Err(\"a\") |> map_err(#(_ + \"b\"))".to_string(), Ok("Err(ab)")),
+            ("match (Some(3)) { Some(x) => x, None => 0 }".to_string(), Ok("3")),
+            ("[Result, Option]".to_string(), Ok("[enum Result { Ok(value), Err(error) }, enum Option { Some(value), None }]")),
+            ("enum Answer { Ok, No }; [Ok, Some(1)]".to_string(), Ok("[Ok, Some(1)]")),
+            ("1?".to_string(), Err("`?` expects Result or Option, got INTEGER")),
+            ("unwrap_or(1, 2)".to_string(), Err("argument to `unwrap_or` must be Result or Option, got INTEGER")),
+            ("is_ok(Some(1))".to_string(), Err("argument to `is_ok` must be Result, got Option")),
+            ("map_err(None, fn(e) { e })".to_string(), Err("argument to `map_err` must be Result, got Option")),
+            ("Err(1).map_err(fn(e) { e + true })".to_string(), Err("type mismatch: INTEGER + BOOLEAN")),
+            ("Ok(1, 2)".to_string(), Err("wrong number of arguments: got=2, expected=1")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.clone());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_quote_unquote() {
+        let pairs = vec![
+            ("quote(5)", Ok("QUOTE(5)")),
+            ("quote(5 + 8)", Ok("QUOTE((5 + 8))")),
+            ("quote(foobar + barfoo)", Ok("QUOTE((foobar + barfoo))")),
+            ("quote(unquote(4 + 4))", Ok("QUOTE(8)")),
+            ("quote(8 + unquote(4 + 4))", Ok("QUOTE((8 + 8))")),
+            ("let foo = 8; quote(unquote(foo) + 1)", Ok("QUOTE((8 + 1))")),
+            ("quote(unquote(true == false))", Ok("QUOTE(false)")),
+            ("quote(unquote([1, \"a\", 'b']))", Ok("QUOTE([1, a, 'b'])")),
+            ("let q = quote(4 + 4); quote(unquote(q) * 2)", Ok("QUOTE(((4 + 4) * 2))")),
+            ("quote(fn(x) { x + unquote(1 + 2) })", Ok("QUOTE(fn(x){(x + 3)})")),
+            ("quote(1) == quote(1)", Ok("true")),
+            ("quote(unquote(fn(x) { x }))", Err("cannot unquote FUNCTION")),
+            ("quote(unquote(missing))", Err("identifier not found: missing")),
+            ("macro(x) { x }", Err("macro can only be defined by a top-level `let`")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.to_string());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_macros() {
+        let unless = "let unless = macro(cond, consequence, alternative) {
+            quote(if (!(unquote(cond))) { unquote(consequence) } else { unquote(alternative) })
+        };";
+        let swap = "let swap = macro(a, b) { quote(fn(tmp, other) { [other, tmp] }(unquote(a), unquote(b))) };";
+
+        let pairs = vec![
+            (format!("{} unless(10 > 5, \"not greater\", \"greater\")", unless), Ok("greater")),
+            (format!("{} let x = 1; unless(x == 1, throw \"not evaluated\", x + 1)", unless), Ok("2")),
+            ("let reverse = macro(a, b) { quote(unquote(b) - unquote(a)) }; reverse(2 + 2, 10 - 5)".to_string(), Ok("1")),
+            ("let infix = macro(a) { let op = quote(unquote(a) * 2); quote(unquote(op) + 1) }; infix(3)".to_string(), Ok("7")),
+            // Names bound by the quoted code don't capture the arguments.
+            (format!("{} let tmp = 1; let other = 2; swap(tmp, other)", swap), Ok("[2, 1]")),
+            ("let assert_eq = macro(left, right) {
+                  quote(if (unquote(left) == unquote(right)) { true } else { throw \"assertion failed\" })
+              };
+              [assert_eq(1 + 1, 2), try { assert_eq(1, 2) } catch (e) { e.message }]".to_string(),
+                Ok("[true, assertion failed]")),
+            ("let double = macro(x) { quote(unquote(x) * 2) };
+              let quadruple = macro(x) { quote(double(double(unquote(x)))) };
+              quadruple(3)".to_string(), Ok("12")),
+            ("let m = macro(x) { quote(fn(x) { x }(unquote(x))) }; let y = 5; m(y)".to_string(), Ok("5")),
+            ("let m = macro() { quote(let_free) }; let let_free = 3; m()".to_string(), Ok("3")),
+            (format!("{} unless(true, 1)", unless), Err("wrong number of arguments to macro `unless`: got=2, expected=3")),
+            ("let m = macro(x) { 1 }; m(2)".to_string(), Err("macro `m` must return a quote, got INTEGER")),
+            ("let m = macro(x) { quote(m(unquote(x))) }; m(1)".to_string(),
+                Err("macro `m` is expanded too deep, does it expand into itself?")),
+            ("let m = macro(x) { x }; m(x: 1)".to_string(), Err("named arguments are not supported by macro `m`")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.clone());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_generators() {
+        let nat = "struct Nat { start };
+            impl Nat { let from = fn*(self, i) { yield i; for (x in self.from(i + 1)) { yield x } } };";
+
+        let pairs = vec![
+            ("let g = fn*() { yield 1; yield 2 }(); [next(g), g.next(), next(g)]".to_string(),
+                Ok("[Some(1), Some(2), None]")),
+            ("let g = fn*() { 1 }(); [g, next(g)]".to_string(), Ok("[generator, None]")),
+            ("let f = fn(x) { if (x > 0) { yield x } else { yield 0 }; for (y in [1, 2]) { yield x + y } };
+              [collect(rest(f(5))), length(f(-1)), last(f(1))]".to_string(), Ok("[[6, 7], 3, 3]")),
+            // `rest` of a generator doesn't run it.
+            ("let g = fn*() { yield 1; throw \"too far\" }(); [rest(g), first(g)]".to_string(), Ok("[seq, 1]")),
+            ("let g = fn*() { for (i in range(0)) { yield i } }; first(rest(rest(g())))".to_string(), Ok("2")),
+            // Body runs only when the next value is asked for.
+            ("let g = fn*() { yield 1; throw \"too far\" }(); first(g)".to_string(), Ok("1")),
+            ("let g = fn*() { yield 1; throw \"too far\" }(); next(g); try { next(g) } catch (e) { e.message }".to_string(),
+                Ok("too far")),
+            ("let g = fn*() { yield 1; return 2; yield 3 }(); [next(g), next(g), next(g)]".to_string(),
+                Ok("[Some(1), None, None]")),
+            (format!("{} let g = Nat(0).from(0); next(g); next(g); first(g)", nat), Ok("2")),
+            (format!("{} let f = fn(g) {{ for (x in g) {{ if (x * x > 50) {{ return x }} }} }}; f(Nat(0).from(1))", nat),
+                Ok("8")),
+            ("let g = fn*(a) { let b = a * 2; yield b; let c = b + 1; yield c }(5); [first(g), first(g)]".to_string(),
+                Ok("[10, 11]")),
+            ("let g = fn*() { yield 1 }(); g == g".to_string(), Ok("true")),
+            ("next(1)".to_string(), Err("argument to `next` must be GENERATOR, got INTEGER")),
+            ("let g = fn*() { let x = 1 + yield 2; }(); next(g)".to_string(),
+                Err("`yield` can only be a statement of a generator, or of `if`, `match`, `for` and `try` in it")),
+            ("let g = fn*() { yield 1 + true }(); next(g)".to_string(), Err("type mismatch: INTEGER + BOOLEAN")),
+            // Endless generators need no recursion.
+            ("let squares = fn*() { for (i in range(0)) { yield i * i } }; collect(take(squares(), 4))".to_string(),
+                Ok("[0, 1, 4, 9]")),
+            ("let g = fn*(xs) { for (x in xs) { match (x) { Some(v) if v > 1 => yield v, Some(v) => yield -v, None => 0 } } };
+              collect(g([Some(1), None, Some(2)]))".to_string(), Ok("[-1, 2]")),
+            ("let g = fn*() { try { yield 1; throw \"x\"; yield 2 } catch (e) { yield e.message } finally { yield 3 }; yield 4 };
+              collect(g())".to_string(), Ok("[1, x, 3, 4]")),
+            ("let g = fn*() { try { yield 1; return 0 } finally { yield 2 }; yield 3 }; collect(g())".to_string(),
+                Ok("[1, 2]")),
+            ("let g = fn*() { try { throw \"boom\" } finally { yield 1 } }(); [next(g), try { next(g) } catch (e) { e.message }]"
+                .to_string(), Ok("[Some(1), boom]")),
+            ("let g = fn*() { try { try { throw \"a\" } finally { yield 1 } } catch (e) { yield e.message } }; collect(g())"
+                .to_string(), Ok("[1, a]")),
+            ("let g = fn*() { match (1) { 2 => yield 2 } }(); next(g)".to_string(), Err("no match arm for value: 1")),
+            // Scopes are the same as without `yield`.
+            ("let g = fn*() { let x = 0; for (i in [1, 2]) { let x = i * 10; yield x }; yield x }; collect(g())"
+                .to_string(), Ok("[10, 20, 0]")),
+            ("let g = fn*() { for (i in [1, 2]) { if (i == 2) { yield y }; let y = i } }; collect(g())".to_string(),
+                Err("identifier not found: y")),
+            ("let g = fn*() { for (i in [1]) { yield i }; yield i }; collect(g())".to_string(),
+                Err("identifier not found: i")),
+            ("let g = fn*() { match (Some(1)) { Some(v) => yield v }; yield v }; collect(g())".to_string(),
+                Err("identifier not found: v")),
+            ("let g = fn*() { let e = 0; try { throw \"x\" } catch (e) { yield e.message }; yield e }; collect(g())"
+                .to_string(), Ok("[x, 0]")),
+            ("let g = fn*() { let x = 0; try { for (i in [1]) { let x = 1; throw \"a\" } } catch (e) { yield x } };
+              collect(g())".to_string(), Ok("[0]")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.clone());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_for_expression() {
+        let pairs = vec![
+            ("let f = fn(xs) { for (x in xs) { if (x > 2) { return x } }; 0 }; [f([1, 2, 3, 4]), f([])]", Ok("[3, 0]")),
+            ("let f = fn(s) { for (c in s) { if (c == 'b') { return true } }; false }; [f(\"abc\"), f(\"\")]",
+                Ok("[true, false]")),
+            ("let f = fn() { for ([k, v] in [[1, 2], [3, 4]]) { if (k == 3) { return v } } }; f()", Ok("4")),
+            ("let f = fn() { for (x in fn*() { yield 1; yield 2 }()) { if (x == 2) { return x * 10 } } }; f()",
+                Ok("20")),
+            ("for (x in [1]) { x }", Ok("null")),
+            ("let x = 5; for (x in [1]) { x }; x", Ok("5")),
+            ("struct Pair { a, b }; impl Iterable for Pair { let iter = fn(p) { [p.a, p.b] } };
+              let f = fn(p) { for (x in p) { if (x > 1) { return x } } }; f(Pair(1, 2))", Ok("2")),
+            ("for (x in 5) { x }", Err("cannot iterate over INTEGER")),
+            ("for (x in [1, true]) { x + 1 }", Err("type mismatch: BOOLEAN + INTEGER")),
+            ("struct Box { v }; impl Iterable for Box { let iter = fn(b) { b.v } }; for (x in Box(1)) { x }",
+                Err("`iter` for Box must return ARRAY, GENERATOR or SEQ, got INTEGER")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.to_string());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_seqs() {
+        let nat = "struct Nat { start };
+            impl Nat { let from = fn*(self, i) { yield i; for (x in self.from(i + 1)) { yield x } } };";
+
+        let pairs = vec![
+            ("map([1, 2, 3], fn(x) { x * 2 })".to_string(), Ok("seq")),
+            ("collect(map([1, 2, 3], fn(x) { x * 2 }))".to_string(), Ok("[2, 4, 6]")),
+            ("[1, 2, 3, 4].filter(fn(x) { x % 2 == 0 }).collect()".to_string(), Ok("[2, 4]")),
+            ("[take([1, 2, 3], 2).collect(), take([1], 5).collect(), drop([1, 2, 3], 2).collect(), drop([1], 5).collect()]"
+                .to_string(), Ok("[[1, 2], [1], [3], []]")),
+            ("[1, 2, 3, 1].take_while(fn(x) { x < 3 }).collect()".to_string(), Ok("[1, 2]")),
+            ("zip([1, 2, 3], \"ab\").collect()".to_string(), Ok("[[1, a], [2, b]]")),
+            ("enumerate(\"ab\").collect()".to_string(), Ok("[[0, a], [1, b]]")),
+            ("chain([1], seq([2, 3])).collect()".to_string(), Ok("[1, 2, 3]")),
+            ("flat_map([1, 2, 3], fn(x) { take([x, x, x], x) }).collect()".to_string(), Ok("[1, 2, 2, 3, 3, 3]")),
+            ("map([1, 2], Some).collect()".to_string(), Ok("[Some(1), Some(2)]")),
+            // Sequences are walked from the start every time.
+            ("let s = map([1, 2], fn(x) { x + 1 }); [collect(s), collect(s), length(s), last(s)]".to_string(),
+                Ok("[[2, 3], [2, 3], 2, 3]")),
+            ("let s = seq([1, 2, 3]); [first(rest(rest(s))), first(rest(rest(rest(s)))), collect(rest(s))]".to_string(),
+                Ok("[3, null, [2, 3]]")),
+            // Values are evaluated only when asked for.
+            ("let s = map([1, 0], fn(x) { 10 / x }); first(s)".to_string(), Ok("10")),
+            ("[1, 2, 3].map(fn(x) { if (x > 1) { throw \"too far\" }; x }).take(1).collect()".to_string(), Ok("[1]")),
+            (format!("{} Nat(0).from(1).map(fn(x) {{ x * x }}).filter(fn(x) {{ x % 2 == 1 }}).take(3).collect()", nat),
+                Ok("[1, 9, 25]")),
+            (format!("{} let f = fn(s) {{ for ([i, x] in s) {{ if (i == 2) {{ return x }} }} }}; f(Nat(0).from(5).enumerate())", nat),
+                Ok("7")),
+            ("struct Pair { a, b }; impl Iterable for Pair { let iter = fn(p) { seq([p.a, p.b]) } };
+              [Pair(1, 2).map(fn(x) { x * 10 }).collect(), length(Pair(1, 2)), collect(rest(Pair(1, 2)))]".to_string(),
+                Ok("[[10, 20], 2, [2]]")),
+            ("map(1, fn(x) { x })".to_string(), Err("argument to `map` must be iterable, got INTEGER")),
+            ("map([1], 1)".to_string(), Err("argument to `map` must be a function, got INTEGER")),
+            ("take([1], -1)".to_string(), Err("argument to `take` must not be negative, got -1")),
+            ("drop([1], true)".to_string(), Err("argument to `drop` must be INTEGER, got BOOLEAN")),
+            ("map([1, true], fn(x) { x + 1 }).collect()".to_string(), Err("type mismatch: BOOLEAN + INTEGER")),
+            ("zip([1])".to_string(), Err("wrong number of arguments to `zip`: got=1, expected=2")),
+            ("[collect(range(2, 5)), collect(range(5, 2)), first(drop(range(0), 1000))]".to_string(),
+                Ok("[[2, 3, 4], [], 1000]")),
+            ("collect(take(iterate(1, fn(x) { x * 2 }), 5))".to_string(), Ok("[1, 2, 4, 8, 16]")),
+            ("collect(zip(repeat(0), \"ab\"))".to_string(), Ok("[[0, a], [0, b]]")),
+            ("first(iterate(1, fn(x) { x + true }))".to_string(), Ok("1")),
+            ("collect(take(iterate(1, fn(x) { x + true }), 2))".to_string(), Err("type mismatch: INTEGER + BOOLEAN")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.clone());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_higher_order_functions() {
+        let nat = "struct Nat { start };
+            impl Nat { let from = fn*(self, i) { yield i; for (x in self.from(i + 1)) { yield x } } };";
+
+        let pairs = vec![
+            ("reduce([1, 2, 3], 0, fn(sum, x) { sum + x })".to_string(), Ok("6")),
+            ("[1, 2, 3].reduce([], fn(acc, x) { push(acc, x * x) })".to_string(), Ok("[1, 4, 9]")),
+            ("reduce(\"abc\", \"\", fn(acc, c) { \"${c}${acc}\" })".to_string(), Ok("cba")),
+            ("sort_by([3, 1, 2], fn(x) { x })".to_string(), Ok("[1, 2, 3]")),
+            // Values with equal keys keep their order.
+            ("[\"bb\", \"a\", \"cc\", \"d\"].sort_by(fn(s) { length(s) })".to_string(), Ok("[a, d, bb, cc]")),
+            ("struct V { n }; impl Ord for V { let compare = fn(a, b) { b.n - a.n } };
+              sort_by([1, 3, 2], fn(n) { V(n) })".to_string(), Ok("[3, 2, 1]")),
+            ("let total = 0; [each([1, 2], fn(x) { let total = total + x; total }), total]".to_string(), Ok("[null, 0]")),
+            ("[any([1, 2], fn(x) { x > 1 }), any([], fn(x) { true }), all([1, 2], fn(x) { x > 1 }), all([], fn(x) { false })]"
+                .to_string(), Ok("[true, false, false, true]")),
+            ("[find([1, 2, 3], fn(x) { x > 1 }), [1].find(fn(x) { x > 1 })]".to_string(), Ok("[Some(2), None]")),
+            ("let limit = 2; find(map([1, 2, 3], fn(x) { x * 10 }), fn(x) { x > limit })".to_string(), Ok("Some(10)")),
+            // Infinite values are fine as long as the answer is found.
+            (format!("{} [any(Nat(0).from(0), fn(x) {{ x > 5 }}), find(Nat(0).from(0), fn(x) {{ x * x > 50 }})]", nat),
+                Ok("[true, Some(8)]")),
+            ("struct Pair { a, b }; impl Iterable for Pair { let iter = fn(p) { [p.a, p.b] } };
+              Pair(1, 2).reduce(10, fn(sum, x) { sum + x })".to_string(), Ok("13")),
+            ("Err(1).map_err(fn(e) { e + 1 })".to_string(), Ok("Err(2)")),
+            ("each([1, 2], fn(x) { if (x > 1) { throw \"stop\" } })".to_string(), Err("stop")),
+            ("reduce(1, 0, fn(sum, x) { sum })".to_string(), Err("argument to `reduce` must be iterable, got INTEGER")),
+            ("find([1], 2)".to_string(), Err("argument to `find` must be a function, got INTEGER")),
+            ("reduce([1], 0)".to_string(), Err("wrong number of arguments to `reduce`: got=2, expected=3")),
+            ("sort_by([[1], [2]], fn(x) { x })".to_string(), Err("unknown operator: ARRAY < ARRAY")),
+            ("all([1, 2], fn(x) { x + true })".to_string(), Err("type mismatch: INTEGER + BOOLEAN")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.clone());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => {
+                    assert_eq!(err.message, expected.to_string())
+                }
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_native_registry() {
+        let pairs = vec![
+            ("doc(push)", Ok("push(array, value, ...values)\nArray with the values added at its end.")),
+            ("doc(enumerate)", Ok("enumerate(values, start?)\nLazy sequence of `[index, value]` pairs, indices count from `start` or 0.")),
+            ("[push([1], 2, 3, 4), [1].push(2, 3)]", Ok("[[1, 2, 3, 4], [1, 2, 3]]")),
+            ("collect(enumerate([\"a\", \"b\"], 1))", Ok("[[1, a], [2, b]]")),
+            ("collect(chain([1], \"ab\", take([3, 4], 1)))", Ok("[1, a, b, 3]")),
+            ("doc(1)", Err("argument to `doc` must be CORE_FUNCTION, got INTEGER")),
+            ("push([1], 2, 3).push()", Err("wrong number of arguments to `push`: got=1, expected=at least 2")),
+            ("enumerate([1], 0, 1)", Err("wrong number of arguments to `enumerate`: got=3, expected=1..2")),
+            ("enumerate([1], true)", Err("argument to `enumerate` must be INTEGER, got BOOLEAN")),
+            ("chain([1], [2], 3)", Err("argument to `chain` must be iterable, got INTEGER")),
+        ];
+
+        for (input, expected) in pairs {
+            let evaluated = run_eval(input.to_string());
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err(expected)) => assert_eq!(err.message, expected),
+                (evaluation::object::Object::Error(err), Ok(_)) => {
+                    panic!("unexpected error for {}: {}", input, err.message)
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_persistent_collections() {
+        // Copies share elements, but every value stays as it was made.
+        let pairs = vec![
+            ("let a = [1, 2, 3]; let b = push(a, 4); let c = push(a, 5); [a, b, c]",
+                "[[1, 2, 3], [1, 2, 3, 4], [1, 2, 3, 5]]"),
+            ("let a = [1, 2, 3]; [rest(a), rest(rest(a)), rest([]), a]", "[[2, 3], [3], [], [1, 2, 3]]"),
+            ("let a = [1, 2, 3]; let [x, ...xs] = a; [x, xs, push(xs, 4), a]", "[1, [2, 3], [2, 3, 4], [1, 2, 3]]"),
+            ("let a = [1]; let f = fn() { a }; let a = push(a, 2); [f(), a]", "[[1], [1, 2]]"),
+            ("let h = {\"a\": 1, \"b\": [1]}; let {b} = h; [push(b, 2), h]",
+                "[[1, 2], {a: 1, b: [1]}]"),
+            ("let a = [1, 2]; [push(a, 3) == [1, 2, 3], a == [1, 2], {1: a} == {1: [1, 2]}]", "[true, true, true]"),
+        ];
+
+        for (input, expected) in pairs {
+            assert_eq!(run_eval(input.to_string()).inspect(), expected.to_string(), "{}", input);
+        }
+    }
+
     #[test]
     fn test_core_functions1() {
         let pairs = vec![
@@ -736,11 +3138,11 @@ mod tests {
             ("length(\"bebe\")".to_string(), Ok(4)),
             (
                 "length(1)".to_string(),
-                Err("argument to `length` not supported, got INTEGER".to_string()),
+                Err("argument to `length` must be STRING, ARRAY or HASH, got INTEGER".to_string()),
             ),
             (
                 "length(\"bebe\", \"milobe\")".to_string(),
-                Err("wrong number of arguments: got=2, expected=1".to_string()),
+                Err("wrong number of arguments to `length`: got=2, expected=1".to_string()),
             ),
             // additional test case for arrays
             ("length([1,2,3,4])".to_string(), Ok(4)),
@@ -752,7 +3154,7 @@ mod tests {
             match result {
                 Ok(int) => assert_integer_object(run_eval(expression), int),
                 Err(err) => match run_eval(expression) {
-                    evaluation::object::Object::Error(evaluation::object::Error { message }) => {
+                    evaluation::object::Object::Error(evaluation::object::Error { message, .. }) => {
                         assert_eq!(message, err)
                     }
                     _ => panic!("Expected error, got {:?}", err),
@@ -767,7 +3169,7 @@ mod tests {
             (
                 "rest([1,2,3,4])".to_string(),
                 evaluation::object::Object::Array(evaluation::object::Array {
-                    elements: vec![
+                    elements: vector![
                         evaluation::object::Object::Integer(evaluation::object::Integer {
                             value: 2,
                         }),
@@ -783,7 +3185,7 @@ mod tests {
             (
                 "push([1,2,3,4], 5)".to_string(),
                 evaluation::object::Object::Array(evaluation::object::Array {
-                    elements: vec![
+                    elements: vector![
                         evaluation::object::Object::Integer(evaluation::object::Integer {
                             value: 1,
                         }),
@@ -810,20 +3212,117 @@ mod tests {
         }
     }
 
-    fn run_eval(source_code: String) -> evaluation::object::Object {
-        let lexer = lexer::Lexer::new(source_code);
-        let mut parser = parser::Parser::new(lexer);
+    #[test]
+    fn test_modules() {
+        let root = std::env::temp_dir().join(format!("clojurium_modules_{}", std::process::id()));
+        let files = [
+            ("lib/util.clm", "export let twice = fn(x) { x * 2 };"),
+            (
+                "lib/math.clm",
+                "import \"util\" as u;
+                 export let square = fn(x) { x * x };
+                 export let [one, two] = [1, 2];
+                 export struct Point { x, y }
+                 export enum Shape { Circle(r), Empty }
+                 let hidden = 1;
+                 export let quad = fn(x) { u.twice(square(x)) };
+                 export let fail = fn() { 1 + true };",
+            ),
+            ("cycle/a.clm", "import \"b\" as b;"),
+            ("cycle/b.clm", "import \"a\" as a;"),
+            ("broken.clm", "import \"lib/util\" as"),
+            // Parser panics on it.
+            ("typo.clm", "export let f = fn(x) { if (x { 1 } };"),
+            ("lib/warn.clm", "export let f = fn(x) { match (x) { y => 1, 2 => 2 } };"),
+            ("lib/fail.clm", "export let boom = fn() {\n  throw \"bad\"\n};\nexport let gen = fn*() { [yield 1] };"),
+            // Same type names, each with methods of its own.
+            (
+                "types/a.clm",
+                "export struct Point { x }
+                 impl Point { let name = fn(p) { \"a\" } }
+                 impl Display for Point { let show = fn(p) { \"a${p.x}\" } }
+                 export let make = fn(x) { Point(x) };",
+            ),
+            (
+                "types/b.clm",
+                "export struct Point { x }
+                 impl Point { let name = fn(p) { \"b\" } }
+                 export let make = fn(x) { Point(x) };",
+            ),
+        ];
+        for (name, source) in files.iter() {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, source).unwrap();
+        }
 
-        let mut lambda_parsers = parser::LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-        lambda_parsers.register_parsers();
+        let pairs = vec![
+            ("import \"lib/math\" as m; [m.square(3), m.one, m.two, m.quad(2)]", Ok("[9, 1, 2, 8]")),
+            ("import \"lib/math\" as m; let c = m.Circle(2); match (c) { Circle(r) => r, Empty => 0 }", Ok("2")),
+            ("import \"lib/math\" as m; m.Point(1, 2)", Ok("Point { x: 1, y: 2 }")),
+            ("import \"lib/math\" as m; import \"./lib/math.clm\" as again; m == again", Ok("true")),
+            ("import \"lib/math\" as m; m.hidden", Err(("module \"lib/math\" does not export `hidden`", "main.clm:1:25"))),
+            ("import \"lib/math\" as m; m.u", Err(("module \"lib/math\" does not export `u`", "main.clm:1:25"))),
+            ("import \"lib/math\" as m;\nm.fail()", Err(("type mismatch: INTEGER + BOOLEAN", "lib/math.clm:8:43"))),
+            ("import \"types/a\" as a; import \"types/b\" as b;
+              [a.make(1).name(), b.make(1).name(), \"${a.make(2)}\", \"${b.make(2)}\", a.make(1) == b.make(1)]",
+                Ok("[a, b, a2, Point { x: 2 }, false]")),
+            ("import \"types/a\" as a; struct Point { x }; impl Point { let name = fn(p) { \"main\" } };
+              [a.make(1).name(), Point(1).name()]", Ok("[a, main]")),
+            ("import \"types/a\" as a; struct Point { x };
+              [match (a.make(1)) { Point(x) => x, _ => 0 }, match (Point(2)) { Point(x) => x }]", Ok("[0, 2]")),
+            ("import \"lib/fail\" as f; f.boom()", Err(("bad", "lib/fail.clm:2:3"))),
+            ("import \"lib/fail\" as f; next(f.gen())",
+                Err(("`yield` can only be a statement of a generator, or of `if`, `match`, `for` and `try` in it", "lib/fail.clm:4:27"))),
+            ("import \"cycle/a\" as a;", Err(("import cycle: \"cycle/a\" -> \"b\" -> \"a\"", "cycle/b.clm:1:1"))),
+            ("import \"missing\" as m;", Err(("cannot import \"missing\": No such file or directory (os error 2)", "main.clm:1:1"))),
+            ("import \"broken\" as m;", Err(("cannot load \"broken\": expected next token to be IDENT, got EOF instead", "main.clm:1:1"))),
+            ("import \"typo\" as m;", Err(("cannot load \"typo\": I've expected closing `)`, but got {", "main.clm:1:1"))),
+        ];
 
-        let program = parser.parse_program(&lambda_parsers);
+        for (input, expected) in pairs {
+            let main = root.join("main.clm");
+            std::fs::write(&main, input).unwrap();
+
+            let env = evaluation::environment::Environment::new();
+            let evaluated = evaluation::modules::run_file(&main, &env);
+            match (evaluated, expected) {
+                (evaluation::object::Object::Error(err), Err((message, location))) => {
+                    assert_eq!(err.message, message.to_string());
+                    let location = root.join(location).display().to_string();
+                    assert_eq!(err.location, Some(location));
+                }
+                (evaluated, Ok(expected)) => assert_eq!(evaluated.inspect(), expected.to_string()),
+                (evaluated, Err(_)) => panic!("expected error for {}, got {:?}", input, evaluated),
+            }
+        }
+
+        // Parser warnings are kept for whoever runs the program.
+        let main = root.join("main.clm");
+        std::fs::write(&main, "import \"lib/warn\" as w; w.f(2)").unwrap();
+        let env = evaluation::environment::Environment::new();
+        assert_eq!(evaluation::modules::run_file(&main, &env).inspect(), "1");
+        let warning = format!(
+            "{}: unreachable match arm `2`, pattern `y` above matches every value",
+            root.join("lib/warn.clm").display()
+        );
+        assert_eq!(env.modules().borrow_mut().take_warnings(), vec![warning]);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    fn run_eval(source_code: String) -> evaluation::object::Object {
+        let (mut program, _) = parser::parse_source(&source_code, parser::builtin_enums());
 
         let mut env = evaluation::environment::Environment::new();
 
+        let mut macro_env = evaluation::environment::Environment::new();
+        evaluation::macro_expansion::define_macros(&mut program, &mut macro_env);
+        let program = match evaluation::macro_expansion::expand_macros(program, &macro_env) {
+            Ok(program) => program,
+            Err(err) => return err,
+        };
+
         evaluation::evaluator::eval(evaluation::evaluator::WN::P(program), &mut env)
     }
 
@@ -0,0 +1,290 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::evaluation::environment;
+use crate::evaluation::evaluator;
+use crate::evaluation::object;
+use crate::evaluation::object::ObjectT;
+use crate::evaluation::seqs;
+use crate::token;
+
+// Body of a generator is not run by `eval` at once. It's walked here
+// statement by statement, so it can stop at `yield` and go on from the
+// same place on the next call. Frames remember where we are in the
+// body, in a branch of `if` or `match`, in a `for` loop and in `try`.
+// So `yield` is a statement of the body or of one of these, anywhere
+// else, e.g. inside of an expression or a nested function, it's an error.
+// As outside of generators, every iteration of `for`, arm of `match`
+// and `catch` has a scope of its own, left when its block is over.
+#[derive(Debug)]
+pub struct GeneratorState {
+    env: environment::Environment,
+    frames: Vec<Frame>,
+}
+
+#[derive(Debug)]
+enum Frame {
+    Block(std::vec::IntoIter<token::Statements>),
+    Loop {
+        pattern: token::Pattern,
+        sequence: Box<seqs::Sequence>,
+        body: token::BlockStatement,
+    },
+    // Body of `try` is the frame above, errors in it stop here.
+    Try {
+        catch: Option<(token::Pattern, token::BlockStatement)>,
+        finally: Option<token::BlockStatement>,
+    },
+    // Error or `return` which goes on once `finally` above is done.
+    Rethrow(object::Object),
+    // Environment to get back to once the block above is over.
+    Scope(environment::Environment),
+}
+
+pub fn new_generator(body: token::BlockStatement, env: environment::Environment) -> object::Object {
+    let state = GeneratorState {
+        env,
+        frames: vec![Frame::Block(body.statements.into_iter())],
+    };
+
+    object::Object::Generator(object::Generator {
+        state: Rc::new(RefCell::new(state)),
+    })
+}
+
+// Runs the generator up to the next `yield` and gives its value,
+// None once the body is over. After an error or `return` the
+// generator is finished as well, the returned value is dropped.
+pub fn resume(generator: &object::Generator) -> Result<Option<object::Object>, object::Object> {
+    let mut state = match generator.state.try_borrow_mut() {
+        Ok(state) => state,
+        Err(_) => return Err(evaluator::new_error("generator is already running".to_string())),
+    };
+
+    let result = match run(&mut state) {
+        Err(object::Object::ReturnValue(_)) => Ok(None),
+        result => result,
+    };
+
+    match result {
+        Ok(Some(_)) => (),
+        _ => state.frames.clear(),
+    }
+
+    result
+}
+
+fn run(state: &mut GeneratorState) -> Result<Option<object::Object>, object::Object> {
+    while !state.frames.is_empty() {
+        match advance(state) {
+            Ok(Some(value)) => return Ok(Some(value)),
+            Ok(None) => (),
+            Err(err) => unwind(state, err)?,
+        }
+    }
+
+    Ok(None)
+}
+
+// Goes one step further in the innermost frame.
+fn advance(state: &mut GeneratorState) -> Result<Option<object::Object>, object::Object> {
+    let next = match state.frames.last_mut() {
+        None => return Ok(None),
+        Some(Frame::Block(statements)) => match statements.next() {
+            Some(statement) => return step(statement, state),
+            None => None,
+        },
+        Some(Frame::Loop {
+            pattern,
+            sequence,
+            body,
+        }) => sequence.next(&state.env)?.map(|item| (pattern.clone(), item, body.clone())),
+        // Body of `try` is over.
+        Some(Frame::Try { finally, .. }) => {
+            let finally = finally.take();
+            state.frames.pop();
+            if let Some(block) = finally {
+                state.frames.push(Frame::Block(block.statements.into_iter()));
+            }
+            return Ok(None);
+        }
+        Some(Frame::Rethrow(_)) => match state.frames.pop() {
+            Some(Frame::Rethrow(err)) => return Err(err),
+            _ => unreachable!("the frame was just seen"),
+        },
+        Some(Frame::Scope(_)) => match state.frames.pop() {
+            Some(Frame::Scope(outer)) => {
+                state.env = outer;
+                return Ok(None);
+            }
+            _ => unreachable!("the frame was just seen"),
+        },
+    };
+
+    match next {
+        Some((pattern, item, body)) => {
+            let mut loop_env = enclosed(&state.env);
+            evaluator::bind_pattern(pattern, item, &mut loop_env)?;
+            enter(state, loop_env, body);
+        }
+        None => {
+            state.frames.pop();
+        }
+    }
+    Ok(None)
+}
+
+// Error or `return` leaves frames up to the innermost `try`, as `try`
+// does outside of generators. Without one it finishes the generator.
+fn unwind(state: &mut GeneratorState, err: object::Object) -> Result<(), object::Object> {
+    while let Some(frame) = state.frames.pop() {
+        let (catch, finally) = match frame {
+            Frame::Try { catch, finally } => (catch, finally),
+            Frame::Scope(outer) => {
+                state.env = outer;
+                continue;
+            }
+            _ => continue,
+        };
+
+        if let (Some((pattern, block)), object::Object::Error(error)) = (catch, &err) {
+            // Errors in `catch` still run `finally`.
+            state.frames.push(Frame::Try { catch: None, finally });
+            let mut catch_env = enclosed(&state.env);
+            let caught = evaluator::caught_error(error.clone());
+            if let Err(err) = evaluator::bind_pattern(pattern, caught, &mut catch_env) {
+                return unwind(state, err);
+            }
+            enter(state, catch_env, block);
+            return Ok(());
+        }
+
+        if let Some(block) = finally {
+            state.frames.push(Frame::Rethrow(err));
+            state.frames.push(Frame::Block(block.statements.into_iter()));
+            return Ok(());
+        }
+    }
+
+    Err(err)
+}
+
+// Runs one statement of the body, gives a value if it was `yield`.
+fn step(
+    statement: token::Statements,
+    state: &mut GeneratorState,
+) -> Result<Option<object::Object>, object::Object> {
+    let es = match statement {
+        token::Statements::ExpressionStatement(es) => es,
+        statement => {
+            eval_statement(statement, &mut state.env)?;
+            return Ok(None);
+        }
+    };
+
+    // Parts of the statement are evaluated as statements of their own,
+    // so errors in them still get its location.
+    let (statement_token, location) = (es.token, es.location);
+    let expression_statement = |expression| {
+        token::Statements::ExpressionStatement(token::ExpressionStatement {
+            token: statement_token.clone(),
+            expression,
+            location: location.clone(),
+        })
+    };
+
+    match es.expression {
+        token::Expression::YieldExpression(ye) => {
+            let value = eval_statement(expression_statement(ye.value), &mut state.env)?;
+            Ok(Some(value))
+        }
+        token::Expression::IfExpression(ie) => {
+            let condition = eval_statement(expression_statement(ie.condition), &mut state.env)?;
+            let branch = if evaluator::is_truthy(condition) {
+                Some(ie.consequence)
+            } else {
+                ie.alternative
+            };
+
+            if let Some(block) = branch {
+                state.frames.push(Frame::Block(block.statements.into_iter()));
+            }
+            Ok(None)
+        }
+        token::Expression::MatchExpression(me) => {
+            let subject = eval_statement(expression_statement(me.subject), &mut state.env)?;
+
+            for arm in me.arms {
+                let mut arm_env = enclosed(&state.env);
+                if evaluator::bind_pattern(arm.pattern, subject.clone(), &mut arm_env).is_err() {
+                    continue;
+                }
+
+                if let Some(guard) = arm.guard {
+                    let passed = eval_statement(expression_statement(guard), &mut arm_env)?;
+                    if !evaluator::is_truthy(passed) {
+                        continue;
+                    }
+                }
+
+                enter(state, arm_env, arm.body);
+                return Ok(None);
+            }
+
+            Err(object::Object::Error(object::Error {
+                message: format!("no match arm for value: {}", subject.inspect()),
+                kind: "RuntimeError".to_string(),
+                location: Some(evaluator::file_location(&location, &state.env)),
+                value: None,
+            }))
+        }
+        token::Expression::TryExpression(te) => {
+            state.frames.push(Frame::Try {
+                catch: te.catch,
+                finally: te.finally,
+            });
+            state.frames.push(Frame::Block(te.body.statements.into_iter()));
+            Ok(None)
+        }
+        token::Expression::ForExpression(fe) => {
+            let iterable = eval_statement(expression_statement(fe.iterable), &mut state.env)?;
+            let sequence = Box::new(seqs::Sequence::new(iterable, &state.env)?);
+
+            state.frames.push(Frame::Loop {
+                pattern: fe.pattern,
+                sequence,
+                body: fe.body,
+            });
+            Ok(None)
+        }
+        expression => {
+            eval_statement(expression_statement(expression), &mut state.env)?;
+            Ok(None)
+        }
+    }
+}
+
+fn enclosed(env: &environment::Environment) -> environment::Environment {
+    environment::Environment::new_enclosed_environment(env.clone())
+}
+
+// Runs the block in the environment, the current one is back after it.
+fn enter(state: &mut GeneratorState, env: environment::Environment, block: token::BlockStatement) {
+    let outer = std::mem::replace(&mut state.env, env);
+    state.frames.push(Frame::Scope(outer));
+    state.frames.push(Frame::Block(block.statements.into_iter()));
+}
+
+// Error or return value stop the generator.
+fn eval_statement(
+    statement: token::Statements,
+    env: &mut environment::Environment,
+) -> Result<object::Object, object::Object> {
+    let result = evaluator::eval_block_statement(vec![statement], env);
+
+    if evaluator::is_error(&result) {
+        Err(result)
+    } else {
+        Ok(result)
+    }
+}
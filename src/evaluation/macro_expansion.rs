@@ -0,0 +1,283 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::ast;
+use crate::evaluation::environment;
+use crate::evaluation::evaluator;
+use crate::evaluation::object;
+use crate::evaluation::object::ObjectT;
+use crate::token;
+
+// Macro returning a call of another macro is expanded again,
+// this limit stops macros which expand into themselves.
+const MAX_EXPANSION_DEPTH: usize = 64;
+
+// Counter for unique names of bindings made by macros.
+static GENSYM_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Macros are handled before evaluation, in their own environment.
+// Top-level `let name = macro(...) { ... };` defines a macro
+// and is removed from the program.
+pub fn define_macros(program: &mut ast::Program, env: &mut environment::Environment) {
+    let statements = std::mem::take(&mut program.statements);
+
+    for statement in statements {
+        match statement {
+            token::Statements::LetStatement(token::LetStatement {
+                pattern: token::Pattern::Identifier(name),
+                value: token::Expression::MacroLiteral(ml),
+                ..
+            }) => {
+                let macro_object = object::Object::Macro(Box::new(object::Macro {
+                    parameters: ml.parameters,
+                    body: ml.body,
+                    env: env.clone(),
+                }));
+                env.set(name.value, macro_object);
+            }
+            statement => program.statements.push(statement),
+        }
+    }
+}
+
+// Replaces every macro call with the code the macro returns.
+// Arguments are passed to the macro unevaluated, as quotes.
+pub fn expand_macros(
+    program: ast::Program,
+    env: &environment::Environment,
+) -> Result<ast::Program, object::Object> {
+    let mut expander = Expander {
+        env,
+        error: None,
+        depth: 0,
+    };
+    let program = ast::modify_program(program, &mut expander);
+
+    match expander.error {
+        Some(err) => Err(err),
+        None => Ok(program),
+    }
+}
+
+struct Expander<'a> {
+    env: &'a environment::Environment,
+    error: Option<object::Object>,
+    depth: usize,
+}
+
+impl Expander<'_> {
+    fn expand(
+        &mut self,
+        name: &str,
+        macro_object: object::Macro,
+        call: &token::CallExpression,
+    ) -> Result<token::Expression, object::Object> {
+        if !call.named_arguments.is_empty() {
+            return Err(evaluator::new_error(format!(
+                "named arguments are not supported by macro `{}`",
+                name
+            )));
+        }
+
+        let arguments = call.arguments.clone().unwrap_or_default();
+        if arguments.len() != macro_object.parameters.len() {
+            return Err(evaluator::new_error(format!(
+                "wrong number of arguments to macro `{}`: got={}, expected={}",
+                name,
+                arguments.len(),
+                macro_object.parameters.len()
+            )));
+        }
+
+        if self.depth >= MAX_EXPANSION_DEPTH {
+            return Err(evaluator::new_error(format!(
+                "macro `{}` is expanded too deep, does it expand into itself?",
+                name
+            )));
+        }
+
+        let mut env = environment::Environment::new_enclosed_environment(macro_object.env);
+        for (parameter, argument) in macro_object.parameters.into_iter().zip(arguments) {
+            env.set(
+                parameter.value,
+                object::Object::Quote(Box::new(object::Quote { node: argument })),
+            );
+        }
+
+        let body = ast::modify_block(macro_object.body, &mut Hygiene);
+        let result = evaluator::eval(evaluator::WN::B(body), &mut env);
+
+        match evaluator::unwrap_return_value(result) {
+            object::Object::Quote(quote) => {
+                // Expanded code may call macros as well.
+                self.depth += 1;
+                let node = ast::modify_expression(quote.node, self);
+                self.depth -= 1;
+                Ok(node)
+            }
+            err @ object::Object::Error(_) => Err(err),
+            other => Err(evaluator::new_error(format!(
+                "macro `{}` must return a quote, got {}",
+                name,
+                other.object_type()
+            ))),
+        }
+    }
+}
+
+impl ast::Modifier for Expander<'_> {
+    fn expression(&mut self, expression: token::Expression) -> token::Expression {
+        if self.error.is_some() {
+            return expression;
+        }
+
+        let call = match &expression {
+            token::Expression::CallExpression(call) => call,
+            _ => return expression,
+        };
+
+        let (name, macro_object) = match &call.function {
+            token::Expression::Identifier(name) => match self.env.get(name.value.clone()) {
+                Some(object::Object::Macro(macro_object)) => {
+                    (name.value.clone(), (**macro_object).clone())
+                }
+                _ => return expression,
+            },
+            _ => return expression,
+        };
+
+        match self.expand(&name, macro_object, call) {
+            Ok(node) => node,
+            Err(err) => {
+                self.error = Some(err);
+                expression
+            }
+        }
+    }
+}
+
+// Hygiene: names bound inside of quoted code get new unique names on
+// every expansion, so the code a macro returns can neither see nor
+// hide variables used in the macro arguments or around the call.
+// Code put into the quote by `unquote` is left as it is.
+struct Hygiene;
+
+impl ast::Modifier for Hygiene {
+    fn expression(&mut self, expression: token::Expression) -> token::Expression {
+        let template = match evaluator::special_form_argument(&expression, "quote") {
+            Some(template) => template.clone(),
+            None => return expression,
+        };
+
+        let mut binders = Binders {
+            names: HashSet::new(),
+        };
+        ast::modify_expression(template.clone(), &mut binders);
+
+        if binders.names.is_empty() {
+            return expression;
+        }
+
+        let mut renamer = Renamer {
+            names: binders
+                .names
+                .into_iter()
+                .map(|name| {
+                    let id = GENSYM_COUNTER.fetch_add(1, Ordering::Relaxed);
+                    let unique = format!("{}#{}", name, id);
+                    (name, unique)
+                })
+                .collect(),
+        };
+        let template = ast::modify_expression(template, &mut renamer);
+
+        match expression {
+            token::Expression::CallExpression(mut call) => {
+                call.arguments = Some(vec![template]);
+                token::Expression::CallExpression(call)
+            }
+            expression => expression,
+        }
+    }
+}
+
+fn is_unquote(expression: &token::Expression) -> bool {
+    evaluator::special_form_argument(expression, "unquote").is_some()
+}
+
+// Collects names bound by patterns of the quoted code.
+struct Binders {
+    names: HashSet<String>,
+}
+
+impl ast::Modifier for Binders {
+    fn enter(&mut self, expression: &token::Expression) -> bool {
+        !is_unquote(expression)
+    }
+
+    fn pattern(&mut self, pattern: token::Pattern) -> token::Pattern {
+        let name = match &pattern {
+            token::Pattern::Identifier(identifier) => Some(identifier),
+            token::Pattern::ArrayPattern(ap) => ap.rest.as_ref(),
+            token::Pattern::HashPattern(hp) => hp.rest.as_ref(),
+            _ => None,
+        };
+
+        if let Some(name) = name {
+            self.names.insert(name.value.clone());
+        }
+
+        pattern
+    }
+}
+
+struct Renamer {
+    names: HashMap<String, String>,
+}
+
+impl Renamer {
+    fn rename(&self, identifier: &mut token::Identifier) {
+        if let Some(unique) = self.names.get(&identifier.value) {
+            identifier.value = unique.clone();
+            identifier.token.literal = unique.clone();
+        }
+    }
+}
+
+impl ast::Modifier for Renamer {
+    fn enter(&mut self, expression: &token::Expression) -> bool {
+        !is_unquote(expression)
+    }
+
+    fn expression(&mut self, expression: token::Expression) -> token::Expression {
+        match expression {
+            token::Expression::Identifier(mut identifier) => {
+                self.rename(&mut identifier);
+                token::Expression::Identifier(identifier)
+            }
+            expression => expression,
+        }
+    }
+
+    fn pattern(&mut self, pattern: token::Pattern) -> token::Pattern {
+        match pattern {
+            token::Pattern::Identifier(mut identifier) => {
+                self.rename(&mut identifier);
+                token::Pattern::Identifier(identifier)
+            }
+            token::Pattern::ArrayPattern(mut ap) => {
+                if let Some(rest) = ap.rest.as_mut() {
+                    self.rename(rest);
+                }
+                token::Pattern::ArrayPattern(ap)
+            }
+            token::Pattern::HashPattern(mut hp) => {
+                if let Some(rest) = hp.rest.as_mut() {
+                    self.rename(rest);
+                }
+                token::Pattern::HashPattern(hp)
+            }
+            pattern => pattern,
+        }
+    }
+}
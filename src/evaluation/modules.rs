@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::evaluation::environment;
+use crate::evaluation::evaluator;
+use crate::evaluation::macro_expansion;
+use crate::evaluation::object;
+use crate::parser;
+use crate::token;
+
+// Import path without extension gets this one:
+// `import "lib/math"` loads `lib/math.clm`.
+pub const EXTENSION: &str = "clm";
+
+#[derive(Debug, Default)]
+pub struct ModuleCache {
+    // Evaluated modules by canonical path of their file.
+    loaded: HashMap<PathBuf, object::Object>,
+    // Files being evaluated right now, the outermost first, with the
+    // path they were imported by. Importing one of them again is a cycle.
+    loading: Vec<(PathBuf, String)>,
+    // Entry files of the project dependencies by package name.
+    packages: HashMap<String, PathBuf>,
+    // Parser warnings of the loaded files, with the file they're in.
+    warnings: Vec<String>,
+}
+
+impl ModuleCache {
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn add_package(&mut self, name: String, entry: PathBuf) {
+        self.packages.insert(name, entry);
+    }
+
+    // `import "json"` is the entry file of package `json`,
+    // `import "json/parser"` is a file next to it.
+    fn package_file(&self, path: &str) -> Option<PathBuf> {
+        let mut parts = path.splitn(2, '/');
+        let entry = self.packages.get(parts.next()?)?;
+
+        match parts.next() {
+            None => Some(entry.clone()),
+            Some(rest) => {
+                let mut file = entry.parent()?.join(rest);
+                if file.extension().is_none() {
+                    file.set_extension(EXTENSION);
+                }
+                Some(file)
+            }
+        }
+    }
+}
+
+// Evaluates a file as the main program and returns its last value.
+pub fn run_file(file: &Path, env: &environment::Environment) -> object::Object {
+    match evaluate_file(file, &file.display().to_string(), env) {
+        Ok((result, _, _)) => result,
+        Err(err) => err,
+    }
+}
+
+// Loads the module for `import "<path>" as name;`. The path is relative
+// to the importing file, or to the working directory in the REPL.
+// Paths which are not files there are looked up among packages.
+pub fn import(path: &str, env: &environment::Environment) -> object::Object {
+    let directory = env.file().and_then(Path::parent).unwrap_or_else(|| Path::new(""));
+    let mut file = directory.join(path);
+    if file.extension().is_none() {
+        file.set_extension(EXTENSION);
+    }
+    if !file.exists() {
+        if let Some(package_file) = env.modules().borrow().package_file(path) {
+            file = package_file;
+        }
+    }
+
+    let key = match fs::canonicalize(&file) {
+        Ok(key) => key,
+        Err(err) => return evaluator::new_error(format!("cannot import \"{}\": {}", path, err)),
+    };
+
+    if let Some(module) = env.modules().borrow().loaded.get(&key) {
+        return module.clone();
+    }
+
+    let (_, module_env, exports) = match evaluate_file(&file, path, env) {
+        Ok(evaluated) => evaluated,
+        Err(err) => return err,
+    };
+
+    let members: BTreeMap<String, object::Object> = exports
+        .into_iter()
+        .filter_map(|name| {
+            let value = module_env.get(name.clone())?.clone();
+            Some((name, value))
+        })
+        .collect();
+
+    let module = object::Object::Module(object::Module {
+        path: path.to_string(),
+        members,
+    });
+    env.modules().borrow_mut().loaded.insert(key, module.clone());
+    module
+}
+
+// Parses and evaluates a file in an environment of its own.
+// Returns the value of the last statement, the environment
+// and names exported by the file.
+fn evaluate_file(
+    file: &Path,
+    name: &str,
+    env: &environment::Environment,
+) -> Result<(object::Object, environment::Environment, Vec<String>), object::Object> {
+    let key = fs::canonicalize(file)
+        .map_err(|err| evaluator::new_error(format!("cannot load \"{}\": {}", name, err)))?;
+
+    if let Some(cycle) = import_cycle(&key, name, env) {
+        return Err(evaluator::new_error(format!("import cycle: {}", cycle)));
+    }
+
+    let source = fs::read_to_string(file)
+        .map_err(|err| evaluator::new_error(format!("cannot load \"{}\": {}", name, err)))?;
+
+    let (mut program, parser) = parser::parse_source(&source, parser::builtin_enums());
+
+    let warnings = parser.warnings.iter().map(|warning| format!("{}: {}", file.display(), warning));
+    env.modules().borrow_mut().warnings.extend(warnings);
+
+    if !parser.errors.is_empty() {
+        return Err(evaluator::new_error(format!(
+            "cannot load \"{}\": {}",
+            name,
+            parser.errors.join("; ")
+        )));
+    }
+
+    let mut macro_env = environment::Environment::new();
+    macro_expansion::define_macros(&mut program, &mut macro_env);
+    let program = macro_expansion::expand_macros(program, &macro_env)?;
+
+    // Only top-level exports make it into the module.
+    let exports = program
+        .statements
+        .iter()
+        .flat_map(|statement| match statement {
+            token::Statements::ExportStatement(es) => es.names(),
+            _ => Vec::new(),
+        })
+        .collect();
+
+    let mut file_env = env.new_file_environment(file.to_path_buf());
+
+    env.modules().borrow_mut().loading.push((key, name.to_string()));
+    let result = evaluator::eval(evaluator::WN::P(program), &mut file_env);
+    env.modules().borrow_mut().loading.pop();
+
+    if let object::Object::Error(_) = result {
+        return Err(result);
+    }
+
+    Ok((result, file_env, exports))
+}
+
+// `"a" -> "b" -> "a"` when the file is being loaded already.
+fn import_cycle(key: &Path, name: &str, env: &environment::Environment) -> Option<String> {
+    let modules = env.modules().borrow();
+    let start = modules.loading.iter().position(|(loading, _)| loading == key)?;
+
+    let cycle = modules.loading[start..]
+        .iter()
+        .map(|(_, loading)| loading.as_str())
+        .chain(std::iter::once(name))
+        .map(|name| format!("\"{}\"", name))
+        .collect::<Vec<_>>()
+        .join(" -> ");
+
+    Some(cycle)
+}
@@ -1,5 +1,11 @@
 use crate::evaluation::environment;
+use crate::evaluation::generators;
+use crate::evaluation::seqs;
 use crate::token;
+use im_rc::{OrdMap, Vector};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
 
 use crate::core;
 
@@ -17,13 +23,26 @@ pub trait ObjectT {
 pub enum Object {
     Integer(Integer),
     Stringl(Stringl),
+    Char(Char),
     Array(Array),
+    Hash(Hash),
     Boolean(Boolean),
     Nil(Nil),
     ReturnValue(Box<ReturnValue>),
     Error(Error),
-    Function(Function),
+    // Big ones are boxed to keep objects, errors in `Result`s too, small.
+    Function(Box<Function>),
     CoreFunc(CoreFunc),
+    StructType(StructType),
+    Struct(Struct),
+    EnumType(EnumType),
+    VariantConstructor(VariantConstructor),
+    Variant(Variant),
+    Quote(Box<Quote>),
+    Macro(Box<Macro>),
+    Module(Module),
+    Generator(Generator),
+    Seq(Seq),
 }
 
 impl Object {
@@ -31,29 +50,80 @@ impl Object {
         match (self, other) {
             (Object::Integer(_), Object::Integer(_)) => true,
             (Object::Stringl(_), Object::Stringl(_)) => true,
+            (Object::Char(_), Object::Char(_)) => true,
             (Object::Array(_), Object::Array(_)) => true,
+            (Object::Hash(_), Object::Hash(_)) => true,
             (Object::Boolean(_), Object::Boolean(_)) => true,
             (Object::Nil(_), Object::Nil(_)) => true,
             (Object::ReturnValue(_), Object::ReturnValue(_)) => true,
             (Object::Error(_), Object::Error(_)) => true,
             (Object::Function(_), Object::Function(_)) => true,
+            (Object::StructType(_), Object::StructType(_)) => true,
+            (Object::Struct(left), Object::Struct(right)) => left.name == right.name,
+            (Object::EnumType(_), Object::EnumType(_)) => true,
+            (Object::VariantConstructor(_), Object::VariantConstructor(_)) => true,
+            (Object::Variant(left), Object::Variant(right)) => left.type_name == right.type_name,
+            (Object::Quote(_), Object::Quote(_)) => true,
+            (Object::Macro(_), Object::Macro(_)) => true,
+            (Object::Module(_), Object::Module(_)) => true,
+            (Object::Generator(_), Object::Generator(_)) => true,
+            (Object::Seq(_), Object::Seq(_)) => true,
             (_, _) => false,
         }
     }
 }
 
+// Types of the same name declared in different files are
+// different types, methods and protocols are kept by this key.
+pub type TypeKey = (Option<String>, ObjectType);
+
+impl Object {
+    pub fn type_key(&self) -> TypeKey {
+        match self {
+            Object::Struct(st) => (st.file.clone(), st.name.clone()),
+            Object::Variant(variant) => (variant.file.clone(), variant.type_name.clone()),
+            object => (None, object.object_type()),
+        }
+    }
+}
+
+impl Object {
+    // Only immutable scalar values can be used as hash keys.
+    pub fn hash_key(&self) -> Option<HashKey> {
+        match self {
+            Object::Integer(i) => Some(HashKey::Integer(i.value)),
+            Object::Stringl(s) => Some(HashKey::Stringl(s.value.clone())),
+            Object::Char(c) => Some(HashKey::Char(c.value)),
+            Object::Boolean(b) => Some(HashKey::Boolean(b.value)),
+            _ => None,
+        }
+    }
+}
+
 impl ObjectT for Object {
     fn object_type(&self) -> ObjectType {
         match self {
             Object::Integer(i) => i.object_type(),
             Object::Stringl(s) => s.object_type(),
+            Object::Char(c) => c.object_type(),
             Object::Array(a) => a.object_type(),
+            Object::Hash(h) => h.object_type(),
             Object::Boolean(b) => b.object_type(),
             Object::Nil(n) => n.object_type(),
             Object::ReturnValue(rv) => rv.object_type(),
             Object::Error(err) => err.object_type(),
             Object::Function(fun) => fun.object_type(),
             Object::CoreFunc(fun) => fun.object_type(),
+            Object::StructType(st) => st.object_type(),
+            Object::Struct(st) => st.object_type(),
+            Object::EnumType(et) => et.object_type(),
+            Object::VariantConstructor(vc) => vc.object_type(),
+            Object::Variant(v) => v.object_type(),
+            Object::Quote(q) => q.object_type(),
+            Object::Macro(m) => m.object_type(),
+            Object::Module(m) => m.object_type(),
+            Object::Generator(g) => g.object_type(),
+            Object::Seq(s) => s.object_type(),
         }
     }
 
@@ -61,13 +131,25 @@ impl ObjectT for Object {
         match self {
             Object::Integer(i) => i.inspect(),
             Object::Stringl(s) => s.inspect(),
+            Object::Char(c) => c.inspect(),
             Object::Array(a) => a.inspect(),
+            Object::Hash(h) => h.inspect(),
             Object::Boolean(b) => b.inspect(),
             Object::Nil(n) => n.inspect(),
             Object::ReturnValue(rv) => rv.inspect(),
             Object::Error(err) => err.inspect(),
             Object::Function(fun) => fun.inspect(),
             Object::CoreFunc(fun) => fun.inspect(),
+            Object::StructType(st) => st.inspect(),
+            Object::Struct(st) => st.inspect(),
+            Object::EnumType(et) => et.inspect(),
+            Object::VariantConstructor(vc) => vc.inspect(),
+            Object::Variant(v) => v.inspect(),
+            Object::Quote(q) => q.inspect(),
+            Object::Macro(m) => m.inspect(),
+            Object::Module(m) => m.inspect(),
+            Object::Generator(g) => g.inspect(),
+            Object::Seq(s) => s.inspect(),
         }
     }
 }
@@ -108,6 +190,22 @@ impl ObjectT for Stringl {
     }
 }
 
+// Single unicode character
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Char {
+    pub value: char,
+}
+
+impl ObjectT for Char {
+    fn object_type(&self) -> ObjectType {
+        "CHAR".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        self.value.to_string()
+    }
+}
+
 // Boolean value
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Boolean {
@@ -155,9 +253,17 @@ impl ObjectT for ReturnValue {
 }
 
 // Error value
+// Kind is `RuntimeError` for errors of the interpreter itself,
+// thrown ones get `Error` or the type name of the thrown value.
+// Location is "line:column" of the statement which failed,
+// prefixed with the file name for code loaded from a file.
+// Value is what was thrown, errors of the interpreter have none.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Error {
     pub message: String,
+    pub kind: String,
+    pub location: Option<String>,
+    pub value: Option<Box<Object>>,
 }
 
 impl ObjectT for Error {
@@ -173,9 +279,11 @@ impl ObjectT for Error {
 // Function object
 #[derive(Debug, Clone)]
 pub struct Function {
-    pub parameters: Option<Vec<token::Identifier>>,
+    pub parameters: Option<Vec<token::Parameter>>,
     pub body: token::BlockStatement,
     pub env: environment::Environment,
+    // Calling a generator function gives a generator, the body runs later.
+    pub generator: bool,
 }
 
 // Functions are never equal.
@@ -202,7 +310,141 @@ impl ObjectT for Function {
             None => "".to_string(),
         };
 
-        format!("fn({}) {{{}}}", params, self.body)
+        let star = if self.generator { "*" } else { "" };
+
+        format!("fn{}({}) {{{}}}", star, params, self.body)
+    }
+}
+
+// Paused body of a generator function. Copies of the object share
+// the state, so a value taken by one of them is gone for the others.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    pub state: Rc<RefCell<generators::GeneratorState>>,
+}
+
+impl PartialEq for Generator {
+    fn eq(&self, other: &Generator) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+}
+impl Eq for Generator {}
+
+impl ObjectT for Generator {
+    fn object_type(&self) -> ObjectType {
+        "GENERATOR".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        "generator".to_string()
+    }
+}
+
+// Lazy sequence, see `seqs::Stage`. Values are not known until
+// it's walked, so it's shown without them.
+#[derive(Debug, Clone)]
+pub struct Seq {
+    pub stage: Rc<seqs::Stage>,
+}
+
+impl PartialEq for Seq {
+    fn eq(&self, other: &Seq) -> bool {
+        Rc::ptr_eq(&self.stage, &other.stage)
+    }
+}
+impl Eq for Seq {}
+
+impl ObjectT for Seq {
+    fn object_type(&self) -> ObjectType {
+        "SEQ".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        "seq".to_string()
+    }
+}
+
+// Quoted, not evaluated, piece of code.
+#[derive(Debug, Clone)]
+pub struct Quote {
+    pub node: token::Expression,
+}
+
+// AST has no equality of its own, same code is the same quote.
+impl PartialEq for Quote {
+    fn eq(&self, other: &Quote) -> bool {
+        self.node.to_string() == other.node.to_string()
+    }
+}
+impl Eq for Quote {}
+
+impl ObjectT for Quote {
+    fn object_type(&self) -> ObjectType {
+        "QUOTE".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        format!("QUOTE({})", self.node)
+    }
+}
+
+// Macro object, lives in the macro environment only.
+#[derive(Debug, Clone)]
+pub struct Macro {
+    pub parameters: Vec<token::Identifier>,
+    pub body: token::BlockStatement,
+    pub env: environment::Environment,
+}
+
+// Macros are never equal, same as functions.
+impl PartialEq for Macro {
+    fn eq(&self, _other: &Macro) -> bool {
+        false
+    }
+}
+impl Eq for Macro {}
+
+impl ObjectT for Macro {
+    fn object_type(&self) -> ObjectType {
+        "MACRO".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        let params = self
+            .parameters
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!("macro({}) {{{}}}", params, self.body)
+    }
+}
+
+// Imported file, holds the values it exports.
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub path: String,
+    pub members: BTreeMap<String, Object>,
+}
+
+// Every file is loaded once, so the same path is the same module.
+impl PartialEq for Module {
+    fn eq(&self, other: &Module) -> bool {
+        self.path == other.path
+    }
+}
+impl Eq for Module {}
+
+impl ObjectT for Module {
+    fn object_type(&self) -> ObjectType {
+        "MODULE".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        let members = self.members.keys().cloned().collect::<Vec<_>>().join(", ");
+
+        format!("module \"{}\" {{ {} }}", self.path, members)
     }
 }
 
@@ -210,22 +452,23 @@ impl ObjectT for Function {
 #[derive(Clone, PartialEq, Eq)]
 pub struct CoreFunc {
     pub function_name: core::funcs::FunctionName,
-    pub arity: core::funcs::Arity,
 }
 
 impl CoreFunc {
     pub fn try_new(function_name: String) -> Option<Object> {
-        match core::funcs::CORE_REGISTRY.get(&function_name) {
-            Some(arity) => Some(Object::CoreFunc(CoreFunc {
-                function_name,
-                arity: *arity,
-            })),
-            None => None,
-        }
+        core::funcs::CORE_REGISTRY.get(&function_name)?;
+        Some(Object::CoreFunc(CoreFunc { function_name }))
+    }
+
+    // Registered function, there is one for every CoreFunc made by `try_new`.
+    pub fn native(&self) -> &'static core::registry::NativeFunction {
+        core::funcs::CORE_REGISTRY
+            .get(&self.function_name)
+            .expect("core function is made only for registered names")
     }
 
-    pub fn call(&self, args: Vec<Object>) -> Object {
-        core::funcs::call(self.function_name.clone(), args)
+    pub fn call(&self, args: Vec<Object>, ctx: &core::funcs::Context) -> Object {
+        self.native().call(args, ctx)
     }
 }
 
@@ -246,9 +489,12 @@ impl ObjectT for CoreFunc {
 }
 
 // Array object
+//
+// Arrays are values, but copying one shares its elements: `push`,
+// `rest` and clones of the environment don't copy the whole array.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Array {
-    pub elements: Vec<Object>,
+    pub elements: Vector<Object>,
 }
 
 impl ObjectT for Array {
@@ -267,3 +513,192 @@ impl ObjectT for Array {
         format!("[{}]", elems)
     }
 }
+
+// Hash object
+//
+// Pairs are stored under a `HashKey` derived from the key object,
+// but we keep the original key around to render it back in `inspect`.
+// Ordered map gives us stable ordering, so the same hash always looks the same,
+// and like arrays, copies of a hash share its pairs.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HashKey {
+    Integer(i32),
+    Stringl(String),
+    Char(char),
+    Boolean(bool),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashPair {
+    pub key: Object,
+    pub value: Object,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hash {
+    pub pairs: OrdMap<HashKey, HashPair>,
+}
+
+impl ObjectT for Hash {
+    fn object_type(&self) -> ObjectType {
+        "HASH".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        let pairs = self
+            .pairs
+            .values()
+            .map(|pair| format!("{}: {}", pair.key.inspect(), pair.value.inspect()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("{{{}}}", pairs)
+    }
+}
+
+// Struct declaration, calling it constructs a new struct.
+// File is where the type is declared, None in the REPL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructType {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub file: Option<String>,
+}
+
+impl ObjectT for StructType {
+    fn object_type(&self) -> ObjectType {
+        "STRUCT_TYPE".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        if self.fields.is_empty() {
+            return format!("struct {} {{}}", self.name);
+        }
+
+        format!("struct {} {{ {} }}", self.name, self.fields.join(", "))
+    }
+}
+
+// Struct value
+//
+// Fields are kept in the declaration order, so two structs are equal
+// when they have the same type and equal fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Struct {
+    pub name: String,
+    pub fields: Vec<(String, Object)>,
+    pub file: Option<String>,
+}
+
+impl Struct {
+    pub fn get(&self, field: &str) -> Option<&Object> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value)
+    }
+}
+
+impl ObjectT for Struct {
+    // Every struct type is a type of its own.
+    fn object_type(&self) -> ObjectType {
+        self.name.clone()
+    }
+
+    fn inspect(&self) -> String {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value.inspect()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if fields.is_empty() {
+            return format!("{} {{}}", self.name);
+        }
+
+        format!("{} {{ {} }}", self.name, fields)
+    }
+}
+
+// Enum declaration, variant names with their fields.
+// File is None in the REPL and for `Result` and `Option`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumType {
+    pub name: String,
+    pub variants: Vec<(String, Vec<String>)>,
+    pub file: Option<String>,
+}
+
+impl ObjectT for EnumType {
+    fn object_type(&self) -> ObjectType {
+        "ENUM_TYPE".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        let variants = self
+            .variants
+            .iter()
+            .map(|(tag, fields)| {
+                if fields.is_empty() {
+                    tag.clone()
+                } else {
+                    format!("{}({})", tag, fields.join(", "))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("enum {} {{ {} }}", self.name, variants)
+    }
+}
+
+// Constructor of enum variant with fields, `Circle` in `Circle(1)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantConstructor {
+    pub type_name: String,
+    pub tag: String,
+    pub fields: Vec<String>,
+    pub file: Option<String>,
+}
+
+impl ObjectT for VariantConstructor {
+    fn object_type(&self) -> ObjectType {
+        "VARIANT_CONSTRUCTOR".to_string()
+    }
+
+    fn inspect(&self) -> String {
+        format!("{}.{}({})", self.type_name, self.tag, self.fields.join(", "))
+    }
+}
+
+// Enum value: tag of the variant and its fields in declaration order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    pub type_name: String,
+    pub tag: String,
+    pub values: Vec<Object>,
+    pub file: Option<String>,
+}
+
+impl ObjectT for Variant {
+    // Variants report the enum they belong to.
+    fn object_type(&self) -> ObjectType {
+        self.type_name.clone()
+    }
+
+    fn inspect(&self) -> String {
+        if self.values.is_empty() {
+            return self.tag.clone();
+        }
+
+        let values = self
+            .values
+            .iter()
+            .map(ObjectT::inspect)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("{}({})", self.tag, values)
+    }
+}
@@ -0,0 +1,318 @@
+use im_rc::Vector;
+use std::rc::Rc;
+
+use crate::evaluation::environment;
+use crate::evaluation::evaluator;
+use crate::evaluation::generators;
+use crate::evaluation::object;
+use crate::evaluation::object::ObjectT;
+
+// Lazy sequence is a source of values and the steps applied to them.
+// Nothing is evaluated until values are asked for, by `for`, `collect`
+// or functions like `first`. Every walk starts from the source again,
+// except generators, which give each value only once.
+#[derive(Debug)]
+pub enum Stage {
+    // Array elements from `start` on, `rest` of such a sequence
+    // is a new start, not a new array.
+    Elements {
+        elements: Vector<object::Object>,
+        start: usize,
+    },
+    // String, generator or a user type implementing `Iterable`.
+    Iterable(object::Object),
+    // Integers from the start up to the end, endless without it.
+    Range(i32, Option<i32>),
+    // Value, `f(value)`, `f(f(value))` and so on.
+    Iterate(object::Object, object::Object),
+    Repeat(object::Object),
+    Map(Rc<Stage>, object::Object),
+    Filter(Rc<Stage>, object::Object),
+    Take(Rc<Stage>, usize),
+    Drop(Rc<Stage>, usize),
+    TakeWhile(Rc<Stage>, object::Object),
+    Zip(Rc<Stage>, Rc<Stage>),
+    // Index of the first value.
+    Enumerate(Rc<Stage>, i32),
+    Chain(Rc<Stage>, Rc<Stage>),
+    FlatMap(Rc<Stage>, object::Object),
+}
+
+// Values being iterated, this is what `for` and lazy sequences walk
+// through. Anything iterable gets here by `Sequence::new`.
+#[derive(Debug)]
+pub enum Sequence {
+    // Elements and index of the next one.
+    Elements(Vector<object::Object>, usize),
+    Generator(object::Generator),
+    // Next integer, None after the last one.
+    Range(Option<i32>, Option<i32>),
+    // Next value, None until it's computed from the one before.
+    Iterate(Option<object::Object>, object::Object, object::Object),
+    Repeat(object::Object),
+    Map(Box<Sequence>, object::Object),
+    Filter(Box<Sequence>, object::Object),
+    // Values left to take.
+    Take(Box<Sequence>, usize),
+    // Values left to skip.
+    Drop(Box<Sequence>, usize),
+    // None once the predicate failed.
+    TakeWhile(Option<Box<Sequence>>, object::Object),
+    Zip(Box<Sequence>, Box<Sequence>),
+    Enumerate(Box<Sequence>, i32),
+    Chain(Box<Sequence>, Box<Sequence>),
+    // Sequence of the value mapped last.
+    FlatMap(Box<Sequence>, object::Object, Option<Box<Sequence>>),
+}
+
+impl Sequence {
+    // Types implementing `Iterable` are converted by their `iter`.
+    pub fn new(
+        value: object::Object,
+        env: &environment::Environment,
+    ) -> Result<Sequence, object::Object> {
+        match value {
+            object::Object::Array(array) => Ok(Sequence::Elements(array.elements, 0)),
+            object::Object::Stringl(string) => {
+                let chars = string
+                    .value
+                    .chars()
+                    .map(|value| object::Object::Char(object::Char { value }))
+                    .collect();
+                Ok(Sequence::Elements(chars, 0))
+            }
+            object::Object::Generator(generator) => Ok(Sequence::Generator(generator)),
+            object::Object::Seq(seq) => Sequence::from_stage(&seq.stage, env),
+            value => Sequence::new(iterated(value, env)?, env),
+        }
+    }
+
+    fn from_stage(
+        stage: &Stage,
+        env: &environment::Environment,
+    ) -> Result<Sequence, object::Object> {
+        let boxed = |stage: &Stage| Sequence::from_stage(stage, env).map(Box::new);
+
+        let sequence = match stage {
+            Stage::Elements { elements, start } => Sequence::Elements(elements.clone(), *start),
+            Stage::Iterable(value) => Sequence::new(value.clone(), env)?,
+            Stage::Range(start, end) => Sequence::Range(Some(*start), *end),
+            Stage::Iterate(value, fun) => {
+                Sequence::Iterate(Some(value.clone()), value.clone(), fun.clone())
+            }
+            Stage::Repeat(value) => Sequence::Repeat(value.clone()),
+            Stage::Map(source, fun) => Sequence::Map(boxed(source)?, fun.clone()),
+            Stage::Filter(source, fun) => Sequence::Filter(boxed(source)?, fun.clone()),
+            Stage::Take(source, count) => Sequence::Take(boxed(source)?, *count),
+            Stage::Drop(source, count) => Sequence::Drop(boxed(source)?, *count),
+            Stage::TakeWhile(source, fun) => Sequence::TakeWhile(Some(boxed(source)?), fun.clone()),
+            Stage::Zip(left, right) => Sequence::Zip(boxed(left)?, boxed(right)?),
+            Stage::Enumerate(source, start) => Sequence::Enumerate(boxed(source)?, *start),
+            Stage::Chain(first, second) => Sequence::Chain(boxed(first)?, boxed(second)?),
+            Stage::FlatMap(source, fun) => Sequence::FlatMap(boxed(source)?, fun.clone(), None),
+        };
+
+        Ok(sequence)
+    }
+
+    pub fn next(
+        &mut self,
+        env: &environment::Environment,
+    ) -> Result<Option<object::Object>, object::Object> {
+        match self {
+            Sequence::Elements(elements, index) => {
+                let element = elements.get(*index).cloned();
+                *index += 1;
+                Ok(element)
+            }
+            Sequence::Generator(generator) => generators::resume(generator),
+            Sequence::Range(next, end) => match *next {
+                Some(value) if end.is_none_or(|end| value < end) => {
+                    *next = value.checked_add(1);
+                    Ok(Some(object::Object::Integer(object::Integer { value })))
+                }
+                _ => Ok(None),
+            },
+            Sequence::Iterate(next, last, fun) => {
+                let value = match next.take() {
+                    Some(value) => value,
+                    None => call(fun, vec![last.clone()], env)?,
+                };
+                *last = value.clone();
+                Ok(Some(value))
+            }
+            Sequence::Repeat(value) => Ok(Some(value.clone())),
+            Sequence::Map(source, fun) => match source.next(env)? {
+                Some(value) => call(fun, vec![value], env).map(Some),
+                None => Ok(None),
+            },
+            Sequence::Filter(source, fun) => {
+                while let Some(value) = source.next(env)? {
+                    if evaluator::is_truthy(call(fun, vec![value.clone()], env)?) {
+                        return Ok(Some(value));
+                    }
+                }
+                Ok(None)
+            }
+            Sequence::Take(source, left) => {
+                if *left == 0 {
+                    return Ok(None);
+                }
+                *left -= 1;
+                source.next(env)
+            }
+            Sequence::Drop(source, left) => {
+                while *left > 0 {
+                    *left -= 1;
+                    if source.next(env)?.is_none() {
+                        return Ok(None);
+                    }
+                }
+                source.next(env)
+            }
+            Sequence::TakeWhile(source, fun) => {
+                let value = match source {
+                    Some(source) => source.next(env)?,
+                    None => None,
+                };
+
+                match value {
+                    Some(value) if evaluator::is_truthy(call(fun, vec![value.clone()], env)?) => {
+                        Ok(Some(value))
+                    }
+                    _ => {
+                        *source = None;
+                        Ok(None)
+                    }
+                }
+            }
+            Sequence::Zip(left, right) => match (left.next(env)?, right.next(env)?) {
+                (Some(left), Some(right)) => Ok(Some(pair(left, right))),
+                _ => Ok(None),
+            },
+            Sequence::Enumerate(source, index) => match source.next(env)? {
+                Some(value) => {
+                    let position = object::Object::Integer(object::Integer { value: *index });
+                    *index += 1;
+                    Ok(Some(pair(position, value)))
+                }
+                None => Ok(None),
+            },
+            Sequence::Chain(first, second) => match first.next(env)? {
+                Some(value) => Ok(Some(value)),
+                None => second.next(env),
+            },
+            Sequence::FlatMap(source, fun, current) => loop {
+                if let Some(current) = current {
+                    if let Some(value) = current.next(env)? {
+                        return Ok(Some(value));
+                    }
+                }
+
+                match source.next(env)? {
+                    Some(value) => {
+                        let mapped = call(fun, vec![value], env)?;
+                        *current = Some(Box::new(Sequence::new(mapped, env)?));
+                    }
+                    None => return Ok(None),
+                }
+            },
+        }
+    }
+
+    // Values left in the sequence.
+    pub fn collect(
+        &mut self,
+        env: &environment::Environment,
+    ) -> Result<Vec<object::Object>, object::Object> {
+        let mut values = Vec::new();
+        while let Some(value) = self.next(env)? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+// Values of a user type, as its `iter` gives them.
+pub fn iterated(
+    value: object::Object,
+    env: &environment::Environment,
+) -> Result<object::Object, object::Object> {
+    let type_name = value.object_type();
+    let method = evaluator::find_protocol_method(&value, "Iterable", "iter", env)
+        .ok_or_else(|| evaluator::new_error(format!("cannot iterate over {}", type_name)))?;
+
+    match evaluator::apply_function(method, vec![value], Vec::new(), env) {
+        err @ object::Object::Error(_) => Err(err),
+        iterated @ object::Object::Array(_)
+        | iterated @ object::Object::Generator(_)
+        | iterated @ object::Object::Seq(_) => Ok(iterated),
+        other => Err(evaluator::new_error(format!(
+            "`iter` for {} must return ARRAY, GENERATOR or SEQ, got {}",
+            type_name,
+            other.object_type()
+        ))),
+    }
+}
+
+pub fn seq(stage: Rc<Stage>) -> object::Object {
+    object::Object::Seq(object::Seq { stage })
+}
+
+// Sequence without its first `count` values. Skipping is folded into
+// the source where possible, so `rest` of `rest` doesn't nest.
+pub fn drop(source: Rc<Stage>, count: usize) -> Rc<Stage> {
+    match &*source {
+        Stage::Elements { elements, start } => Rc::new(Stage::Elements {
+            elements: elements.clone(),
+            start: start.saturating_add(count).min(elements.len()),
+        }),
+        Stage::Drop(inner, skipped) => Rc::new(Stage::Drop(inner.clone(), skipped + count)),
+        _ => Rc::new(Stage::Drop(source, count)),
+    }
+}
+
+// Arrays are walked by index, everything else is converted
+// to a `Sequence` when it's walked.
+pub fn stage(value: object::Object) -> Rc<Stage> {
+    match value {
+        object::Object::Seq(seq) => seq.stage,
+        object::Object::Array(array) => Rc::new(Stage::Elements {
+            elements: array.elements,
+            start: 0,
+        }),
+        value => Rc::new(Stage::Iterable(value)),
+    }
+}
+
+// Number of values for `take` and `drop`.
+pub fn count(function_name: &str, value: &object::Object) -> Result<usize, object::Object> {
+    match value {
+        object::Object::Integer(integer) if integer.value >= 0 => Ok(integer.value as usize),
+        value => Err(evaluator::new_error(format!(
+            "argument to `{}` must not be negative, got {}",
+            function_name,
+            value.inspect()
+        ))),
+    }
+}
+
+fn call(
+    fun: &object::Object,
+    args: Vec<object::Object>,
+    env: &environment::Environment,
+) -> Result<object::Object, object::Object> {
+    let result = evaluator::apply_function(fun.clone(), args, Vec::new(), env);
+
+    if evaluator::is_error(&result) {
+        Err(result)
+    } else {
+        Ok(result)
+    }
+}
+
+fn pair(left: object::Object, right: object::Object) -> object::Object {
+    object::Object::Array(object::Array {
+        elements: vec![left, right].into(),
+    })
+}
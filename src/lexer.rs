@@ -7,6 +7,8 @@ pub struct Lexer {
     position: u32,
     read_position: u32,
     ch: u8,
+    // Position of the first character of the last returned token.
+    token_start: u32,
 }
 
 impl Lexer {
@@ -41,6 +43,7 @@ impl Lexer {
     // TODO: rethink types. Current one are not very nice.
     pub fn next_token(&mut self) -> token::Token {
         self.skip_whitespace();
+        self.token_start = self.position;
 
         let tok = match self.ch {
             b'=' => {
@@ -52,6 +55,14 @@ impl Lexer {
                         token_type: token::EQ.to_string(),
                         literal,
                     }
+                } else if self.peek_char() == b'>' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::FAT_ARROW.to_string(),
+                        literal,
+                    }
                 } else {
                     token::Token {
                         token_type: token::ASSIGN.to_string(),
@@ -83,22 +94,157 @@ impl Lexer {
                     }
                 }
             }
-            b'/' => token::Token {
-                token_type: token::SLASH.to_string(),
+            b'&' => {
+                if self.peek_char() == b'&' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::AND.to_string(),
+                        literal,
+                    }
+                } else {
+                    token::Token {
+                        token_type: token::BIT_AND.to_string(),
+                        literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
+                    }
+                }
+            }
+            b'|' => {
+                if self.peek_char() == b'|' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::OR.to_string(),
+                        literal,
+                    }
+                } else if self.peek_char() == b'>' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::PIPE.to_string(),
+                        literal,
+                    }
+                } else {
+                    token::Token {
+                        token_type: token::BIT_OR.to_string(),
+                        literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
+                    }
+                }
+            }
+            b'^' => token::Token {
+                token_type: token::BIT_XOR.to_string(),
                 literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
             },
-            b'*' => token::Token {
-                token_type: token::ASTERISK.to_string(),
+            b'%' => token::Token {
+                token_type: token::PERCENT.to_string(),
                 literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
             },
-            b'<' => token::Token {
-                token_type: token::LT.to_string(),
+            b'/' => token::Token {
+                token_type: token::SLASH.to_string(),
                 literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
             },
-            b'>' => token::Token {
-                token_type: token::GT.to_string(),
+            b'*' => {
+                if self.peek_char() == b'*' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::POWER.to_string(),
+                        literal,
+                    }
+                } else {
+                    token::Token {
+                        token_type: token::ASTERISK.to_string(),
+                        literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
+                    }
+                }
+            }
+            b'<' => {
+                if self.peek_char() == b'=' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::LT_EQ.to_string(),
+                        literal,
+                    }
+                } else if self.peek_char() == b'<' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::SHIFT_LEFT.to_string(),
+                        literal,
+                    }
+                } else {
+                    token::Token {
+                        token_type: token::LT.to_string(),
+                        literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
+                    }
+                }
+            }
+            b'>' => {
+                if self.peek_char() == b'=' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::GT_EQ.to_string(),
+                        literal,
+                    }
+                } else if self.peek_char() == b'>' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::SHIFT_RIGHT.to_string(),
+                        literal,
+                    }
+                } else {
+                    token::Token {
+                        token_type: token::GT.to_string(),
+                        literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
+                    }
+                }
+            }
+            b':' => token::Token {
+                token_type: token::COLON.to_string(),
                 literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
             },
+            b'.' => {
+                if self.peek_char() == b'.' && self.peek_nth_char(2) == b'.' {
+                    self.read_char();
+                    self.read_char();
+                    token::Token {
+                        token_type: token::ELLIPSIS.to_string(),
+                        literal: "...".to_string(),
+                    }
+                } else {
+                    token::Token {
+                        token_type: token::DOT.to_string(),
+                        literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
+                    }
+                }
+            }
+            b'#' => {
+                if self.peek_char() == b'(' {
+                    let ch = self.ch;
+                    self.read_char();
+                    let literal = [ch, self.ch].iter().map(|&c| c as char).collect::<String>();
+                    token::Token {
+                        token_type: token::PLACEHOLDER_FN.to_string(),
+                        literal,
+                    }
+                } else {
+                    token::Token {
+                        token_type: token::ILLEGAL.to_string(),
+                        literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
+                    }
+                }
+            }
             b';' => token::Token {
                 token_type: token::SEMICOLON.to_string(),
                 literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
@@ -111,6 +257,10 @@ impl Lexer {
                 token_type: token::RPAREN.to_string(),
                 literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
             },
+            b'?' => token::Token {
+                token_type: token::QUESTION.to_string(),
+                literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
+            },
             b',' => token::Token {
                 token_type: token::COMMA.to_string(),
                 literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
@@ -123,10 +273,37 @@ impl Lexer {
                 token_type: token::RBRACE.to_string(),
                 literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
             },
-            b'"' => token::Token {
-                token_type: token::STRING.to_string(),
-                literal: self.read_string(),
-            },
+            b'"' => {
+                let literal = if self.peek_char() == b'"' && self.peek_nth_char(2) == b'"' {
+                    self.read_multiline_string()
+                } else {
+                    self.read_string()
+                };
+                let token_type = if literal.contains("${") {
+                    token::INTERPOLATED_STRING
+                } else {
+                    token::STRING
+                };
+
+                token::Token {
+                    token_type: token_type.to_string(),
+                    literal,
+                }
+            }
+            b'\'' => {
+                let literal = self.read_char_literal();
+                let token_type = if self.ch == b'\'' {
+                    token::CHAR
+                } else {
+                    // unterminated literal
+                    token::ILLEGAL
+                };
+
+                token::Token {
+                    token_type: token_type.to_string(),
+                    literal,
+                }
+            }
             b'[' => token::Token {
                 token_type: token::LBRACKET.to_string(),
                 literal: [self.ch].iter().map(|&c| c as char).collect::<String>(),
@@ -142,7 +319,12 @@ impl Lexer {
             _ => {
                 let character = self.ch as char;
 
-                if character.is_ascii_alphabetic() || character == '_' {
+                if character == 'r' && self.is_raw_string_ahead() {
+                    token::Token {
+                        token_type: token::STRING.to_string(),
+                        literal: self.read_raw_string(),
+                    }
+                } else if character.is_ascii_alphabetic() || character == '_' {
                     let literal = self.read_identifier();
                     let token_type = token::lookup_ident(literal.clone());
                     // We need early return here because
@@ -152,7 +334,7 @@ impl Lexer {
                         token_type,
                         literal,
                     };
-                } else if character.is_digit(10) {
+                } else if character.is_ascii_digit() {
                     // TODO: Add linenumber and position to track the error
                     // and show output to user.
                     let literal = self.read_number();
@@ -189,26 +371,232 @@ impl Lexer {
             .collect::<String>()
     }
 
+    // Interpolated parts `${...}` may contain their own strings and braces,
+    // so `"` closes the string only outside of them.
     fn read_string(&mut self) -> String {
         let position = self.position + 1; // ignore opening `"`
+        let mut depth = 0;
 
         loop {
             self.read_char();
 
-            if self.ch == b'"' || self.ch == 0 {
-                break
+            match self.ch {
+                0 => break,
+                b'"' if depth == 0 => break,
+                b'"' => {
+                    // nested string inside of `${...}`
+                    self.read_string();
+                }
+                b'$' if self.peek_char() == b'{' => {
+                    self.read_char();
+                    depth += 1;
+                }
+                b'{' if depth > 0 => depth += 1,
+                b'}' if depth > 0 => depth -= 1,
+                _ => (),
             }
         }
 
-        self.input.as_bytes()[position as usize..self.position as usize]
-            .iter()
-            .map(|&c| c as char)
-            .collect::<String>()
+        // Input is walked byte by byte, decode the whole slice to keep
+        // non-ASCII characters intact.
+        String::from_utf8_lossy(&self.input.as_bytes()[position as usize..self.position as usize])
+            .to_string()
+    }
+
+    // Source of the char literal between quotes, escapes are kept
+    // as they are and decoded by the parser.
+    // Stops on the closing `'`, the end of line or the end of input.
+    fn read_char_literal(&mut self) -> String {
+        let position = self.position + 1; // ignore opening `'`
+
+        loop {
+            self.read_char();
+
+            match self.ch {
+                b'\\' if self.peek_char() != 0 && self.peek_char() != b'\n' => {
+                    self.read_char();
+                }
+                b'\'' | b'\n' | 0 => break,
+                _ => (),
+            }
+        }
+
+        String::from_utf8_lossy(&self.input.as_bytes()[position as usize..self.position as usize])
+            .to_string()
+    }
+
+    // Triple-quoted string, the cursor is on the first `"`.
+    // It's left on the last `"` of the closing `"""`.
+    fn read_multiline_string(&mut self) -> String {
+        self.read_char();
+        self.read_char();
+
+        let position = self.position + 1; // ignore opening `"""`
+        let mut depth = 0;
+
+        loop {
+            self.read_char();
+
+            match self.ch {
+                0 => break,
+                b'"' if depth == 0
+                    && self.peek_char() == b'"'
+                    && self.peek_nth_char(2) == b'"' =>
+                {
+                    break
+                }
+                b'"' if depth > 0 => {
+                    self.read_string();
+                }
+                b'$' if self.peek_char() == b'{' => {
+                    self.read_char();
+                    depth += 1;
+                }
+                b'{' if depth > 0 => depth += 1,
+                b'}' if depth > 0 => depth -= 1,
+                _ => (),
+            }
+        }
+
+        let literal = String::from_utf8_lossy(
+            &self.input.as_bytes()[position as usize..self.position as usize],
+        )
+        .to_string();
+
+        if self.ch != 0 {
+            self.read_char();
+            self.read_char();
+        }
+
+        strip_indentation(&literal)
+    }
+
+    // Raw string `r"..."` or `r#"..."#`, the cursor is on `r`.
+    // Number of `#` after the closing `"` must be the same as before
+    // the opening one, so the string may contain `"` itself.
+    // Content is taken as is, no interpolation.
+    fn read_raw_string(&mut self) -> String {
+        self.read_char(); // skip `r`
+
+        let mut hashes = 0;
+        while self.ch == b'#' {
+            hashes += 1;
+            self.read_char();
+        }
+
+        let position = self.position + 1; // ignore opening `"`
+
+        loop {
+            self.read_char();
+
+            if self.ch == 0 {
+                break;
+            }
+
+            if self.ch == b'"' && (1..=hashes).all(|n| self.peek_nth_char(n) == b'#') {
+                break;
+            }
+        }
+
+        let literal = String::from_utf8_lossy(
+            &self.input.as_bytes()[position as usize..self.position as usize],
+        )
+        .to_string();
+
+        for _ in 0..hashes {
+            self.read_char();
+        }
+
+        literal
+    }
+
+    fn is_raw_string_ahead(&self) -> bool {
+        let mut n = 1;
+        while self.peek_nth_char(n) == b'#' {
+            n += 1;
+        }
+
+        self.peek_nth_char(n) == b'"'
+    }
+
+    // Splits the literal of `INTERPOLATED_STRING` token into
+    // plain text and source code of `${...}` parts.
+    pub fn split_interpolated_string(literal: &str) -> Vec<token::StringPart> {
+        let mut lexer = Lexer::new(literal.to_string());
+        let mut parts = vec![];
+        let mut text = vec![];
+
+        while lexer.ch != 0 {
+            if lexer.ch == b'$' && lexer.peek_char() == b'{' {
+                lexer.read_char(); // skip `$`
+                lexer.read_char(); // skip `{`
+
+                let position = lexer.position;
+                let mut depth = 1;
+                while lexer.ch != 0 {
+                    match lexer.ch {
+                        b'"' => {
+                            lexer.read_string();
+                        }
+                        b'{' => depth += 1,
+                        b'}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => (),
+                    }
+                    lexer.read_char();
+                }
+
+                if !text.is_empty() {
+                    parts.push(token::StringPart::Text(
+                        String::from_utf8_lossy(&text).to_string(),
+                    ));
+                    text.clear();
+                }
+                parts.push(token::StringPart::Code(
+                    literal[position as usize..lexer.position as usize].to_string(),
+                ));
+            } else {
+                text.push(lexer.ch);
+            }
+
+            lexer.read_char();
+        }
+
+        if !text.is_empty() {
+            parts.push(token::StringPart::Text(
+                String::from_utf8_lossy(&text).to_string(),
+            ));
+        }
+
+        parts
     }
 
+    pub fn token_start(&self) -> u32 {
+        self.token_start
+    }
+
+    // Line and column of the position, both start from 1.
+    pub fn location(&self, position: u32) -> (usize, usize) {
+        let before = &self.input.as_bytes()[..(position as usize).min(self.input.len())];
+        let line = before.iter().filter(|&&c| c == b'\n').count() + 1;
+        let column = match before.iter().rposition(|&c| c == b'\n') {
+            Some(newline) => before.len() - newline,
+            None => before.len() + 1,
+        };
+
+        (line, column)
+    }
+
+    // Reads `42`, `1_000`, `0xFF`, `0o755` and `0b1010`.
+    // Letters are consumed as well, so `12abc` is a single (invalid)
+    // literal and parser reports it as a whole.
     fn read_number(&mut self) -> String {
         let position = self.position;
-        while (self.ch as char).is_digit(10) {
+        while self.ch.is_ascii_alphanumeric() || self.ch == b'_' {
             self.read_char()
         }
 
@@ -226,6 +614,16 @@ impl Lexer {
         }
     }
 
+    // Looks `n` characters ahead, `peek_nth_char(1)` is the same as `peek_char()`.
+    fn peek_nth_char(&self, n: u32) -> u8 {
+        let position = self.position + n;
+        if position >= self.input.len() as u32 {
+            0
+        } else {
+            self.input.as_bytes()[position as usize]
+        }
+    }
+
     fn is_letter(&self, c: u8) -> bool {
         let character = c as char;
         character.is_ascii_alphabetic() || character == '_'
@@ -238,6 +636,55 @@ impl Lexer {
     }
 }
 
+// Removes the line break right after opening `"""`, the line with
+// closing `"""` and the common indentation of the rest lines:
+//
+//     """
+//       SELECT *
+//         FROM users
+//       """
+//
+// becomes "SELECT *\n  FROM users".
+fn strip_indentation(literal: &str) -> String {
+    let mut lines = literal.split('\n').collect::<Vec<_>>();
+
+    if lines.len() > 1 && lines[0].trim().is_empty() {
+        lines.remove(0);
+    }
+
+    let indentation_of = |line: &str| line.len() - line.trim_start_matches([' ', '\t']).len();
+
+    // The closing line takes part in the indentation,
+    // so text may be indented relative to `"""`.
+    let mut indentation = match lines.last() {
+        Some(last) if lines.len() > 1 && last.trim().is_empty() => {
+            let indentation = indentation_of(last);
+            lines.pop();
+            Some(indentation)
+        }
+        _ => None,
+    };
+
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        let line_indentation = indentation_of(line);
+        indentation = Some(indentation.map_or(line_indentation, |i| i.min(line_indentation)));
+    }
+
+    let indentation = indentation.unwrap_or(0);
+
+    lines
+        .into_iter()
+        .map(|line| {
+            if line.trim().is_empty() {
+                ""
+            } else {
+                &line[indentation..]
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::lexer::Lexer;
@@ -378,6 +825,311 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_extended_operators() {
+        let input = r###"1 <= 2 >= 3 % 4 ** 5 & 6 | 7 ^ 8 << 9 >> 10 * 11 |> f"###.to_string();
+
+        let expected = vec![
+            (token::INT.to_string(), String::from("1")),
+            (token::LT_EQ.to_string(), String::from("<=")),
+            (token::INT.to_string(), String::from("2")),
+            (token::GT_EQ.to_string(), String::from(">=")),
+            (token::INT.to_string(), String::from("3")),
+            (token::PERCENT.to_string(), String::from("%")),
+            (token::INT.to_string(), String::from("4")),
+            (token::POWER.to_string(), String::from("**")),
+            (token::INT.to_string(), String::from("5")),
+            (token::BIT_AND.to_string(), String::from("&")),
+            (token::INT.to_string(), String::from("6")),
+            (token::BIT_OR.to_string(), String::from("|")),
+            (token::INT.to_string(), String::from("7")),
+            (token::BIT_XOR.to_string(), String::from("^")),
+            (token::INT.to_string(), String::from("8")),
+            (token::SHIFT_LEFT.to_string(), String::from("<<")),
+            (token::INT.to_string(), String::from("9")),
+            (token::SHIFT_RIGHT.to_string(), String::from(">>")),
+            (token::INT.to_string(), String::from("10")),
+            (token::ASTERISK.to_string(), String::from("*")),
+            (token::INT.to_string(), String::from("11")),
+            (token::PIPE.to_string(), String::from("|>")),
+            (token::IDENT.to_string(), String::from("f")),
+            (token::EOF.to_string(), String::from("")),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        expected.into_iter().for_each(|(token_type, literal)| {
+            let token = lexer.next_token();
+
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+        });
+    }
+
+    #[test]
+    fn test_destructuring_delimiters() {
+        let input = r###"let [a, ...b] = {"c": 1};"###.to_string();
+
+        let expected = vec![
+            (token::LET.to_string(), String::from("let")),
+            (token::LBRACKET.to_string(), String::from("[")),
+            (token::IDENT.to_string(), String::from("a")),
+            (token::COMMA.to_string(), String::from(",")),
+            (token::ELLIPSIS.to_string(), String::from("...")),
+            (token::IDENT.to_string(), String::from("b")),
+            (token::RBRACKET.to_string(), String::from("]")),
+            (token::ASSIGN.to_string(), String::from("=")),
+            (token::LBRACE.to_string(), String::from("{")),
+            (token::STRING.to_string(), String::from("c")),
+            (token::COLON.to_string(), String::from(":")),
+            (token::INT.to_string(), String::from("1")),
+            (token::RBRACE.to_string(), String::from("}")),
+            (token::SEMICOLON.to_string(), String::from(";")),
+            (token::EOF.to_string(), String::from("")),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        expected.into_iter().for_each(|(token_type, literal)| {
+            let token = lexer.next_token();
+
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+        });
+    }
+
+    #[test]
+    fn test_type_declaration_tokens() {
+        let input = "struct Point { x } p.x p with { x: 1 } enum impl Add for throw try catch finally r? macro import \"m\" as m export yield x in"
+            .to_string();
+
+        let expected = vec![
+            (token::STRUCT.to_string(), String::from("struct")),
+            (token::IDENT.to_string(), String::from("Point")),
+            (token::LBRACE.to_string(), String::from("{")),
+            (token::IDENT.to_string(), String::from("x")),
+            (token::RBRACE.to_string(), String::from("}")),
+            (token::IDENT.to_string(), String::from("p")),
+            (token::DOT.to_string(), String::from(".")),
+            (token::IDENT.to_string(), String::from("x")),
+            (token::IDENT.to_string(), String::from("p")),
+            (token::WITH.to_string(), String::from("with")),
+            (token::LBRACE.to_string(), String::from("{")),
+            (token::IDENT.to_string(), String::from("x")),
+            (token::COLON.to_string(), String::from(":")),
+            (token::INT.to_string(), String::from("1")),
+            (token::RBRACE.to_string(), String::from("}")),
+            (token::ENUM.to_string(), String::from("enum")),
+            (token::IMPL.to_string(), String::from("impl")),
+            (token::IDENT.to_string(), String::from("Add")),
+            (token::FOR.to_string(), String::from("for")),
+            (token::THROW.to_string(), String::from("throw")),
+            (token::TRY.to_string(), String::from("try")),
+            (token::CATCH.to_string(), String::from("catch")),
+            (token::FINALLY.to_string(), String::from("finally")),
+            (token::IDENT.to_string(), String::from("r")),
+            (token::QUESTION.to_string(), String::from("?")),
+            (token::MACRO.to_string(), String::from("macro")),
+            (token::IMPORT.to_string(), String::from("import")),
+            (token::STRING.to_string(), String::from("m")),
+            (token::AS.to_string(), String::from("as")),
+            (token::IDENT.to_string(), String::from("m")),
+            (token::EXPORT.to_string(), String::from("export")),
+            (token::YIELD.to_string(), String::from("yield")),
+            (token::IDENT.to_string(), String::from("x")),
+            (token::IN.to_string(), String::from("in")),
+            (token::EOF.to_string(), String::from("")),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (expected_type, expected_literal) in expected {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, expected_type);
+            assert_eq!(token.literal, expected_literal);
+        }
+    }
+
+    #[test]
+    fn test_char_literals() {
+        let input = r"'a' '\n' '\'' '\\' 'ж' '\u{1F600}' 'ab' 'x".to_string();
+
+        let expected = vec![
+            (token::CHAR.to_string(), String::from("a")),
+            (token::CHAR.to_string(), String::from(r"\n")),
+            (token::CHAR.to_string(), String::from(r"\'")),
+            (token::CHAR.to_string(), String::from(r"\\")),
+            (token::CHAR.to_string(), String::from("ж")),
+            (token::CHAR.to_string(), String::from(r"\u{1F600}")),
+            (token::CHAR.to_string(), String::from("ab")),
+            (token::ILLEGAL.to_string(), String::from("x")),
+            (token::EOF.to_string(), String::from("")),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (expected_type, expected_literal) in expected {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, expected_type);
+            assert_eq!(token.literal, expected_literal);
+        }
+    }
+
+    #[test]
+    fn test_number_literals() {
+        let input = "42 1_000_000 0xFF 0o755 0b1010 12abc -7;".to_string();
+
+        let expected = vec![
+            (token::INT.to_string(), String::from("42")),
+            (token::INT.to_string(), String::from("1_000_000")),
+            (token::INT.to_string(), String::from("0xFF")),
+            (token::INT.to_string(), String::from("0o755")),
+            (token::INT.to_string(), String::from("0b1010")),
+            (token::INT.to_string(), String::from("12abc")),
+            (token::MINUS.to_string(), String::from("-")),
+            (token::INT.to_string(), String::from("7")),
+            (token::SEMICOLON.to_string(), String::from(";")),
+            (token::EOF.to_string(), String::from("")),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (expected_type, expected_literal) in expected {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, expected_type);
+            assert_eq!(token.literal, expected_literal);
+        }
+    }
+
+    #[test]
+    fn test_token_location() {
+        let mut lexer = Lexer::new("let x\n  = 5;".to_string());
+
+        let locations = (0..4)
+            .map(|_| {
+                lexer.next_token();
+                lexer.location(lexer.token_start())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(locations, vec![(1, 1), (1, 5), (2, 3), (2, 5)]);
+    }
+
+    #[test]
+    fn test_multiline_and_raw_strings() {
+        let input = "let sql = \"\"\"
+            SELECT *
+              FROM users
+             WHERE id = ${id}
+
+            \"\"\";
+        \"\"\"one line\"\"\" \"\" r\"C:\\path\" r#\"say \"hi\"\"# rust r##\"a\"#b\"## r"
+            .to_string();
+
+        let expected = vec![
+            (token::LET.to_string(), String::from("let")),
+            (token::IDENT.to_string(), String::from("sql")),
+            (token::ASSIGN.to_string(), String::from("=")),
+            (
+                token::INTERPOLATED_STRING.to_string(),
+                String::from("SELECT *\n  FROM users\n WHERE id = ${id}\n"),
+            ),
+            (token::SEMICOLON.to_string(), String::from(";")),
+            (token::STRING.to_string(), String::from("one line")),
+            (token::STRING.to_string(), String::from("")),
+            (token::STRING.to_string(), String::from("C:\\path")),
+            (token::STRING.to_string(), String::from("say \"hi\"")),
+            (token::IDENT.to_string(), String::from("rust")),
+            (token::STRING.to_string(), String::from("a\"#b")),
+            (token::IDENT.to_string(), String::from("r")),
+            (token::EOF.to_string(), String::from("")),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (expected_type, expected_literal) in expected {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, expected_type);
+            assert_eq!(token.literal, expected_literal);
+        }
+
+        assert_eq!(
+            Lexer::new(r#"r"${raw}""#.to_string()).next_token().token_type,
+            token::STRING.to_string()
+        );
+    }
+
+    #[test]
+    fn test_interpolated_string_tokens() {
+        let input = r###""a ${b} c" "${"}"}" "$5" x"###.to_string();
+
+        let expected = vec![
+            (
+                token::INTERPOLATED_STRING.to_string(),
+                String::from("a ${b} c"),
+            ),
+            (
+                token::INTERPOLATED_STRING.to_string(),
+                String::from(r#"${"}"}"#),
+            ),
+            (token::STRING.to_string(), String::from("$5")),
+            (token::IDENT.to_string(), String::from("x")),
+            (token::EOF.to_string(), String::from("")),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for (expected_type, expected_literal) in expected {
+            let token = lexer.next_token();
+            assert_eq!(token.token_type, expected_type);
+            assert_eq!(token.literal, expected_literal);
+        }
+
+        assert_eq!(
+            Lexer::split_interpolated_string(r#"a ${b + "}"} c${d}"#),
+            vec![
+                token::StringPart::Text("a ".to_string()),
+                token::StringPart::Code(r#"b + "}""#.to_string()),
+                token::StringPart::Text(" c".to_string()),
+                token::StringPart::Code("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_and_lambda_tokens() {
+        let input = r###"match (x) { 1 => 2, _ => 3 } #(_)"###.to_string();
+
+        let expected = vec![
+            (token::MATCH.to_string(), String::from("match")),
+            (token::LPAREN.to_string(), String::from("(")),
+            (token::IDENT.to_string(), String::from("x")),
+            (token::RPAREN.to_string(), String::from(")")),
+            (token::LBRACE.to_string(), String::from("{")),
+            (token::INT.to_string(), String::from("1")),
+            (token::FAT_ARROW.to_string(), String::from("=>")),
+            (token::INT.to_string(), String::from("2")),
+            (token::COMMA.to_string(), String::from(",")),
+            (token::IDENT.to_string(), String::from("_")),
+            (token::FAT_ARROW.to_string(), String::from("=>")),
+            (token::INT.to_string(), String::from("3")),
+            (token::RBRACE.to_string(), String::from("}")),
+            (token::PLACEHOLDER_FN.to_string(), String::from("#(")),
+            (token::IDENT.to_string(), String::from("_")),
+            (token::RPAREN.to_string(), String::from(")")),
+            (token::EOF.to_string(), String::from("")),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        expected.into_iter().for_each(|(token_type, literal)| {
+            let token = lexer.next_token();
+
+            assert_eq!(token.token_type, token_type);
+            assert_eq!(token.literal, literal);
+        });
+    }
+
     #[test]
     fn test_flow_control_keywords() {
         let input = r###"
@@ -421,7 +1173,7 @@ mod tests {
 
     #[test]
     fn test_multichar_operators() {
-        let input = r###"1 == 1 1 != 2"###.to_string();
+        let input = r###"1 == 1 1 != 2 true && false || true"###.to_string();
 
         let expected = vec![
             (token::INT.to_string(), String::from("1")),
@@ -430,6 +1182,11 @@ mod tests {
             (token::INT.to_string(), String::from("1")),
             (token::NOT_EQ.to_string(), String::from("!=")),
             (token::INT.to_string(), String::from("2")),
+            (token::TRUE.to_string(), String::from("true")),
+            (token::AND.to_string(), String::from("&&")),
+            (token::FALSE.to_string(), String::from("false")),
+            (token::OR.to_string(), String::from("||")),
+            (token::TRUE.to_string(), String::from("true")),
         ];
 
         let mut lexer = Lexer::new(input);
@@ -5,19 +5,113 @@ mod token;
 mod repl;
 mod ast;
 mod parser;
+mod package;
 mod evaluation {
     pub mod object;
     pub mod evaluator;
     pub mod environment;
+    pub mod macro_expansion;
+    pub mod modules;
+    pub mod generators;
+    pub mod seqs;
 }
 mod core {
     pub mod funcs;
+    pub mod registry;
 }
 
 fn main() {
+    // `clojurium resolve` locks dependencies of the project in the current
+    // directory, `clojurium run` runs it and `clojurium <file>` runs the file.
+    // Without arguments we start the REPL.
+    if let Some(command) = std::env::args().nth(1) {
+        let code = match command.as_str() {
+            "resolve" => resolve_project(),
+            "run" => run_project(),
+            file => {
+                let env = evaluation::environment::Environment::new();
+                run_file(std::path::Path::new(file), &env)
+            }
+        };
+        std::process::exit(code);
+    }
+
     // how to get current user name in crossplatform style?
     let _username = "user".to_string();
     println!("Dear user, welcome to Clojurium REPL!");
     println!("Some more advanced description of what's going on here");
     repl::start();
 }
+
+fn resolve_project() -> i32 {
+    let project = std::path::Path::new("");
+    let resolved = package::read_manifest(project).and_then(|manifest| {
+        let packages = package::resolve(project, &manifest)?;
+        package::write_lockfile(project, &packages)?;
+        Ok(packages)
+    });
+
+    match resolved {
+        Ok(packages) => {
+            for package in packages {
+                println!("{} {} ({})", package.name, package.version, package.root.display());
+            }
+            0
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            1
+        }
+    }
+}
+
+fn run_project() -> i32 {
+    let project = std::path::Path::new("");
+    let env = evaluation::environment::Environment::new();
+    let entry = package::read_manifest(project).and_then(|manifest| {
+        let packages = package::load_packages(project, &manifest)?;
+        package::register_packages(project, &packages, &env)?;
+        Ok(project.join(manifest.entry))
+    });
+
+    match entry {
+        Ok(entry) => run_file(&entry, &env),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            1
+        }
+    }
+}
+
+fn run_file(file: &std::path::Path, env: &evaluation::environment::Environment) -> i32 {
+    use evaluation::object::{Object, ObjectT};
+
+    let evaluated = evaluation::modules::run_file(file, env);
+
+    for warning in env.modules().borrow_mut().take_warnings() {
+        eprintln!("warning: {}", warning);
+    }
+
+    let evaluated = match evaluated {
+        Object::Error(err) => {
+            match err.location {
+                Some(location) => eprintln!("error at {}: {}", location, err.message),
+                None => eprintln!("error: {}", err.message),
+            }
+            return 1;
+        }
+        evaluated => evaluated,
+    };
+
+    // There is no printing function yet, so we show the last value as REPL does.
+    match evaluation::evaluator::display(&evaluated, env) {
+        Ok(shown) => {
+            println!("{}", shown);
+            0
+        }
+        Err(err) => {
+            eprintln!("error: {}", err.inspect());
+            1
+        }
+    }
+}
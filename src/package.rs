@@ -0,0 +1,771 @@
+// Projects and their dependencies.
+//
+// Project is a directory with the `project.clm` manifest, a hash literal:
+//
+//     {
+//       "name": "app",
+//       "version": "0.1.0",
+//       "entry": "main.clm",
+//       "registry": "vendor",
+//       "dependencies": {
+//         "math": {"path": "../math"},
+//         "json": "1.2.0"
+//       }
+//     }
+//
+// Only `name` and `version` are required. A dependency is either a local
+// directory with a project of its own or a package vendored into the
+// registry directory, which keeps every version as `<registry>/<name>/<version>/`
+// or as an archive `<registry>/<name>/<version>.tar.gz`. Path dependencies
+// may point at such an archive too. Archives are unpacked into
+// `.clojurium/packages/` of the project, with the package files at the top
+// of the archive or in its only directory. Version `"*"` takes the newest
+// one. Nothing is downloaded, so all of it works offline.
+//
+// Resolved packages are written to `project.lock` and every one of them
+// can be imported by name: `import "json" as json;` loads the entry file
+// of `json`, `import "json/parser" as parser;` a file next to it.
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+
+use crate::evaluation::environment;
+use crate::evaluation::evaluator;
+use crate::evaluation::object;
+use crate::evaluation::object::ObjectT;
+use crate::parser;
+
+pub const MANIFEST: &str = "project.clm";
+pub const LOCKFILE: &str = "project.lock";
+
+const DEFAULT_ENTRY: &str = "main.clm";
+const DEFAULT_REGISTRY: &str = "vendor";
+
+const ARCHIVE_EXTENSION: &str = ".tar.gz";
+const UNPACKED: &str = ".clojurium/packages";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    pub entry: String,
+    pub registry: String,
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub name: String,
+    pub source: Source,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    // Directory or archive relative to the project which depends on it.
+    Path(String),
+    // Version in the registry, exact or `*`.
+    Registry(String),
+}
+
+// Resolved dependency. Root is relative to the project directory,
+// without `.` and `name/..` in it. It's a directory or an archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub root: PathBuf,
+}
+
+pub fn read_manifest(directory: &Path) -> Result<Manifest, String> {
+    let file = directory.join(MANIFEST);
+    let manifest = evaluate_file(&file)?;
+    let error = |message: String| format!("{}: {}", file.display(), message);
+
+    let name = string_field(&manifest, "name").map_err(error)?;
+    let version = string_field(&manifest, "version").map_err(error)?;
+    if parse_version(&version).is_none() {
+        return Err(error(format!("invalid version `{}`", version)));
+    }
+
+    let entry = optional_string_field(&manifest, "entry")
+        .map_err(error)?
+        .unwrap_or_else(|| DEFAULT_ENTRY.to_string());
+    let registry = optional_string_field(&manifest, "registry")
+        .map_err(error)?
+        .unwrap_or_else(|| DEFAULT_REGISTRY.to_string());
+
+    let dependencies = match field(&manifest, "dependencies") {
+        None => Vec::new(),
+        Some(object::Object::Hash(dependencies)) => dependencies
+            .pairs
+            .values()
+            .map(|pair| dependency(&pair.key, &pair.value))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)?,
+        Some(other) => {
+            return Err(error(format!(
+                "`dependencies` must be HASH, got {}",
+                other.object_type()
+            )))
+        }
+    };
+
+    Ok(Manifest {
+        name,
+        version,
+        entry,
+        registry,
+        dependencies,
+    })
+}
+
+// Finds a package for every dependency of the project, dependencies
+// of dependencies included. Each package is taken once, requiring it
+// from two different places is a conflict.
+pub fn resolve(project: &Path, manifest: &Manifest) -> Result<Vec<Package>, String> {
+    let registry = PathBuf::from(&manifest.registry);
+    let mut resolved: BTreeMap<String, Package> = BTreeMap::new();
+    let mut queue: VecDeque<(PathBuf, Dependency)> = manifest
+        .dependencies
+        .iter()
+        .map(|dependency| (PathBuf::new(), dependency.clone()))
+        .collect();
+
+    while let Some((requester, dependency)) = queue.pop_front() {
+        let root = match &dependency.source {
+            Source::Path(path) => normalize(&requester.join(path)),
+            Source::Registry(requirement) => {
+                find_version(project, &registry, &dependency.name, requirement)?
+            }
+        };
+
+        // Path dependencies of an archived package are relative to its files.
+        let directory = package_directory(project, &dependency.name, &root)?;
+        let package_manifest = read_manifest(&project.join(&directory))?;
+        if package_manifest.name != dependency.name {
+            return Err(format!(
+                "dependency `{}` at {} is a package named `{}`",
+                dependency.name,
+                root.display(),
+                package_manifest.name
+            ));
+        }
+
+        let package = Package {
+            name: dependency.name.clone(),
+            version: package_manifest.version,
+            root: root.clone(),
+        };
+
+        match resolved.get(&package.name) {
+            Some(existing) if same_package(project, existing, &package) => continue,
+            Some(existing) => {
+                return Err(format!(
+                    "conflicting requirements for package `{}`: {} at {} and {} at {}",
+                    package.name,
+                    existing.version,
+                    existing.root.display(),
+                    package.version,
+                    package.root.display()
+                ))
+            }
+            None => (),
+        }
+
+        for nested in package_manifest.dependencies {
+            queue.push_back((directory.clone(), nested));
+        }
+        resolved.insert(package.name.clone(), package);
+    }
+
+    Ok(resolved.into_values().collect())
+}
+
+pub fn write_lockfile(project: &Path, packages: &[Package]) -> Result<(), String> {
+    let mut entries = Vec::new();
+    for package in packages {
+        let root = package.root.display().to_string();
+        // Strings have no escapes, so the lockfile can't hold these.
+        if root.contains('"') || root.contains("${") {
+            return Err(format!("cannot lock path {} of package `{}`", root, package.name));
+        }
+        entries.push(format!(
+            "  {{\"name\": \"{}\", \"version\": \"{}\", \"root\": \"{}\"}}",
+            package.name, package.version, root
+        ));
+    }
+
+    let lockfile = format!("[\n{}\n]\n", entries.join(",\n"));
+    let file = project.join(LOCKFILE);
+    fs::write(&file, lockfile).map_err(|err| format!("cannot write {}: {}", file.display(), err))
+}
+
+// Packages from the lockfile, None if there is no lockfile.
+pub fn read_lockfile(project: &Path) -> Result<Option<Vec<Package>>, String> {
+    let file = project.join(LOCKFILE);
+    if !file.exists() {
+        return Ok(None);
+    }
+
+    let error = |message: String| format!("{}: {}", file.display(), message);
+    let entries = match evaluate_file(&file)? {
+        object::Object::Array(array) => array.elements,
+        other => return Err(error(format!("expected ARRAY, got {}", other.object_type()))),
+    };
+
+    entries
+        .iter()
+        .map(|entry| {
+            Ok(Package {
+                name: string_field(entry, "name").map_err(error)?,
+                version: string_field(entry, "version").map_err(error)?,
+                root: PathBuf::from(string_field(entry, "root").map_err(error)?),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+// Locked packages if the lockfile still satisfies the manifest,
+// otherwise dependencies are resolved again and locked.
+pub fn load_packages(project: &Path, manifest: &Manifest) -> Result<Vec<Package>, String> {
+    if let Some(locked) = read_lockfile(project)? {
+        if is_up_to_date(manifest, &locked) {
+            return Ok(locked);
+        }
+    }
+
+    let packages = resolve(project, manifest)?;
+    write_lockfile(project, &packages)?;
+    Ok(packages)
+}
+
+// Makes every package importable by its name.
+pub fn register_packages(
+    project: &Path,
+    packages: &[Package],
+    env: &environment::Environment,
+) -> Result<(), String> {
+    for package in packages {
+        let root = project.join(package_directory(project, &package.name, &package.root)?);
+        let manifest = read_manifest(&root)?;
+        env.modules()
+            .borrow_mut()
+            .add_package(package.name.clone(), root.join(manifest.entry));
+    }
+    Ok(())
+}
+
+// Directory with the files of the package, relative to the project.
+// Archives are unpacked again only when they change.
+fn package_directory(project: &Path, name: &str, root: &Path) -> Result<PathBuf, String> {
+    let stem = root
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .and_then(|file_name| file_name.strip_suffix(ARCHIVE_EXTENSION));
+    let stem = match stem {
+        Some(stem) => stem,
+        None => return Ok(root.to_path_buf()),
+    };
+
+    let archive = project.join(root);
+    let unpacked = normalize(&Path::new(UNPACKED).join(name).join(stem));
+    let target = project.join(&unpacked);
+    let error = |err: std::io::Error| format!("cannot unpack {}: {}", root.display(), err);
+
+    // Target is removed before unpacking, it must be `<name>/<stem>` there.
+    let depth = unpacked.strip_prefix(UNPACKED).map(|path| path.components().count());
+    if depth != Ok(2) {
+        return Err(format!("cannot unpack {} outside of {}", root.display(), UNPACKED));
+    }
+
+    // Unpacked files get the current time, so the directory
+    // is newer than the archive it was unpacked from.
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+    let archive_modified = modified(&archive).map_err(error)?;
+    if modified(&target).map_or(true, |unpacked_at| unpacked_at < archive_modified) {
+        if target.exists() {
+            fs::remove_dir_all(&target).map_err(error)?;
+        }
+        let file = fs::File::open(&archive).map_err(error)?;
+        let mut unpacker = tar::Archive::new(GzDecoder::new(file));
+        unpacker.set_preserve_mtime(false);
+        unpacker.unpack(&target).map_err(error)?;
+    }
+
+    if target.join(MANIFEST).exists() {
+        return Ok(unpacked);
+    }
+
+    // `json-1.2.0/project.clm` and the like.
+    let entries = fs::read_dir(&target)
+        .map_err(error)?
+        .filter_map(|entry| entry.ok())
+        .collect::<Vec<_>>();
+    match entries.as_slice() {
+        [entry] if entry.path().is_dir() => Ok(unpacked.join(entry.file_name())),
+        _ => Ok(unpacked),
+    }
+}
+
+// Packages required from two places are the same one when both
+// places are the same directory, even if spelled differently.
+fn same_package(project: &Path, left: &Package, right: &Package) -> bool {
+    let canonical = |package: &Package| fs::canonicalize(project.join(&package.root)).ok();
+
+    let same_root = match canonical(left) {
+        Some(root) => Some(root) == canonical(right),
+        None => left.root == right.root,
+    };
+
+    left.version == right.version && same_root
+}
+
+// `../util/../math` is `../math`. Leading `..` are kept, the path
+// stays relative to the same directory.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => (),
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+// Only direct dependencies are checked, the lockfile is rewritten
+// whenever one of them changes.
+fn is_up_to_date(manifest: &Manifest, locked: &[Package]) -> bool {
+    let locked: HashMap<&str, &Package> = locked
+        .iter()
+        .map(|package| (package.name.as_str(), package))
+        .collect();
+
+    manifest.dependencies.iter().all(|dependency| {
+        let package = match locked.get(dependency.name.as_str()) {
+            Some(package) => package,
+            None => return false,
+        };
+
+        match &dependency.source {
+            Source::Path(path) => package.root == normalize(Path::new(path)),
+            Source::Registry(requirement) => {
+                matches_requirement(&package.version, requirement)
+                    && package.root.starts_with(&manifest.registry)
+            }
+        }
+    })
+}
+
+// Root of the newest version of the package in the registry matching
+// the requirement. Directory is taken over an archive of the same version.
+fn find_version(
+    project: &Path,
+    registry: &Path,
+    name: &str,
+    requirement: &str,
+) -> Result<PathBuf, String> {
+    let directory = project.join(registry).join(name);
+    let versions = fs::read_dir(&directory)
+        .map_err(|err| format!("package `{}` is not in the registry {}: {}", name, registry.display(), err))?;
+
+    versions
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let is_dir = entry.path().is_dir();
+            let version = match file_name.strip_suffix(ARCHIVE_EXTENSION) {
+                Some(version) if !is_dir => version.to_string(),
+                _ if is_dir => file_name.clone(),
+                _ => return None,
+            };
+            Some((version, is_dir, file_name))
+        })
+        .filter(|(version, _, _)| matches_requirement(version, requirement))
+        .max_by_key(|(version, is_dir, _)| (parse_version(version), *is_dir))
+        .map(|(_, _, file_name)| registry.join(name).join(file_name))
+        .ok_or_else(|| {
+            format!(
+                "no version of package `{}` matching `{}` in the registry {}",
+                name,
+                requirement,
+                registry.display()
+            )
+        })
+}
+
+fn matches_requirement(version: &str, requirement: &str) -> bool {
+    match parse_version(version) {
+        Some(_) if requirement == "*" => true,
+        Some(version) => parse_version(requirement) == Some(version),
+        None => false,
+    }
+}
+
+// `1.2.0` is [1, 2, 0], so versions compare as numbers.
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+fn dependency(key: &object::Object, spec: &object::Object) -> Result<Dependency, String> {
+    let name = match key {
+        object::Object::Stringl(name) => name.value.clone(),
+        other => {
+            return Err(format!(
+                "dependency name must be STRING, got {}",
+                other.object_type()
+            ))
+        }
+    };
+
+    // Names are directories of unpacked archives.
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(format!("invalid name `{}` of dependency", name));
+    }
+
+    let source = match spec {
+        object::Object::Stringl(version) => {
+            if version.value != "*" && parse_version(&version.value).is_none() {
+                return Err(format!(
+                    "invalid version `{}` of dependency `{}`",
+                    version.value, name
+                ));
+            }
+            Source::Registry(version.value.clone())
+        }
+        object::Object::Hash(_) => match optional_string_field(spec, "path")? {
+            Some(path) => Source::Path(path),
+            None => return Err(format!("missing `path` of dependency `{}`", name)),
+        },
+        other => {
+            return Err(format!(
+                "dependency `{}` must be a version STRING or a HASH with `path`, got {}",
+                name,
+                other.object_type()
+            ))
+        }
+    };
+
+    Ok(Dependency { name, source })
+}
+
+fn field<'a>(hash: &'a object::Object, key: &str) -> Option<&'a object::Object> {
+    match hash {
+        object::Object::Hash(hash) => hash
+            .pairs
+            .get(&object::HashKey::Stringl(key.to_string()))
+            .map(|pair| &pair.value),
+        _ => None,
+    }
+}
+
+fn string_field(hash: &object::Object, key: &str) -> Result<String, String> {
+    optional_string_field(hash, key)?.ok_or_else(|| format!("missing `{}`", key))
+}
+
+fn optional_string_field(hash: &object::Object, key: &str) -> Result<Option<String>, String> {
+    let value = match hash {
+        object::Object::Hash(_) => field(hash, key),
+        other => return Err(format!("expected HASH, got {}", other.object_type())),
+    };
+
+    match value {
+        None => Ok(None),
+        Some(object::Object::Stringl(value)) => Ok(Some(value.value.clone())),
+        Some(other) => Err(format!(
+            "`{}` must be STRING, got {}",
+            key,
+            other.object_type()
+        )),
+    }
+}
+
+// Manifest and lockfile are plain Clojurium values.
+fn evaluate_file(file: &Path) -> Result<object::Object, String> {
+    let source = fs::read_to_string(file)
+        .map_err(|err| format!("cannot read {}: {}", file.display(), err))?;
+
+    let (program, parser) = parser::parse_source(&source, parser::builtin_enums());
+
+    if !parser.errors.is_empty() {
+        return Err(format!("{}: {}", file.display(), parser.errors.join("; ")));
+    }
+
+    let mut env = environment::Environment::new();
+    match evaluator::eval(evaluator::WN::P(program), &mut env) {
+        object::Object::Error(err) => Err(format!("{}: {}", file.display(), err.message)),
+        value => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_files(root: &Path, files: &[(&str, &str)]) {
+        for (name, source) in files {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+    }
+
+    // Gzipped tar with the files under `prefix`.
+    fn write_archive(path: &Path, prefix: &str, files: &[(&str, &str)]) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let encoder = flate2::write::GzEncoder::new(fs::File::create(path).unwrap(), flate2::Compression::default());
+        let mut archive = tar::Builder::new(encoder);
+
+        for (name, source) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(source.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            archive
+                .append_data(&mut header, Path::new(prefix).join(name), source.as_bytes())
+                .unwrap();
+        }
+
+        archive.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn project(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("clojurium_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        write_files(
+            &root,
+            &[
+                ("math/project.clm", "{\"name\": \"math\", \"version\": \"0.2.0\", \"entry\": \"src/lib.clm\", \"dependencies\": {\"json\": \"1.2.0\"}}"),
+                ("math/src/lib.clm", "import \"json\" as json; export let square = fn(x) { x * x };"),
+                ("app/vendor/json/1.0.0/project.clm", "{\"name\": \"json\", \"version\": \"1.0.0\"}"),
+                ("app/vendor/json/1.0.0/main.clm", "export let version = \"1.0.0\";"),
+                ("app/vendor/json/1.2.0/project.clm", "{\"name\": \"json\", \"version\": \"1.2.0\"}"),
+                ("app/vendor/json/1.2.0/main.clm", "export let version = \"1.2.0\";"),
+                ("app/vendor/json/1.2.0/extra.clm", "export let answer = 42;"),
+                ("app/vendor/json/1.10.0/project.clm", "{\"name\": \"json\", \"version\": \"1.10.0\"}"),
+                ("util/project.clm", "{\"name\": \"util\", \"version\": \"1.0.0\", \"dependencies\": {\"math\": {\"path\": \"./../math\"}}}"),
+            ],
+        );
+        root
+    }
+
+    fn set_dependencies(app: &Path, dependencies: &str) {
+        let manifest = format!(
+            "{{\"name\": \"app\", \"version\": \"0.1.0\", \"dependencies\": {{{}}}}}",
+            dependencies
+        );
+        fs::write(app.join(MANIFEST), manifest).unwrap();
+    }
+
+    #[test]
+    fn test_manifest() {
+        let root = project("manifest");
+
+        let manifest = read_manifest(&root.join("math")).unwrap();
+        assert_eq!(
+            manifest,
+            Manifest {
+                name: "math".to_string(),
+                version: "0.2.0".to_string(),
+                entry: "src/lib.clm".to_string(),
+                registry: "vendor".to_string(),
+                dependencies: vec![Dependency {
+                    name: "json".to_string(),
+                    source: Source::Registry("1.2.0".to_string()),
+                }],
+            }
+        );
+
+        let pairs = [
+            ("[1, 2]", "expected HASH, got ARRAY"),
+            ("{\"version\": \"1.0.0\"}", "missing `name`"),
+            ("{\"name\": \"x\", \"version\": 1}", "`version` must be STRING, got INTEGER"),
+            ("{\"name\": \"x\", \"version\": \"one\"}", "invalid version `one`"),
+            ("{\"name\": \"x\", \"version\": \"1.0\", \"dependencies\": [1]}", "`dependencies` must be HASH, got ARRAY"),
+            ("{\"name\": \"x\", \"version\": \"1.0\", \"dependencies\": {\"y\": \"latest\"}}", "invalid version `latest` of dependency `y`"),
+            ("{\"name\": \"x\", \"version\": \"1.0\", \"dependencies\": {\"y\": {}}}", "missing `path` of dependency `y`"),
+            ("{\"name\": \"x\", \"version\": \"1.0\", \"dependencies\": {\"y\": 1}}", "dependency `y` must be a version STRING or a HASH with `path`, got INTEGER"),
+            ("{\"name\": \"x\", \"version\": \"1.0\", \"dependencies\": {\"../../src\": \"1.0.0\"}}", "invalid name `../../src` of dependency"),
+            ("{\"name\": \"x\", \"version\": (\"1.0\" {}", "I've expected `)`, but got {"),
+            ("{\"name\": \"x\", \"version\": \"1.0\", \"dependencies\": {\"\": \"1.0.0\"}}", "invalid name `` of dependency"),
+            ("{\"name\": \"x\", \"version\": \"1.0\", \"dependencies\": {\"a\\b\": \"1.0.0\"}}", "invalid name `a\\b` of dependency"),
+        ];
+
+        for (source, expected) in pairs.iter() {
+            let directory = root.join("broken");
+            write_files(&directory, &[(MANIFEST, source)]);
+            let file = directory.join(MANIFEST).display().to_string();
+            assert_eq!(read_manifest(&directory), Err(format!("{}: {}", file, expected)));
+        }
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let root = project("resolve");
+        let app = root.join("app");
+
+        write_archive(
+            &app.join("vendor/csv/2.0.0.tar.gz"),
+            "csv-2.0.0",
+            &[(MANIFEST, "{\"name\": \"csv\", \"version\": \"2.0.0\", \"dependencies\": {\"json\": \"1.0.0\"}}")],
+        );
+        write_archive(&app.join("vendor/csv/1.0.0.tar.gz"), "", &[(MANIFEST, "{\"name\": \"csv\", \"version\": \"1.0.0\"}")]);
+        write_files(&app, &[("vendor/csv/1.0.0/project.clm", "{\"name\": \"csv\", \"version\": \"1.0.0\"}")]);
+        // Its stem is `..`, unpacking it would replace the whole `UNPACKED`.
+        write_archive(&root.join("...tar.gz"), "", &[(MANIFEST, "{\"name\": \"bad\", \"version\": \"1.0.0\"}")]);
+        write_files(&app, &[(".clojurium/packages/keep/main.clm", "")]);
+
+        let pairs = vec![
+            (
+                "\"math\": {\"path\": \"../math\"}, \"json\": \"1.2.0\"",
+                Ok(vec![("json", "1.2.0", "vendor/json/1.2.0"), ("math", "0.2.0", "../math")]),
+            ),
+            // Archives are versions of their own, directories win over them.
+            (
+                "\"csv\": \"*\"",
+                Ok(vec![("csv", "2.0.0", "vendor/csv/2.0.0.tar.gz"), ("json", "1.0.0", "vendor/json/1.0.0")]),
+            ),
+            ("\"csv\": \"1.0.0\"", Ok(vec![("csv", "1.0.0", "vendor/csv/1.0.0")])),
+            ("\"json\": \"*\"", Ok(vec![("json", "1.10.0", "vendor/json/1.10.0")])),
+            // Both lead to the same directory.
+            (
+                "\"math\": {\"path\": \"../math/\"}, \"util\": {\"path\": \"../util\"}",
+                Ok(vec![("json", "1.2.0", "vendor/json/1.2.0"), ("math", "0.2.0", "../math"), ("util", "1.0.0", "../util")]),
+            ),
+            (
+                "\"util\": {\"path\": \"./../util\"}",
+                Ok(vec![("json", "1.2.0", "vendor/json/1.2.0"), ("math", "0.2.0", "../math"), ("util", "1.0.0", "../util")]),
+            ),
+            ("", Ok(vec![])),
+            (
+                "\"math\": {\"path\": \"../math\"}, \"json\": \"1.0.0\"",
+                Err("conflicting requirements for package `json`: 1.0.0 at vendor/json/1.0.0 and 1.2.0 at vendor/json/1.2.0".to_string()),
+            ),
+            (
+                "\"json\": \"2.0.0\"",
+                Err("no version of package `json` matching `2.0.0` in the registry vendor".to_string()),
+            ),
+            (
+                "\"maths\": {\"path\": \"../math\"}",
+                Err("dependency `maths` at ../math is a package named `math`".to_string()),
+            ),
+            (
+                "\"bad\": {\"path\": \"../...tar.gz\"}",
+                Err("cannot unpack ../...tar.gz outside of .clojurium/packages".to_string()),
+            ),
+        ];
+
+        for (dependencies, expected) in pairs {
+            set_dependencies(&app, dependencies);
+            let manifest = read_manifest(&app).unwrap();
+            let resolved = resolve(&app, &manifest).map(|packages| {
+                packages
+                    .into_iter()
+                    .map(|package| (package.name, package.version, package.root.display().to_string()))
+                    .collect::<Vec<_>>()
+            });
+            let expected = expected.map(|packages| {
+                packages
+                    .into_iter()
+                    .map(|(name, version, root)| (name.to_string(), version.to_string(), root.to_string()))
+                    .collect::<Vec<_>>()
+            });
+            assert_eq!(resolved, expected, "dependencies: {}", dependencies);
+        }
+        assert!(app.join(".clojurium/packages/keep/main.clm").exists());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_lockfile() {
+        let root = project("lockfile");
+        let app = root.join("app");
+
+        set_dependencies(&app, "\"math\": {\"path\": \"../math\"}, \"json\": \"1.2.0\"");
+        let manifest = read_manifest(&app).unwrap();
+        assert_eq!(read_lockfile(&app), Ok(None));
+
+        let packages = resolve(&app, &manifest).unwrap();
+        write_lockfile(&app, &packages).unwrap();
+        assert_eq!(read_lockfile(&app), Ok(Some(packages)));
+
+        set_dependencies(&app, "\"json\": \"*\"");
+        let manifest = read_manifest(&app).unwrap();
+
+        let packages = resolve(&app, &manifest).unwrap();
+        write_lockfile(&app, &packages).unwrap();
+
+        // Lock is used as it is, even if a newer version appears.
+        write_files(&app, &[("vendor/json/1.20.0/project.clm", "{\"name\": \"json\", \"version\": \"1.20.0\"}")]);
+        assert_eq!(load_packages(&app, &manifest), Ok(packages));
+
+        // Changed requirement makes the lock stale.
+        set_dependencies(&app, "\"json\": \"1.0.0\"");
+        let manifest = read_manifest(&app).unwrap();
+        let expected = vec![Package {
+            name: "json".to_string(),
+            version: "1.0.0".to_string(),
+            root: PathBuf::from("vendor/json/1.0.0"),
+        }];
+        assert_eq!(load_packages(&app, &manifest), Ok(expected.clone()));
+        assert_eq!(read_lockfile(&app), Ok(Some(expected)));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_import_packages() {
+        let root = project("import");
+        let app = root.join("app");
+
+        write_archive(
+            &root.join("libs/fmt.tar.gz"),
+            "",
+            &[
+                (MANIFEST, "{\"name\": \"fmt\", \"version\": \"0.1.0\", \"dependencies\": {\"json\": \"1.2.0\"}}"),
+                ("main.clm", "import \"json\" as json; export let show = fn(x) { \"${x}@${json.version}\" };"),
+            ],
+        );
+        set_dependencies(
+            &app,
+            "\"math\": {\"path\": \"../math\"}, \"json\": \"1.2.0\", \"fmt\": {\"path\": \"../libs/fmt.tar.gz\"}",
+        );
+        write_files(
+            &app,
+            &[(
+                "main.clm",
+                "import \"math\" as math; import \"json\" as json; import \"json/extra\" as extra; import \"fmt\" as fmt;
+                 [math.square(4), json.version, extra.answer, fmt.show(1)]",
+            )],
+        );
+
+        // The second run takes the archive unpacked by the first one.
+        for _ in 0..2 {
+            let env = environment::Environment::new();
+            let manifest = read_manifest(&app).unwrap();
+            let packages = load_packages(&app, &manifest).unwrap();
+            register_packages(&app, &packages, &env).unwrap();
+
+            let evaluated = crate::evaluation::modules::run_file(&app.join(manifest.entry), &env);
+            assert_eq!(evaluated.inspect(), "[16, 1.2.0, 42, 1@1.2.0]");
+        }
+        assert!(app.join(".clojurium/packages/fmt/fmt/main.clm").exists());
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -1,9 +1,14 @@
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt;
+use std::panic;
+use std::sync::Once;
 
 use lazy_static::lazy_static;
 
 use crate::ast;
+use crate::core;
 use crate::lexer;
 use crate::token;
 use trace::trace;
@@ -16,16 +21,31 @@ trace::init_depth_var!();
 // Priority table for different fixity operations
 lazy_static! {
     static ref PRECEDENCES: HashMap<token::TokenType, u8> = [
+        (token::PIPE.to_string(), token::PIPELINE),
+        (token::OR.to_string(), token::LOGICAL_OR),
+        (token::AND.to_string(), token::LOGICAL_AND),
         (token::EQ.to_string(), token::EQUALS),
         (token::NOT_EQ.to_string(), token::EQUALS),
         (token::LT.to_string(), token::LESSGREATER),
         (token::GT.to_string(), token::LESSGREATER),
+        (token::LT_EQ.to_string(), token::LESSGREATER),
+        (token::GT_EQ.to_string(), token::LESSGREATER),
+        (token::BIT_OR.to_string(), token::BITWISE_OR),
+        (token::BIT_XOR.to_string(), token::BITWISE_XOR),
+        (token::BIT_AND.to_string(), token::BITWISE_AND),
+        (token::SHIFT_LEFT.to_string(), token::SHIFT),
+        (token::SHIFT_RIGHT.to_string(), token::SHIFT),
         (token::PLUS.to_string(), token::SUM),
         (token::MINUS.to_string(), token::SUM),
         (token::SLASH.to_string(), token::PRODUCT),
         (token::ASTERISK.to_string(), token::PRODUCT),
+        (token::PERCENT.to_string(), token::PRODUCT),
+        (token::POWER.to_string(), token::EXPONENT),
         (token::LPAREN.to_string(), token::CALL), // `(` in infix position should have highest priority
         (token::LBRACKET.to_string(), token::INDEX), // index expression have a highest precedence
+        (token::DOT.to_string(), token::INDEX),
+        (token::QUESTION.to_string(), token::CALL),
+        (token::WITH.to_string(), token::UPDATE),
     ]
     .iter()
     .cloned()
@@ -44,21 +64,22 @@ fn precedence_by_token_type(token_type: &token::TokenType) -> u8 {
 // https://users.rust-lang.org/t/is-it-possible-to-implement-debug-for-fn-type/14824
 
 // Greeting to the master of functinal Rust - mighty @raventid
-type PrefixParseFnAlias = Fn(&mut Parser) -> token::Expression + 'static;
+type PrefixParseFnAlias = dyn Fn(&mut Parser) -> token::Expression + Send + Sync + 'static;
 
 pub struct PrefixParseFn(Box<PrefixParseFnAlias>);
 impl fmt::Debug for PrefixParseFn {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", "prefix_parse_fn")
+        write!(f, "prefix_parse_fn")
     }
 }
 
-type InfixParseFnAlias = Fn(&mut Parser, token::Expression) -> token::Expression + 'static;
+type InfixParseFnAlias =
+    dyn Fn(&mut Parser, token::Expression) -> token::Expression + Send + Sync + 'static;
 
 pub struct InfixParseFn(Box<InfixParseFnAlias>);
 impl fmt::Debug for InfixParseFn {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", "infix_parse_fn")
+        write!(f, "infix_parse_fn")
     }
 }
 
@@ -68,18 +89,22 @@ pub struct LambdaParsers {
     pub infix_parse_fns: HashMap<token::TokenType, InfixParseFn>,
 }
 
+// Parsers are registered once and shared by every parse function.
+lazy_static! {
+    pub static ref PARSERS: LambdaParsers = {
+        let mut lambda_parsers = LambdaParsers {
+            prefix_parse_fns: HashMap::new(),
+            infix_parse_fns: HashMap::new(),
+        };
+        lambda_parsers.register_parsers();
+        lambda_parsers
+    };
+}
+
 impl LambdaParsers {
-    pub fn register_parsers(&mut self) {
+    fn register_parsers(&mut self) {
         // PREFIX PARSERS
-        self.register_prefix(
-            token::IDENT.to_string(),
-            Box::new(|parser| {
-                token::Expression::Identifier(token::Identifier {
-                    token: parser.current_token.clone(),
-                    value: parser.current_token.literal.clone(),
-                })
-            }),
-        );
+        self.register_prefix(token::IDENT.to_string(), Box::new(Self::parse_identifier));
 
         self.register_prefix(token::INT.to_string(), Box::new(Self::parse_int_literal));
 
@@ -88,11 +113,23 @@ impl LambdaParsers {
             Box::new(Self::parse_string_literal),
         );
 
+        self.register_prefix(token::CHAR.to_string(), Box::new(Self::parse_char_literal));
+
+        self.register_prefix(
+            token::INTERPOLATED_STRING.to_string(),
+            Box::new(Self::parse_interpolated_string),
+        );
+
         self.register_prefix(
             token::LBRACKET.to_string(),
             Box::new(Self::parse_array_literal),
         );
 
+        self.register_prefix(
+            token::LBRACE.to_string(),
+            Box::new(Self::parse_hash_literal),
+        );
+
         self.register_prefix(
             token::BANG.to_string(),
             Box::new(Self::parse_prefix_expression),
@@ -114,11 +151,37 @@ impl LambdaParsers {
 
         self.register_prefix(token::IF.to_string(), Box::new(Self::parse_if_expression));
 
+        self.register_prefix(
+            token::THROW.to_string(),
+            Box::new(Self::parse_throw_expression),
+        );
+
+        self.register_prefix(token::TRY.to_string(), Box::new(Self::parse_try_expression));
+
+        self.register_prefix(
+            token::YIELD.to_string(),
+            Box::new(Self::parse_yield_expression),
+        );
+
+        self.register_prefix(token::FOR.to_string(), Box::new(Self::parse_for_expression));
+
         self.register_prefix(
             token::FUNCTION.to_string(),
             Box::new(Self::parse_function_literal),
         );
 
+        self.register_prefix(token::MACRO.to_string(), Box::new(Self::parse_macro_literal));
+
+        self.register_prefix(
+            token::MATCH.to_string(),
+            Box::new(Self::parse_match_expression),
+        );
+
+        self.register_prefix(
+            token::PLACEHOLDER_FN.to_string(),
+            Box::new(Self::parse_placeholder_function),
+        );
+
         // INFIX PARSERS
         self.register_infix(
             token::PLUS.to_string(),
@@ -160,6 +223,66 @@ impl LambdaParsers {
             Box::new(Self::parse_infix_expression),
         );
 
+        self.register_infix(
+            token::LT_EQ.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::GT_EQ.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::PERCENT.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::POWER.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::BIT_AND.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::BIT_OR.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::BIT_XOR.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::SHIFT_LEFT.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::SHIFT_RIGHT.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::AND.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::OR.to_string(),
+            Box::new(Self::parse_infix_expression),
+        );
+
+        self.register_infix(
+            token::PIPE.to_string(),
+            Box::new(Self::parse_pipe_expression),
+        );
+
         // By registering `(` handler as an infix
         // parser we allow it to parse CALL syntax.
         self.register_infix(
@@ -170,6 +293,18 @@ impl LambdaParsers {
         self.register_infix(
             token::LBRACKET.to_string(),
             Box::new(Self::parse_index_expression),
+        );
+
+        self.register_infix(token::DOT.to_string(), Box::new(Self::parse_field_access));
+
+        self.register_infix(
+            token::QUESTION.to_string(),
+            Box::new(Self::parse_propagate_expression),
+        );
+
+        self.register_infix(
+            token::WITH.to_string(),
+            Box::new(Self::parse_with_expression),
         )
     }
 
@@ -181,23 +316,81 @@ impl LambdaParsers {
         self.infix_parse_fns.insert(token_type, InfixParseFn(f));
     }
 
-    fn parse_int_literal(parser: &mut Parser) -> token::Expression {
-        let to_be_integer = parser.current_token.literal.clone();
+    fn parse_identifier(parser: &mut Parser) -> token::Expression {
+        let mut identifier = token::Identifier {
+            token: parser.current_token.clone(),
+            value: parser.current_token.literal.clone(),
+        };
 
-        // TODO: This extremly bad
-        // Lambda parsers should bubble errors to parser.
-        // Parser should handle them gracefully.
-        // For the future:
-        //
-        // enum ParserError {
-        //     FailedToReconiseIntegerLiteral(parse_int_error),
-        //     FailedToObtainSomeValue(some_error_message),
-        // }
-        let integer = to_be_integer.parse::<i32>().unwrap();
+        // Short lambda with a single parameter: `x => x + 1`
+        if parser.peek_token.token_type == token::FAT_ARROW && parser.allow_arrow_functions {
+            let parameter = token::Parameter {
+                pattern: token::Pattern::Identifier(identifier),
+                default: None,
+                variadic: false,
+            };
+            return Self::parse_arrow_function_body(parser, Some(vec![parameter]));
+        }
+
+        // Inside of `#(...)` every `_` is the next parameter of the function.
+        // Lexer never produces identifiers with digits, so `_1` can't clash
+        // with anything user wrote.
+        if identifier.value == "_" {
+            if let Some(count) = parser.placeholders.as_mut() {
+                *count += 1;
+                identifier.value = format!("_{}", count);
+            }
+        }
+
+        token::Expression::Identifier(identifier)
+    }
+
+    fn parse_int_literal(parser: &mut Parser) -> token::Expression {
+        let value = match parse_integer(&parser.current_token.literal, false) {
+            Ok(value) => value,
+            Err(message) => {
+                let location = parser.current_location();
+                parser.errors.push(format!("{} at {}", message, location));
+                0
+            }
+        };
 
         token::Expression::IntegerLiteral(token::IntegerLiteral {
             token: parser.current_token.clone(),
-            value: integer,
+            value,
+        })
+    }
+
+    // `-` followed by INT folded into a single literal,
+    // so `-2147483648` fits into i32.
+    // The cursor is on `-`, it's left on INT.
+    fn parse_negative_int_literal(parser: &mut Parser) -> token::Expression {
+        let minus = parser.current_token.clone();
+        let minus_location = parser.current_location();
+
+        parser.next_token(); // skip `-`
+
+        let value = match parse_integer(&parser.current_token.literal, true) {
+            Ok(value) => value,
+            Err(message) => {
+                // Out of range is about the whole `-<literal>`,
+                // invalid digits are about the literal itself.
+                let location = if message.starts_with("invalid") {
+                    parser.current_location()
+                } else {
+                    minus_location
+                };
+                parser.errors.push(format!("{} at {}", message, location));
+                0
+            }
+        };
+
+        token::Expression::IntegerLiteral(token::IntegerLiteral {
+            token: token::Token {
+                token_type: token::INT.to_string(),
+                literal: format!("{}{}", minus.literal, parser.current_token.literal),
+            },
+            value,
         })
     }
 
@@ -208,6 +401,73 @@ impl LambdaParsers {
         })
     }
 
+    fn parse_char_literal(parser: &mut Parser) -> token::Expression {
+        let value = match unescape_char(&parser.current_token.literal) {
+            Some(value) => value,
+            None => {
+                let message = format!(
+                    "invalid character literal `'{}'` at {}",
+                    parser.current_token.literal,
+                    parser.current_location()
+                );
+                parser.errors.push(message);
+                '\0'
+            }
+        };
+
+        token::Expression::CharLiteral(token::CharLiteral {
+            token: parser.current_token.clone(),
+            value,
+        })
+    }
+
+    // Every `${...}` part is parsed by its own parser,
+    // errors are reported to the outer one.
+    fn parse_interpolated_string(parser: &mut Parser) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let token = parser.current_token.clone();
+        let mut parts = vec![];
+
+        for part in lexer::Lexer::split_interpolated_string(&token.literal) {
+            match part {
+                token::StringPart::Text(text) => parts.push(token::InterpolationPart::Text(text)),
+                token::StringPart::Code(code) => {
+                    let mut code_parser = Parser::new(lexer::Lexer::new(code.clone()));
+
+                    if code_parser.current_token.token_type == token::EOF {
+                        parser
+                            .errors
+                            .push("empty string interpolation `${}`".to_string());
+                        continue;
+                    }
+
+                    let expression = code_parser.parse_expression(lambda_parsers, token::LOWEST);
+
+                    if code_parser.peek_token.token_type != token::EOF {
+                        code_parser.errors.push(format!(
+                            "unexpected `{}` after expression",
+                            code_parser.peek_token.literal
+                        ));
+                    }
+
+                    for error in code_parser.errors {
+                        parser.errors.push(format!(
+                            "in string interpolation `${{{}}}`: {}",
+                            code, error
+                        ));
+                    }
+
+                    if let Some(expression) = expression {
+                        parts.push(token::InterpolationPart::Expression(expression));
+                    }
+                }
+            }
+        }
+
+        token::Expression::InterpolatedString(token::InterpolatedString { token, parts })
+    }
+
     fn parse_array_literal(parser: &mut Parser) -> token::Expression {
         // This function is loosely based on internal `parse_call_expression`
         // helper wich parses params.
@@ -215,13 +475,7 @@ impl LambdaParsers {
         // TODO: This could be unified with helper from `parse_call_expressin`
         // or they could be both unified
         fn parse_expression_list(parser: &mut Parser) -> Vec<token::Expression> {
-            // TODO: Reinitialization of parser here and in the `parse_prefix_expression`
-            // Should move this initialization somewhere and use link everywhere else.
-            let mut lambda_parsers = LambdaParsers {
-                prefix_parse_fns: HashMap::new(),
-                infix_parse_fns: HashMap::new(),
-            };
-            lambda_parsers.register_parsers();
+            let lambda_parsers: &LambdaParsers = &PARSERS;
 
             if parser.peek_token.token_type == token::RBRACKET {
                 parser.next_token();
@@ -230,7 +484,7 @@ impl LambdaParsers {
 
             parser.next_token(); // skip `LBRACKET`
 
-            let first_param = match parser.parse_expression(&lambda_parsers, token::LOWEST) {
+            let first_param = match parser.parse_expression(lambda_parsers, token::LOWEST) {
                 Some(expr) => expr,
                 None => panic!("Failed to parse param"),
             };
@@ -241,7 +495,7 @@ impl LambdaParsers {
                 parser.next_token(); // set cursor to `,`
                 parser.next_token(); // skip `,` and move cursor to next token
 
-                let param = match parser.parse_expression(&lambda_parsers, token::LOWEST) {
+                let param = match parser.parse_expression(lambda_parsers, token::LOWEST) {
                     Some(expr) => expr,
                     None => panic!("Failed to parse param"),
                 };
@@ -266,20 +520,58 @@ impl LambdaParsers {
         token::Expression::ArrayLiteral(token::ArrayLiteral { token, elements })
     }
 
+    fn parse_hash_literal(parser: &mut Parser) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let token = parser.current_token.clone();
+        let mut pairs = Vec::new();
+
+        while parser.peek_token.token_type != token::RBRACE {
+            parser.next_token(); // skip `{` or `,`
+
+            let key = match parser.parse_expression(lambda_parsers, token::LOWEST) {
+                Some(expr) => expr,
+                None => panic!("Failed to parse hash key"),
+            };
+
+            if parser.peek_token.token_type != token::COLON {
+                panic!(
+                    "Expected `:` after hash key, got {}",
+                    parser.peek_token.token_type
+                );
+            }
+            parser.next_token(); // set cursor to `:`
+            parser.next_token(); // skip `:`
+
+            let value = match parser.parse_expression(lambda_parsers, token::LOWEST) {
+                Some(expr) => expr,
+                None => panic!("Failed to parse hash value"),
+            };
+
+            pairs.push((key, value));
+
+            if parser.peek_token.token_type == token::COMMA {
+                parser.next_token(); // set cursor to `,`
+            } else if parser.peek_token.token_type != token::RBRACE {
+                panic!(
+                    "Expected `,` or closing `}}` in hash literal, got {}",
+                    parser.peek_token.token_type
+                );
+            }
+        }
+        parser.next_token(); // set cursor to `}` and leave parser
+
+        token::Expression::HashLiteral(token::HashLiteral { token, pairs })
+    }
+
     fn parse_index_expression(parser: &mut Parser, left: token::Expression) -> token::Expression {
-        // TODO: Reinitialization of parser here and in the `parse_prefix_expression`
-        // Should move this initialization somewhere and use link everywhere else.
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
         let token = parser.current_token.clone();
 
         parser.next_token();
 
-        let index = match parser.parse_expression(&lambda_parsers, token::LOWEST) {
+        let index = match parser.parse_expression(lambda_parsers, token::LOWEST) {
             Some(i) => i,
             None => panic!("failed to parse index expression, failed on index"),
         };
@@ -295,6 +587,105 @@ impl LambdaParsers {
         token::Expression::IndexExpression(Box::new(token::IndexExpression { token, left, index}))
     }
 
+    // Structure: <expression>.<identifier>
+    fn parse_field_access(parser: &mut Parser, left: token::Expression) -> token::Expression {
+        let token = parser.current_token.clone();
+
+        if parser.peek_token.token_type != token::IDENT {
+            panic!(
+                "I've expected field name after `.`, but got {}",
+                parser.peek_token.token_type
+            );
+        }
+        parser.next_token(); // set cursor to field name
+
+        let field = token::Identifier {
+            token: parser.current_token.clone(),
+            value: parser.current_token.literal.clone(),
+        };
+
+        token::Expression::FieldAccess(Box::new(token::FieldAccess { token, left, field }))
+    }
+
+    // Structure: <expression> with { <identifier>: <expression>, ... }
+    // Postfix, nothing to parse on the right.
+    fn parse_propagate_expression(
+        parser: &mut Parser,
+        left: token::Expression,
+    ) -> token::Expression {
+        token::Expression::PropagateExpression(Box::new(token::PropagateExpression {
+            token: parser.current_token.clone(),
+            value: left,
+        }))
+    }
+
+    fn parse_with_expression(parser: &mut Parser, left: token::Expression) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let token = parser.current_token.clone();
+
+        if parser.peek_token.token_type != token::LBRACE {
+            panic!(
+                "I've expected `{{` after `with`, but got {}",
+                parser.peek_token.token_type
+            );
+        }
+        parser.next_token(); // set cursor to `{`
+
+        let mut fields = vec![];
+
+        while parser.peek_token.token_type != token::RBRACE {
+            parser.next_token(); // skip `{` or `,`
+
+            if parser.current_token.token_type != token::IDENT {
+                panic!(
+                    "I've expected field name in `with`, but got {}",
+                    parser.current_token.token_type
+                );
+            }
+
+            let name = token::Identifier {
+                token: parser.current_token.clone(),
+                value: parser.current_token.literal.clone(),
+            };
+
+            if parser.peek_token.token_type != token::COLON {
+                panic!(
+                    "I've expected `:` after field name, but got {}",
+                    parser.peek_token.token_type
+                );
+            }
+            parser.next_token(); // set cursor to `:`
+            parser.next_token(); // skip `:`
+
+            let value = match parser.parse_expression(lambda_parsers, token::LOWEST) {
+                Some(value) => value,
+                None => panic!("failed to parse value of field `{}`", name),
+            };
+
+            fields.push((name, value));
+
+            if parser.peek_token.token_type != token::COMMA {
+                break;
+            }
+            parser.next_token(); // set cursor to `,`
+        }
+
+        if parser.peek_token.token_type != token::RBRACE {
+            panic!(
+                "I've expected closing `}}`, but got {}",
+                parser.peek_token.token_type
+            );
+        }
+        parser.next_token(); // set cursor to `}`
+
+        token::Expression::WithExpression(Box::new(token::WithExpression {
+            token,
+            left,
+            fields,
+        }))
+    }
+
     fn parse_boolean(parser: &mut Parser) -> token::Expression {
         // TODO: extract?
         // fn (parser: &Parser) cur_token_is(t: token::TokenType) -> bool { p.cur_token.type == t }
@@ -307,16 +698,21 @@ impl LambdaParsers {
     }
 
     fn parse_prefix_expression(parser: &mut Parser) -> token::Expression {
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
         // We have to extract current token and operator
         // Because we'll move to next_token now.
         // To call parse_expression and get `right` expression.
+        // `-2147483648` is valid only as a whole,
+        // `2147483648` alone doesn't fit into i32.
+        // Broken literals are reported by the folded one as well.
+        if parser.current_token.token_type == token::MINUS
+            && parser.peek_token.token_type == token::INT
+            && parse_integer(&parser.peek_token.literal, false).is_err()
+        {
+            return Self::parse_negative_int_literal(parser);
+        }
+
         let token = parser.current_token.clone();
         let operator = parser.current_token.literal.clone();
 
@@ -325,7 +721,7 @@ impl LambdaParsers {
         // If we enter `parse_expression()` here without `next_token()`
         // we enter the endless loop, followed by stack overflow.
         // parse_expression() -> parse_prefix_expression() -> parse_expression()
-        let expression = match parser.parse_expression(&lambda_parsers, token::PREFIX) {
+        let expression = match parser.parse_expression(lambda_parsers, token::PREFIX) {
             Some(result) => result,
             None => panic!(
                 "Can't parse parser.current_token = {}",
@@ -341,23 +737,25 @@ impl LambdaParsers {
     }
 
     fn parse_infix_expression(parser: &mut Parser, left: token::Expression) -> token::Expression {
-        // TODO: Reinitialization of parser here and in the `parse_prefix_expression`
-        // Should move this initialization somewhere and use link everywhere else.
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
         let token = parser.current_token.clone();
         let operator = parser.current_token.literal.clone();
 
         let precedence = precedence_by_token_type(&token.token_type);
 
+        // Exponentiation is right associative: `2 ** 3 ** 2` is `2 ** (3 ** 2)`.
+        // Lowering the precedence for the right side lets the next `**`
+        // be consumed by the nested call.
+        let precedence = if token.token_type == token::POWER {
+            precedence - 1
+        } else {
+            precedence
+        };
+
         parser.next_token();
 
-        let right = match parser.parse_expression(&lambda_parsers, precedence) {
+        let right = match parser.parse_expression(lambda_parsers, precedence) {
             Some(parsed_expression) => parsed_expression,
             None => panic!("Cannot find infix parser for {:?}", token),
         };
@@ -371,20 +769,37 @@ impl LambdaParsers {
         }))
     }
 
+    fn parse_pipe_expression(parser: &mut Parser, left: token::Expression) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let token = parser.current_token.clone();
+
+        parser.next_token(); // skip `|>`
+
+        // Parsing with PIPELINE precedence keeps pipes left associative:
+        // `a |> f |> g` is `(a |> f) |> g`.
+        let right = match parser.parse_expression(lambda_parsers, token::PIPELINE) {
+            Some(expression) => expression,
+            None => panic!("Cannot parse right side of `|>`, got {:?}", parser.current_token),
+        };
+
+        token::Expression::PipeExpression(Box::new(token::PipeExpression { token, left, right }))
+    }
+
     #[trace]
     fn parse_grouped_expressions(parser: &mut Parser) -> token::Expression {
-        // TODO: Reinitialization of parser here and in the `parse_prefix_expression`
-        // Should move this initialization somewhere and use link everywhere else.
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        // Short lambda with parameters in parens: `(a, b) => a + b`
+        if parser.allow_arrow_functions && parser.is_arrow_function_ahead() {
+            let parameters = Self::parse_function_parameters(parser);
+            return Self::parse_arrow_function_body(parser, parameters);
+        }
 
         // If we see `(` we enter here and move cursor to the next token.
         parser.next_token();
 
-        let expression = match parser.parse_expression(&lambda_parsers, token::LOWEST) {
+        let expression = match parser.parse_expression(lambda_parsers, token::LOWEST) {
             Some(expression) => expression,
             None => panic!("Cannot find parser for {:?}", parser.current_token),
         };
@@ -408,13 +823,7 @@ impl LambdaParsers {
 
     #[trace]
     fn parse_if_expression(parser: &mut Parser) -> token::Expression {
-        // TODO: Reinitialization of parser here and in the `parse_prefix_expression`
-        // Should move this initialization somewhere and use link everywhere else.
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
         let token = parser.current_token.clone();
 
@@ -430,7 +839,7 @@ impl LambdaParsers {
 
         // I should find the best place to record errors.
         // Use Result instead of Option. Log result in Parser { errors }.
-        let condition = match parser.parse_expression(&lambda_parsers, token::LOWEST) {
+        let condition = match parser.parse_expression(lambda_parsers, token::LOWEST) {
             Some(c) => c,
             _ => panic!("failed to parse some condition"),
         };
@@ -485,49 +894,59 @@ impl LambdaParsers {
         }))
     }
 
-    fn parse_function_literal(parser: &mut Parser) -> token::Expression {
-        // Parse function parameters helper function.
-        fn parse_function_parameters(parser: &mut Parser) -> Option<Vec<token::Identifier>> {
-            if parser.peek_token.token_type == token::RPAREN {
-                parser.next_token();
-                return None;
-            }
+    fn parse_throw_expression(parser: &mut Parser) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-            parser.next_token(); // we already parsed `fn` here, so current token is `(`
+        let token = parser.current_token.clone();
+        let location = parser.current_location();
 
-            let identifier = token::Identifier {
-                token: parser.current_token.clone(),
-                value: parser.current_token.literal.clone(),
-            };
+        parser.next_token(); // skip `throw`
 
-            let mut identifiers = vec![identifier];
+        let value = match parser.parse_expression(lambda_parsers, token::LOWEST) {
+            Some(value) => value,
+            None => panic!("failed to parse thrown value"),
+        };
 
-            dbg!(&identifiers);
-            while parser.peek_token.token_type == token::COMMA {
-                // next identifier exists
-                parser.next_token(); // set cursor to comma
-                parser.next_token(); // skip comma
+        token::Expression::ThrowExpression(Box::new(token::ThrowExpression {
+            token,
+            value,
+            location,
+        }))
+    }
 
-                let identifier = token::Identifier {
-                    token: parser.current_token.clone(),
-                    value: parser.current_token.literal.clone(),
-                };
-                identifiers.push(identifier);
-            }
+    fn parse_yield_expression(parser: &mut Parser) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-            // I expect closing `)` after function arguments.
-            // (a,b,c,d,e) <- this one
-            if parser.peek_token.token_type != token::RPAREN {
-                panic!(
-                    "Expected closing `)`, got `{}`",
-                    parser.peek_token.token_type
-                );
-            }
-            parser.next_token();
+        let token = parser.current_token.clone();
+        let location = parser.current_location();
 
-            Some(identifiers)
+        // Function with `yield` in its body is a generator.
+        match parser.yields.as_mut() {
+            Some(yields) => *yields = true,
+            None => {
+                let message = format!("`yield` outside of a function at {}", location);
+                parser.errors.push(message);
+            }
         }
 
+        parser.next_token(); // skip `yield`
+
+        let value = match parser.parse_expression(lambda_parsers, token::LOWEST) {
+            Some(value) => value,
+            None => panic!("failed to parse yielded value"),
+        };
+
+        token::Expression::YieldExpression(Box::new(token::YieldExpression {
+            token,
+            value,
+            location,
+        }))
+    }
+
+    // Structure: for (<pattern> in <expression>) <block>
+    fn parse_for_expression(parser: &mut Parser) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
         let token = parser.current_token.clone();
 
         if parser.peek_token.token_type != token::LPAREN {
@@ -538,421 +957,2491 @@ impl LambdaParsers {
         };
         parser.next_token(); // set cursor to `(`
 
-        let parameters = parse_function_parameters(parser);
+        parser.next_token(); // skip `(`
+
+        let pattern = match parser.parse_pattern() {
+            Some(pattern) => pattern,
+            None => panic!("failed to parse pattern of `for`"),
+        };
 
-        // This block is the same as one above
-        // We should find LBRACE or panic! (we should not panic actually)
-        if parser.peek_token.token_type != token::LBRACE {
+        if parser.peek_token.token_type != token::IN {
             panic!(
-                "I've expected opening `{{`, but got `{}`",
+                "I've expected `in`, but got {}",
                 parser.peek_token.token_type
             );
         };
+        parser.next_token(); // set cursor to `in`
 
-        // next token is `{`, everything is fine, set cursor on it
-        parser.next_token();
+        parser.next_token(); // skip `in`
+
+        let iterable = match parser.parse_expression(lambda_parsers, token::LOWEST) {
+            Some(iterable) => iterable,
+            None => panic!("failed to parse iterable of `for`"),
+        };
+
+        if parser.peek_token.token_type != token::RPAREN {
+            panic!(
+                "I've expected closing `)`, but got {}",
+                parser.peek_token.token_type
+            );
+        };
+        parser.next_token(); // set cursor to `)`
+
+        if parser.peek_token.token_type != token::LBRACE {
+            panic!(
+                "I've expected opening `{{`, but got {}",
+                parser.peek_token.token_type
+            );
+        };
+        parser.next_token(); // set cursor to `{`
 
         let body = Self::parse_block_statement(parser);
 
-        token::Expression::FunctionLiteral(token::FunctionLiteral {
+        token::Expression::ForExpression(Box::new(token::ForExpression {
             token,
-            parameters,
+            pattern,
+            iterable,
             body,
-        })
+        }))
     }
 
-    #[trace]
-    fn parse_block_statement(parser: &mut Parser) -> token::BlockStatement {
-        // TODO: Reinitialization of parser here and in the `parse_prefix_expression`
-        // Should move this initialization somewhere and use link everywhere else.
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-        lambda_parsers.register_parsers();
-
+    fn parse_try_expression(parser: &mut Parser) -> token::Expression {
         let token = parser.current_token.clone();
-        let mut statements = Vec::new();
-
-        // current token is `{` skip it
-        parser.next_token();
+        let location = parser.current_location();
 
-        while parser.current_token.token_type != token::RBRACE
-            && parser.current_token.token_type != token::EOF
-        {
-            let statement = match parser.parse_statement(&lambda_parsers) {
-                Some(s) => s,
-                None => panic!("useless panic one more time, failed to parse if block"),
-            };
-            statements.push(statement);
-            parser.next_token();
+        if parser.peek_token.token_type != token::LBRACE {
+            panic!(
+                "I've expected `{{` after `try`, but got {}",
+                parser.peek_token.token_type
+            );
         }
+        parser.next_token(); // set cursor to `{`
 
-        token::BlockStatement { token, statements }
-    }
+        let body = Self::parse_block_statement(parser);
 
-    fn parse_call_expression(
-        parser: &mut Parser,
-        function: token::Expression,
-    ) -> token::Expression {
-        fn parse_call_arguments(parser: &mut Parser) -> Option<Vec<token::Expression>> {
-            // TODO: Reinitialization of parser here and in the `parse_prefix_expression`
-            // Should move this initialization somewhere and use link everywhere else.
-            let mut lambda_parsers = LambdaParsers {
-                prefix_parse_fns: HashMap::new(),
-                infix_parse_fns: HashMap::new(),
-            };
-            lambda_parsers.register_parsers();
+        let catch = if parser.peek_token.token_type == token::CATCH {
+            parser.next_token(); // set cursor to `catch`
 
-            if parser.peek_token.token_type == token::RPAREN {
-                parser.next_token();
-                return None;
+            if parser.peek_token.token_type != token::LPAREN {
+                panic!(
+                    "I've expected `(` after `catch`, but got {}",
+                    parser.peek_token.token_type
+                );
             }
+            parser.next_token(); // set cursor to `(`
+            parser.next_token(); // skip `(`
 
-            parser.next_token(); // skip `RPAREN`
-
-            let first_param = match parser.parse_expression(&lambda_parsers, token::LOWEST) {
-                Some(expr) => expr,
-                None => panic!("Failed to parse param for function CALL"),
+            let pattern = match parser.parse_pattern() {
+                Some(pattern) => pattern,
+                None => panic!("Failed to parse catch pattern: {:?}", parser.errors),
             };
 
-            let mut params = vec![first_param];
+            if parser.peek_token.token_type != token::RPAREN {
+                panic!(
+                    "I've expected closing `)`, but got {}",
+                    parser.peek_token.token_type
+                );
+            }
+            parser.next_token(); // set cursor to `)`
 
-            while parser.peek_token.token_type == token::COMMA {
-                parser.next_token(); // set cursor to `,`
-                parser.next_token(); // skip `,` and move cursor to next token
+            if parser.peek_token.token_type != token::LBRACE {
+                panic!(
+                    "I've expected `{{` after `catch (...)`, but got {}",
+                    parser.peek_token.token_type
+                );
+            }
+            parser.next_token(); // set cursor to `{`
 
-                let param = match parser.parse_expression(&lambda_parsers, token::LOWEST) {
-                    Some(expr) => expr,
-                    None => panic!("Failed to parse param for function CALL"),
-                };
+            Some((pattern, Self::parse_block_statement(parser)))
+        } else {
+            None
+        };
 
-                params.push(param);
-            }
+        let finally = if parser.peek_token.token_type == token::FINALLY {
+            parser.next_token(); // set cursor to `finally`
 
-            if parser.peek_token.token_type != token::RPAREN {
+            if parser.peek_token.token_type != token::LBRACE {
                 panic!(
-                    "Expected closing `)` after params, got {}",
+                    "I've expected `{{` after `finally`, but got {}",
                     parser.peek_token.token_type
                 );
             }
-            parser.next_token(); // set cursor to RPAREN and leave parser
+            parser.next_token(); // set cursor to `{`
 
-            Some(params)
-        }
+            Some(Self::parse_block_statement(parser))
+        } else {
+            None
+        };
 
-        let token = parser.current_token.clone();
-        let arguments = parse_call_arguments(parser);
+        // `try` alone would just swallow nothing, so it's most likely a typo.
+        if catch.is_none() && finally.is_none() {
+            parser.errors.push(format!(
+                "expected `catch` or `finally` after try block at {}",
+                location
+            ));
+        }
 
-        token::Expression::CallExpression(Box::new(token::CallExpression {
+        token::Expression::TryExpression(Box::new(token::TryExpression {
             token,
-            function,
-            arguments,
+            body,
+            catch,
+            finally,
         }))
     }
-}
 
-#[derive(Debug)]
-pub struct Parser {
-    lexer: lexer::Lexer,
-    current_token: token::Token,
-    peek_token: token::Token,
-    pub errors: Vec<String>,
-}
+    // Structure: <pattern> | <pattern> = <expression> | ...<identifier>
+    fn parse_function_parameter(parser: &mut Parser) -> token::Parameter {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-impl Parser {
-    pub fn new(mut lexer: lexer::Lexer) -> Self {
-        let current_token = lexer.next_token();
-        let peek_token = lexer.next_token();
-        let errors = Vec::new();
+        if parser.current_token.token_type == token::ELLIPSIS {
+            let identifier = match parser.parse_rest_binding() {
+                Some(identifier) => identifier,
+                None => panic!("Failed to parse rest parameter: {:?}", parser.errors),
+            };
 
-        Self {
-            lexer,
-            current_token,
-            peek_token,
-            errors,
+            return token::Parameter {
+                pattern: token::Pattern::Identifier(identifier),
+                default: None,
+                variadic: true,
+            };
         }
-    }
 
-    fn next_token(&mut self) {
-        self.current_token = self.peek_token.clone();
-        self.peek_token = self.lexer.next_token();
-    }
+        // Every parameter is a pattern, so `fn([a, b], {name})` destructures
+        // its arguments the same way `let` does.
+        let pattern = match parser.parse_pattern() {
+            Some(pattern) => pattern,
+            None => panic!("Failed to parse function parameter: {:?}", parser.errors),
+        };
 
-    // TODO: Current attempt. Move link to LambdaParsers to every function.
-    // To avoid double borrowing of self in case of mutual recursive
-    // calls.
-    pub fn parse_program(&mut self, lambda_parsers: &LambdaParsers) -> ast::Program {
-        let mut program = ast::Program {
-            statements: Vec::new(),
+        let default = if parser.peek_token.token_type == token::ASSIGN {
+            parser.next_token(); // set cursor to `=`
+            parser.next_token(); // skip `=`
+
+            match parser.parse_expression(lambda_parsers, token::LOWEST) {
+                Some(expr) => Some(expr),
+                None => panic!("Failed to parse default value of `{}`", pattern),
+            }
+        } else {
+            None
         };
-        while self.current_token.token_type != token::EOF {
-            let statement = self.parse_statement(lambda_parsers);
-            match statement {
-                Some(stmt) => program.statements.push(stmt),
-                _ => (), // Just ignore that case
-            };
-            self.next_token();
-        }
-        program
-    }
 
-    fn parse_statement(&mut self, lambda_parsers: &LambdaParsers) -> Option<token::Statements> {
-        match self.current_token.token_type.as_ref() {
-            token::LET => match self.parse_let_statement() {
-                Some(stmt) => Some(token::Statements::LetStatement(stmt)),
-                _ => None,
-            },
-            token::RETURN => match self.parse_return_statement() {
-                Some(stmt) => Some(token::Statements::ReturnStatement(stmt)),
-                _ => None,
-            },
-            // If we did not encounter any `let` or `return` it might've happened that
-            // we've encountered another type of statement.
-            // The last one in our language - expresion statement.
-            _ => match self.parse_expression_statement(lambda_parsers) {
-                Some(stmt) => Some(token::Statements::ExpressionStatement(stmt)),
-                _ => None,
-            },
+        token::Parameter {
+            pattern,
+            default,
+            variadic: false,
         }
     }
 
-    fn parse_let_statement(&mut self) -> Option<token::LetStatement> {
-        let token = self.current_token.clone();
-
-        if self.peek_token.token_type == token::IDENT {
-            self.next_token();
-        } else {
-            self.peek_error(token::IDENT.to_string());
+    fn parse_function_parameters(parser: &mut Parser) -> Option<Vec<token::Parameter>> {
+        if parser.peek_token.token_type == token::RPAREN {
+            parser.next_token();
             return None;
         }
 
-        let name = token::Identifier {
-            token: self.current_token.clone(),
-            value: self.current_token.literal.clone(),
-        };
+        parser.next_token(); // skip `(`
 
-        if self.peek_token.token_type == token::ASSIGN {
-            self.next_token();
-        } else {
-            self.peek_error(token::ASSIGN.to_string());
-            return None;
-        }
+        let mut parameters = vec![Self::parse_function_parameter(parser)];
 
-        self.next_token(); // skip ASSIGN `=` in let statement
+        while parser.peek_token.token_type == token::COMMA {
+            // next parameter exists
+            parser.next_token(); // set cursor to comma
+            parser.next_token(); // skip comma
 
-        // TODO: Very bad, we have to create lambda parsers here.
-        // I will fix it soon.
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-        lambda_parsers.register_parsers();
+            if parameters.last().is_some_and(|p| p.variadic) {
+                let message = "rest parameter must be the last one".to_string();
+                parser.errors.push(message);
+            }
 
-        let value = match self.parse_expression(&lambda_parsers, token::LOWEST) {
-            Some(expr) => expr,
-            None => panic!("FAILED TO PARSE {}", self.current_token.token_type),
-        };
+            let parameter = Self::parse_function_parameter(parser);
 
-        if self.peek_token.token_type == token::SEMICOLON {
-            self.next_token(); // set cursor to SEMICOLON
+            // Arguments fill parameters in order, one without default
+            // after a defaulted one could never be left out.
+            let defaulted = parameters.iter().any(|p| p.default.is_some());
+            if defaulted && parameter.default.is_none() && !parameter.variadic {
+                let message = format!(
+                    "parameter `{}` without default follows one with default",
+                    parameter.pattern
+                );
+                parser.errors.push(message);
+            }
+
+            parameters.push(parameter);
         }
-        // TODO: It's a fragile design for now, this code might hang if we don't
-        // have a terminating semicolon and next token is token::EOF
-        // in this case we'll enter an infinite loop.
-        // Doesn't next_token() protect us from this? Apparently - not.
-        //
-        // UPD: Now as we parse expression here we do not care about
-        // this corner case that much, but I think we should care about it
-        // in the future. So I'll leave this note here.
-        //
-        // while !(self.current_token.token_type == token::SEMICOLON) {
-        //     self.next_token(); // skip to next statement in our program
-        // }
 
-        // TODO: Same happens in return parser. I'm skipping
-        // semicolon, so in `parse_statement` function I can
-        // just start to parse next value.
-        // Weird, it does not work here that way.
-        // self.next_token();
+        // I expect closing `)` after function arguments.
+        // (a,b,c,d,e) <- this one
+        if parser.peek_token.token_type != token::RPAREN {
+            panic!(
+                "Expected closing `)`, got `{}`",
+                parser.peek_token.token_type
+            );
+        }
+        parser.next_token();
 
-        Some(token::LetStatement { token, name, value })
+        Some(parameters)
     }
 
-    // TODO: Why Option here?
-    fn parse_return_statement(&mut self) -> Option<token::ReturnStatement> {
-        let token = self.current_token.clone();
+    // Finishes short lambda after its parameters were parsed,
+    // the cursor is on the last token before `=>`.
+    // Body is either a block or a single expression.
+    fn parse_arrow_function_body(
+        parser: &mut Parser,
+        parameters: Option<Vec<token::Parameter>>,
+    ) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-        self.next_token();
+        if parser.peek_token.token_type != token::FAT_ARROW {
+            panic!(
+                "I've expected `=>`, but got {}",
+                parser.peek_token.token_type
+            );
+        }
+        parser.next_token(); // set cursor to `=>`
 
-        // TODO: Very bad, we have to create lambda parsers here.
-        // I will fix it soon.
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
+        let outer_yields = parser.yields.replace(false);
+
+        let body = if parser.peek_token.token_type == token::LBRACE {
+            parser.next_token(); // set cursor to `{`
+            Self::parse_block_statement(parser)
+        } else {
+            parser.next_token(); // skip `=>`
+            Self::parse_expression_as_block(parser, lambda_parsers)
         };
-        lambda_parsers.register_parsers();
 
-        let return_value = match self.parse_expression(&lambda_parsers, token::LOWEST) {
-            Some(expr) => expr,
-            None => panic!(
-                "UNREACHABLE: return statement parser with {}",
-                self.current_token.token_type
-            ),
+        let generator = std::mem::replace(&mut parser.yields, outer_yields).unwrap_or(false);
+
+        token::Expression::FunctionLiteral(token::FunctionLiteral {
+            token: token::Token {
+                token_type: token::FUNCTION.to_string(),
+                literal: "fn".to_string(),
+            },
+            parameters,
+            body,
+            generator,
+        })
+    }
+
+    // Placeholder function: `#(_ * 2)` is `fn(_1) { _1 * 2 }`,
+    // every `_` in the body becomes the next parameter.
+    fn parse_placeholder_function(parser: &mut Parser) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        // Nested `#(...)` count their own placeholders.
+        let outer_placeholders = parser.placeholders.replace(0);
+        let outer_yields = parser.yields.replace(false);
+
+        parser.next_token(); // skip `#(`
+
+        let body = Self::parse_expression_as_block(parser, lambda_parsers);
+
+        if parser.peek_token.token_type != token::RPAREN {
+            panic!(
+                "I've expected closing `)`, but got {}",
+                parser.peek_token.token_type
+            );
+        }
+        parser.next_token(); // set cursor to `)`
+
+        let count = std::mem::replace(&mut parser.placeholders, outer_placeholders).unwrap_or(0);
+        let generator = std::mem::replace(&mut parser.yields, outer_yields).unwrap_or(false);
+
+        let parameters = (1..=count)
+            .map(|i| {
+                let name = format!("_{}", i);
+                token::Parameter {
+                    pattern: token::Pattern::Identifier(token::Identifier {
+                        token: token::Token {
+                            token_type: token::IDENT.to_string(),
+                            literal: name.clone(),
+                        },
+                        value: name,
+                    }),
+                    default: None,
+                    variadic: false,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        token::Expression::FunctionLiteral(token::FunctionLiteral {
+            token: token::Token {
+                token_type: token::FUNCTION.to_string(),
+                literal: "fn".to_string(),
+            },
+            parameters: if parameters.is_empty() {
+                None
+            } else {
+                Some(parameters)
+            },
+            body,
+            generator,
+        })
+    }
+
+    // Wraps a single expression into a block,
+    // used by function and match arm shorthands.
+    fn parse_expression_as_block(
+        parser: &mut Parser,
+        lambda_parsers: &LambdaParsers,
+    ) -> token::BlockStatement {
+        let token = parser.current_token.clone();
+        let location = parser.current_location();
+        let expression = match parser.parse_expression(lambda_parsers, token::LOWEST) {
+            Some(expression) => expression,
+            None => panic!("I don't know how to parse `{}`", parser.current_token.literal),
         };
 
-        if self.peek_token.token_type == token::SEMICOLON {
-            self.next_token(); // set cursor to semicolon if any
+        token::BlockStatement {
+            token: token.clone(),
+            statements: vec![token::Statements::ExpressionStatement(
+                token::ExpressionStatement { token, expression, location },
+            )],
+        }
+    }
+
+    fn parse_function_literal(parser: &mut Parser) -> token::Expression {
+        let token = parser.current_token.clone();
+
+        // `fn*` is a generator even without `yield`.
+        let star = parser.peek_token.token_type == token::ASTERISK;
+        if star {
+            parser.next_token(); // set cursor to `*`
         }
 
-        let statement = token::ReturnStatement {
+        if parser.peek_token.token_type != token::LPAREN {
+            panic!(
+                "I've expected `(`, but got {}",
+                parser.peek_token.token_type
+            );
+        };
+        parser.next_token(); // set cursor to `(`
+
+        let parameters = Self::parse_function_parameters(parser);
+
+        // This block is the same as one above
+        // We should find LBRACE or panic! (we should not panic actually)
+        if parser.peek_token.token_type != token::LBRACE {
+            panic!(
+                "I've expected opening `{{`, but got `{}`",
+                parser.peek_token.token_type
+            );
+        };
+
+        // next token is `{`, everything is fine, set cursor on it
+        parser.next_token();
+
+        let outer_yields = parser.yields.replace(false);
+        let body = Self::parse_block_statement(parser);
+        let yields = std::mem::replace(&mut parser.yields, outer_yields).unwrap_or(false);
+
+        token::Expression::FunctionLiteral(token::FunctionLiteral {
             token,
-            return_value,
+            parameters,
+            body,
+            generator: star || yields,
+        })
+    }
+
+    // Macro parameters are just names, they get quoted arguments.
+    fn parse_macro_literal(parser: &mut Parser) -> token::Expression {
+        let token = parser.current_token.clone();
+
+        if parser.peek_token.token_type != token::LPAREN {
+            panic!(
+                "I've expected `(`, but got {}",
+                parser.peek_token.token_type
+            );
         };
+        parser.next_token(); // set cursor to `(`
 
-        Some(statement)
+        let mut parameters = vec![];
+
+        for parameter in Self::parse_function_parameters(parser).unwrap_or_default() {
+            match parameter {
+                token::Parameter {
+                    pattern: token::Pattern::Identifier(identifier),
+                    default: None,
+                    variadic: false,
+                } => parameters.push(identifier),
+                parameter => parser.errors.push(format!(
+                    "macro parameter must be a name, got `{}`",
+                    parameter
+                )),
+            }
+        }
+
+        if parser.peek_token.token_type != token::LBRACE {
+            panic!(
+                "I've expected opening `{{`, but got `{}`",
+                parser.peek_token.token_type
+            );
+        };
+
+        // next token is `{`, everything is fine, set cursor on it
+        parser.next_token();
+
+        let body = Self::parse_block_statement(parser);
+
+        token::Expression::MacroLiteral(token::MacroLiteral {
+            token,
+            parameters,
+            body,
+        })
     }
 
-    fn parse_expression_statement(
-        &mut self,
-        lambda_parsers: &LambdaParsers,
-    ) -> Option<token::ExpressionStatement> {
-        let statement = token::ExpressionStatement {
-            token: self.current_token.clone(),
-            expression: match self.parse_expression(lambda_parsers, token::LOWEST) {
-                Some(expression) => expression,
-                None => panic!("I don't know how to parse `{}`", self.current_token.literal),
-            },
+    fn parse_match_expression(parser: &mut Parser) -> token::Expression {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let token = parser.current_token.clone();
+
+        if parser.peek_token.token_type != token::LPAREN {
+            panic!(
+                "I've expected `(`, but got {}",
+                parser.peek_token.token_type
+            );
+        };
+        parser.next_token(); // set cursor to `(`
+        parser.next_token(); // skip `(`
+
+        let subject = match parser.parse_expression(lambda_parsers, token::LOWEST) {
+            Some(s) => s,
+            None => panic!("failed to parse match subject"),
         };
 
-        if self.peek_token.token_type == token::SEMICOLON {
-            self.next_token();
+        if parser.peek_token.token_type != token::RPAREN {
+            panic!(
+                "I've expected closing `)`, but got {}",
+                parser.peek_token.token_type
+            );
+        };
+        parser.next_token(); // set cursor to `)`
+
+        if parser.peek_token.token_type != token::LBRACE {
+            panic!(
+                "I've expected opening `{{`, but got {}",
+                parser.peek_token.token_type
+            );
+        };
+        parser.next_token(); // set cursor to `{`
+
+        let mut arms = Vec::new();
+        // First arm which matches anything, every arm after it is dead code.
+        let mut catch_all: Option<String> = None;
+
+        while parser.peek_token.token_type != token::RBRACE {
+            parser.next_token(); // skip `{` or `,`
+
+            let pattern = match parser.parse_pattern() {
+                Some(pattern) => pattern,
+                None => panic!("Failed to parse match pattern: {:?}", parser.errors),
+            };
+
+            let guard = if parser.peek_token.token_type == token::IF {
+                parser.next_token(); // set cursor to `if`
+                parser.next_token(); // skip `if`
+
+                // `=>` after the guard belongs to the arm, not to a short lambda.
+                let allow_arrow_functions = parser.allow_arrow_functions;
+                parser.allow_arrow_functions = false;
+                let guard = parser.parse_expression(lambda_parsers, token::LOWEST);
+                parser.allow_arrow_functions = allow_arrow_functions;
+
+                match guard {
+                    Some(guard) => Some(guard),
+                    None => panic!("failed to parse match guard"),
+                }
+            } else {
+                None
+            };
+
+            if parser.peek_token.token_type != token::FAT_ARROW {
+                panic!(
+                    "I've expected `=>` after match pattern, but got {}",
+                    parser.peek_token.token_type
+                );
+            }
+            parser.next_token(); // set cursor to `=>`
+
+            // Arm body is either a block or a single expression, which
+            // we wrap into a block to evaluate both of them the same way.
+            let is_block = parser.peek_token.token_type == token::LBRACE;
+            let body = if is_block {
+                parser.next_token(); // set cursor to `{`
+                Self::parse_block_statement(parser)
+            } else {
+                parser.next_token(); // skip `=>`
+                Self::parse_expression_as_block(parser, lambda_parsers)
+            };
+
+            match &catch_all {
+                Some(previous) => parser.warnings.push(format!(
+                    "unreachable match arm `{}`, pattern `{}` above matches every value",
+                    pattern, previous
+                )),
+                None if guard.is_none() && pattern.is_irrefutable() => {
+                    catch_all = Some(pattern.to_string())
+                }
+                None => (),
+            }
+
+            arms.push(token::MatchArm {
+                pattern,
+                guard,
+                body,
+            });
+
+            if parser.peek_token.token_type == token::COMMA {
+                parser.next_token(); // set cursor to `,`
+            } else if parser.peek_token.token_type != token::RBRACE && !is_block {
+                panic!(
+                    "Expected `,` or closing `}}` after match arm, got {}",
+                    parser.peek_token.token_type
+                );
+            }
         }
+        parser.next_token(); // set cursor to `}`
 
-        Some(statement)
+        if catch_all.is_none() {
+            parser.check_match_exhaustiveness(&arms);
+        }
+
+        token::Expression::MatchExpression(Box::new(token::MatchExpression {
+            token,
+            subject,
+            arms,
+        }))
     }
 
     #[trace]
-    fn parse_expression(
-        &mut self,
-        lambda_parsers: &LambdaParsers,
-        precedence: u8,
-    ) -> Option<token::Expression> {
-        let prefix_function = lambda_parsers
-            .prefix_parse_fns
-            .get(&self.current_token.token_type.clone());
+    fn parse_block_statement(parser: &mut Parser) -> token::BlockStatement {
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-        let mut left = match prefix_function {
-            Some(PrefixParseFn(prefix_parse_fn)) => prefix_parse_fn(self),
-            None => {
-                // this step might be redundant, because we check the error above
-                self.register_no_prefix_parser_found(self.current_token.token_type.clone());
-                return None;
-            }
-        };
+        let token = parser.current_token.clone();
+        let mut statements = Vec::new();
 
-        while !(self.peek_token.token_type == token::SEMICOLON)
-            && (precedence < precedence_by_token_type(&self.peek_token.token_type))
+        // current token is `{` skip it
+        parser.next_token();
+
+        while parser.current_token.token_type != token::RBRACE
+            && parser.current_token.token_type != token::EOF
         {
-            let infix_function = lambda_parsers
-                .infix_parse_fns
-                .get(&self.peek_token.token_type.clone());
+            let statement = match parser.parse_statement(lambda_parsers) {
+                Some(s) => s,
+                None => panic!("useless panic one more time, failed to parse if block"),
+            };
+            statements.push(statement);
+            parser.next_token();
+        }
 
-            // change cursor position before calling infix_parse_fn
-            // call it with new position
-            self.next_token();
+        token::BlockStatement { token, statements }
+    }
 
-            // update left
-            left = match infix_function {
-                Some(InfixParseFn(infix_parse_fn)) => infix_parse_fn(self, left.clone()),
-                None => panic!("Cannot find infix function for {:?}", self.peek_token),
+    fn parse_call_expression(
+        parser: &mut Parser,
+        function: token::Expression,
+    ) -> token::Expression {
+        type NamedArguments = Vec<(token::Identifier, token::Expression)>;
+
+        // Arguments are either positional `f(1, 2)` or named `f(y: 2, x: 1)`.
+        // Positional ones should come first.
+        fn parse_call_argument(
+            parser: &mut Parser,
+            positional: &mut Vec<token::Expression>,
+            named: &mut NamedArguments,
+        ) {
+            let lambda_parsers: &LambdaParsers = &PARSERS;
+
+            let name = if parser.current_token.token_type == token::IDENT
+                && parser.peek_token.token_type == token::COLON
+            {
+                let name = token::Identifier {
+                    token: parser.current_token.clone(),
+                    value: parser.current_token.literal.clone(),
+                };
+                parser.next_token(); // set cursor to `:`
+                parser.next_token(); // skip `:`
+                Some(name)
+            } else {
+                None
+            };
+
+            let argument = match parser.parse_expression(lambda_parsers, token::LOWEST) {
+                Some(expr) => expr,
+                None => panic!("Failed to parse param for function CALL"),
             };
+
+            match name {
+                Some(name) => named.push((name, argument)),
+                None => {
+                    if !named.is_empty() {
+                        let message = format!(
+                            "positional argument `{}` follows named argument",
+                            argument
+                        );
+                        parser.errors.push(message);
+                    }
+                    positional.push(argument)
+                }
+            }
         }
 
-        Some(left)
-    }
+        fn parse_call_arguments(
+            parser: &mut Parser,
+        ) -> (Option<Vec<token::Expression>>, NamedArguments) {
+            if parser.peek_token.token_type == token::RPAREN {
+                parser.next_token();
+                return (None, Vec::new());
+            }
+
+            parser.next_token(); // skip `RPAREN`
+
+            let mut params = Vec::new();
+            let mut named = Vec::new();
+
+            parse_call_argument(parser, &mut params, &mut named);
+
+            while parser.peek_token.token_type == token::COMMA {
+                parser.next_token(); // set cursor to `,`
+                parser.next_token(); // skip `,` and move cursor to next token
+
+                parse_call_argument(parser, &mut params, &mut named);
+            }
+
+            if parser.peek_token.token_type != token::RPAREN {
+                panic!(
+                    "Expected closing `)` after params, got {}",
+                    parser.peek_token.token_type
+                );
+            }
+            parser.next_token(); // set cursor to RPAREN and leave parser
+
+            if params.is_empty() {
+                (None, named)
+            } else {
+                (Some(params), named)
+            }
+        }
+
+        let token = parser.current_token.clone();
+        let (arguments, named_arguments) = parse_call_arguments(parser);
+
+        token::Expression::CallExpression(Box::new(token::CallExpression {
+            token,
+            function,
+            arguments,
+            named_arguments,
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct Parser {
+    lexer: lexer::Lexer,
+    current_token: token::Token,
+    peek_token: token::Token,
+    pub errors: Vec<String>,
+    // Warnings don't stop evaluation, REPL just shows them to the user.
+    pub warnings: Vec<String>,
+    // Variant names of every enum declared so far, used to check
+    // that `match` covers all of them. REPL carries it between lines.
+    pub enums: HashMap<String, Vec<String>>,
+    // Where current and peek tokens start in the input.
+    current_position: u32,
+    peek_position: u32,
+    // Number of `_` seen in the innermost `#(...)`, None outside of it.
+    placeholders: Option<usize>,
+    // Whether the innermost function has `yield`, None outside of functions.
+    yields: Option<bool>,
+    allow_arrow_functions: bool,
+}
+
+impl Parser {
+    pub fn new(mut lexer: lexer::Lexer) -> Self {
+        let current_token = lexer.next_token();
+        let current_position = lexer.token_start();
+        let peek_token = lexer.next_token();
+        let peek_position = lexer.token_start();
+        let errors = Vec::new();
+        let warnings = Vec::new();
+
+        Self {
+            lexer,
+            current_token,
+            peek_token,
+            errors,
+            warnings,
+            current_position,
+            peek_position,
+            enums: builtin_enums(),
+            placeholders: None,
+            yields: None,
+            allow_arrow_functions: true,
+        }
+    }
+
+    fn next_token(&mut self) {
+        self.current_token = self.peek_token.clone();
+        self.current_position = self.peek_position;
+        self.peek_token = self.lexer.next_token();
+        self.peek_position = self.lexer.token_start();
+    }
+
+    // `line:column` of the current token, for error messages.
+    fn current_location(&self) -> String {
+        let (line, column) = self.lexer.location(self.current_position);
+        format!("{}:{}", line, column)
+    }
+
+    // TODO: Current attempt. Move link to LambdaParsers to every function.
+    // To avoid double borrowing of self in case of mutual recursive
+    // calls.
+    pub fn parse_program(&mut self, lambda_parsers: &LambdaParsers) -> ast::Program {
+        let mut program = ast::Program {
+            statements: Vec::new(),
+        };
+        while self.current_token.token_type != token::EOF {
+            let statement = self.parse_statement(lambda_parsers);
+            if let Some(stmt) = statement {
+                program.statements.push(stmt);
+            }
+            self.next_token();
+        }
+        program
+    }
+
+    fn parse_statement(&mut self, lambda_parsers: &LambdaParsers) -> Option<token::Statements> {
+        match self.current_token.token_type.as_ref() {
+            token::LET => self.parse_let_statement().map(token::Statements::LetStatement),
+            token::RETURN => self.parse_return_statement().map(token::Statements::ReturnStatement),
+            token::STRUCT => self.parse_struct_statement().map(token::Statements::StructStatement),
+            token::ENUM => self.parse_enum_statement().map(token::Statements::EnumStatement),
+            token::IMPL => self.parse_impl_statement().map(token::Statements::ImplStatement),
+            token::IMPORT => self.parse_import_statement().map(token::Statements::ImportStatement),
+            token::EXPORT => self.parse_export_statement().map(token::Statements::ExportStatement),
+            // If we did not encounter any `let` or `return` it might've happened that
+            // we've encountered another type of statement.
+            // The last one in our language - expresion statement.
+            _ => self.parse_expression_statement(lambda_parsers).map(token::Statements::ExpressionStatement),
+        }
+    }
+
+    // Structure: struct <identifier> { <identifier>, ... }
+    fn parse_struct_statement(&mut self) -> Option<token::StructStatement> {
+        let token = self.current_token.clone();
+
+        if self.peek_token.token_type == token::IDENT {
+            self.next_token();
+        } else {
+            self.peek_error(token::IDENT.to_string());
+            return None;
+        }
+
+        let name = token::Identifier {
+            token: self.current_token.clone(),
+            value: self.current_token.literal.clone(),
+        };
+
+        if self.peek_token.token_type == token::LBRACE {
+            self.next_token();
+        } else {
+            self.peek_error(token::LBRACE.to_string());
+            return None;
+        }
+
+        let mut fields: Vec<token::Identifier> = Vec::new();
+        // Errors in names are reported once the declaration is read,
+        // so parsing goes on after its `}`.
+        let mut valid = true;
+
+        while self.peek_token.token_type != token::RBRACE {
+            if self.peek_token.token_type == token::IDENT {
+                self.next_token(); // skip `{` or `,`
+            } else {
+                self.peek_error(token::IDENT.to_string());
+                return None;
+            }
+
+            let field = token::Identifier {
+                token: self.current_token.clone(),
+                value: self.current_token.literal.clone(),
+            };
+
+            if fields.iter().any(|f| f.value == field.value) {
+                self.errors.push(format!(
+                    "duplicate field `{}` in struct {}",
+                    field, name
+                ));
+                valid = false;
+            }
+
+            fields.push(field);
+
+            if self.peek_token.token_type != token::COMMA {
+                break;
+            }
+            self.next_token(); // set cursor to `,`
+        }
+
+        if self.peek_token.token_type == token::RBRACE {
+            self.next_token();
+        } else {
+            self.peek_error(token::RBRACE.to_string());
+            return None;
+        }
+
+        if self.peek_token.token_type == token::SEMICOLON {
+            self.next_token();
+        }
+
+        if !valid {
+            return None;
+        }
+
+        Some(token::StructStatement {
+            token,
+            name,
+            fields,
+        })
+    }
+
+    // Structure: enum <identifier> { <identifier>[(<identifier>, ...)], ... }
+    fn parse_enum_statement(&mut self) -> Option<token::EnumStatement> {
+        let token = self.current_token.clone();
+
+        if self.peek_token.token_type == token::IDENT {
+            self.next_token();
+        } else {
+            self.peek_error(token::IDENT.to_string());
+            return None;
+        }
+
+        let name = token::Identifier {
+            token: self.current_token.clone(),
+            value: self.current_token.literal.clone(),
+        };
+
+        if self.peek_token.token_type == token::LBRACE {
+            self.next_token();
+        } else {
+            self.peek_error(token::LBRACE.to_string());
+            return None;
+        }
+
+        let mut variants: Vec<token::EnumVariant> = Vec::new();
+        // As in structs, bad names don't stop parsing before `}`.
+        let mut valid = true;
+
+        while self.peek_token.token_type != token::RBRACE {
+            if self.peek_token.token_type == token::IDENT {
+                self.next_token(); // skip `{` or `,`
+            } else {
+                self.peek_error(token::IDENT.to_string());
+                return None;
+            }
+
+            let variant_name = token::Identifier {
+                token: self.current_token.clone(),
+                value: self.current_token.literal.clone(),
+            };
+
+            // Patterns tell variants from bindings by the first letter.
+            if !is_variant_name(&variant_name.value) {
+                self.errors.push(format!(
+                    "variant `{}` of enum {} must start with a capital letter",
+                    variant_name, name
+                ));
+                valid = false;
+            }
+
+            if variants.iter().any(|v| v.name.value == variant_name.value) {
+                self.errors.push(format!(
+                    "duplicate variant `{}` in enum {}",
+                    variant_name, name
+                ));
+                valid = false;
+            }
+
+            let mut fields = Vec::new();
+
+            if self.peek_token.token_type == token::LPAREN {
+                self.next_token(); // set cursor to `(`
+
+                while self.peek_token.token_type != token::RPAREN {
+                    if self.peek_token.token_type == token::IDENT {
+                        self.next_token(); // skip `(` or `,`
+                    } else {
+                        self.peek_error(token::IDENT.to_string());
+                        return None;
+                    }
+
+                    fields.push(token::Identifier {
+                        token: self.current_token.clone(),
+                        value: self.current_token.literal.clone(),
+                    });
+
+                    if self.peek_token.token_type != token::COMMA {
+                        break;
+                    }
+                    self.next_token(); // set cursor to `,`
+                }
+
+                if self.peek_token.token_type == token::RPAREN {
+                    self.next_token();
+                } else {
+                    self.peek_error(token::RPAREN.to_string());
+                    return None;
+                }
+            }
+
+            variants.push(token::EnumVariant {
+                name: variant_name,
+                fields,
+            });
+
+            if self.peek_token.token_type != token::COMMA {
+                break;
+            }
+            self.next_token(); // set cursor to `,`
+        }
+
+        if self.peek_token.token_type == token::RBRACE {
+            self.next_token();
+        } else {
+            self.peek_error(token::RBRACE.to_string());
+            return None;
+        }
+
+        if self.peek_token.token_type == token::SEMICOLON {
+            self.next_token();
+        }
+
+        if !valid {
+            return None;
+        }
+
+        self.enums.insert(
+            name.value.clone(),
+            variants.iter().map(|v| v.name.value.clone()).collect(),
+        );
+
+        Some(token::EnumStatement {
+            token,
+            name,
+            variants,
+        })
+    }
+
+    // Structure: impl <identifier> { let <identifier> = <expression>; ... }
+    fn parse_impl_statement(&mut self) -> Option<token::ImplStatement> {
+        let token = self.current_token.clone();
+
+        if self.peek_token.token_type == token::IDENT {
+            self.next_token();
+        } else {
+            self.peek_error(token::IDENT.to_string());
+            return None;
+        }
+
+        let mut name = token::Identifier {
+            token: self.current_token.clone(),
+            value: self.current_token.literal.clone(),
+        };
+
+        // `impl Protocol for Type`, the first name was a protocol.
+        let mut protocol = None;
+        if self.peek_token.token_type == token::FOR {
+            self.next_token();
+
+            if self.peek_token.token_type == token::IDENT {
+                self.next_token();
+            } else {
+                self.peek_error(token::IDENT.to_string());
+                return None;
+            }
+
+            let type_name = token::Identifier {
+                token: self.current_token.clone(),
+                value: self.current_token.literal.clone(),
+            };
+            protocol = Some(std::mem::replace(&mut name, type_name));
+        }
+
+        if self.peek_token.token_type == token::LBRACE {
+            self.next_token();
+        } else {
+            self.peek_error(token::LBRACE.to_string());
+            return None;
+        }
+
+        let mut methods = Vec::new();
+
+        while self.peek_token.token_type != token::RBRACE {
+            if self.peek_token.token_type == token::LET {
+                self.next_token(); // set cursor to `let`
+            } else {
+                self.peek_error(token::LET.to_string());
+                return None;
+            }
+
+            let method = self.parse_let_statement()?;
+
+            // Method needs a name, destructuring makes no sense here.
+            if let token::Pattern::Identifier(_) = method.pattern {
+                methods.push(method);
+            } else {
+                self.errors.push(format!(
+                    "expected method name in impl {}, got pattern {}",
+                    name, method.pattern
+                ));
+                return None;
+            }
+        }
+
+        self.next_token(); // set cursor to `}`
+
+        if self.peek_token.token_type == token::SEMICOLON {
+            self.next_token();
+        }
+
+        Some(token::ImplStatement {
+            token,
+            protocol,
+            name,
+            methods,
+        })
+    }
+
+    // Structure: import <string> as <identifier>;
+    fn parse_import_statement(&mut self) -> Option<token::ImportStatement> {
+        let token = self.current_token.clone();
+        let location = self.current_location();
+
+        if self.peek_token.token_type == token::STRING {
+            self.next_token();
+        } else {
+            self.peek_error(token::STRING.to_string());
+            return None;
+        }
+
+        let path = self.current_token.literal.clone();
+
+        if self.peek_token.token_type == token::AS {
+            self.next_token();
+        } else {
+            self.peek_error(token::AS.to_string());
+            return None;
+        }
+
+        if self.peek_token.token_type == token::IDENT {
+            self.next_token();
+        } else {
+            self.peek_error(token::IDENT.to_string());
+            return None;
+        }
+
+        let name = token::Identifier {
+            token: self.current_token.clone(),
+            value: self.current_token.literal.clone(),
+        };
+
+        if self.peek_token.token_type == token::SEMICOLON {
+            self.next_token();
+        }
+
+        Some(token::ImportStatement {
+            token,
+            path,
+            name,
+            location,
+        })
+    }
+
+    // Structure: export <let statement | struct statement | enum statement>
+    fn parse_export_statement(&mut self) -> Option<token::ExportStatement> {
+        let token = self.current_token.clone();
+
+        self.next_token(); // skip `export`
+
+        let statement = match self.current_token.token_type.as_ref() {
+            token::LET => token::Statements::LetStatement(self.parse_let_statement()?),
+            token::STRUCT => token::Statements::StructStatement(self.parse_struct_statement()?),
+            token::ENUM => token::Statements::EnumStatement(self.parse_enum_statement()?),
+            _ => {
+                self.errors.push(format!(
+                    "only `let`, `struct` and `enum` can be exported, got `{}` at {}",
+                    self.current_token.literal,
+                    self.current_location()
+                ));
+                return None;
+            }
+        };
+
+        Some(token::ExportStatement {
+            token,
+            statement: Box::new(statement),
+        })
+    }
+
+    fn parse_let_statement(&mut self) -> Option<token::LetStatement> {
+        let token = self.current_token.clone();
+        let location = self.current_location();
+
+        self.next_token(); // skip `let`, the pattern starts here
+
+        let pattern = self.parse_pattern()?;
+
+        if self.peek_token.token_type == token::ASSIGN {
+            self.next_token();
+        } else {
+            self.peek_error(token::ASSIGN.to_string());
+            return None;
+        }
+
+        self.next_token(); // skip ASSIGN `=` in let statement
+
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let value = match self.parse_expression(lambda_parsers, token::LOWEST) {
+            Some(expr) => expr,
+            None => panic!("FAILED TO PARSE {}", self.current_token.token_type),
+        };
+
+        if self.peek_token.token_type == token::SEMICOLON {
+            self.next_token(); // set cursor to SEMICOLON
+        }
+        // TODO: It's a fragile design for now, this code might hang if we don't
+        // have a terminating semicolon and next token is token::EOF
+        // in this case we'll enter an infinite loop.
+        // Doesn't next_token() protect us from this? Apparently - not.
+        //
+        // UPD: Now as we parse expression here we do not care about
+        // this corner case that much, but I think we should care about it
+        // in the future. So I'll leave this note here.
+        //
+        // while !(self.current_token.token_type == token::SEMICOLON) {
+        //     self.next_token(); // skip to next statement in our program
+        // }
+
+        // TODO: Same happens in return parser. I'm skipping
+        // semicolon, so in `parse_statement` function I can
+        // just start to parse next value.
+        // Weird, it does not work here that way.
+        // self.next_token();
+
+        Some(token::LetStatement {
+            token,
+            pattern,
+            value,
+            location,
+        })
+    }
+
+    // TODO: Why Option here?
+    fn parse_return_statement(&mut self) -> Option<token::ReturnStatement> {
+        let token = self.current_token.clone();
+        let location = self.current_location();
+
+        self.next_token();
+
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let return_value = match self.parse_expression(lambda_parsers, token::LOWEST) {
+            Some(expr) => expr,
+            None => panic!(
+                "UNREACHABLE: return statement parser with {}",
+                self.current_token.token_type
+            ),
+        };
+
+        if self.peek_token.token_type == token::SEMICOLON {
+            self.next_token(); // set cursor to semicolon if any
+        }
+
+        let statement = token::ReturnStatement {
+            token,
+            return_value,
+            location,
+        };
+
+        Some(statement)
+    }
+
+    fn parse_expression_statement(
+        &mut self,
+        lambda_parsers: &LambdaParsers,
+    ) -> Option<token::ExpressionStatement> {
+        let statement = token::ExpressionStatement {
+            token: self.current_token.clone(),
+            location: self.current_location(),
+            expression: match self.parse_expression(lambda_parsers, token::LOWEST) {
+                Some(expression) => expression,
+                None => panic!("I don't know how to parse `{}`", self.current_token.literal),
+            },
+        };
+
+        if self.peek_token.token_type == token::SEMICOLON {
+            self.next_token();
+        }
+
+        Some(statement)
+    }
+
+    #[trace]
+    fn parse_expression(
+        &mut self,
+        lambda_parsers: &LambdaParsers,
+        precedence: u8,
+    ) -> Option<token::Expression> {
+        let prefix_function = lambda_parsers
+            .prefix_parse_fns
+            .get(&self.current_token.token_type.clone());
+
+        let mut left = match prefix_function {
+            Some(PrefixParseFn(prefix_parse_fn)) => prefix_parse_fn(self),
+            None => {
+                // this step might be redundant, because we check the error above
+                self.register_no_prefix_parser_found(self.current_token.token_type.clone());
+                return None;
+            }
+        };
+
+        while self.peek_token.token_type != token::SEMICOLON
+            && (precedence < precedence_by_token_type(&self.peek_token.token_type))
+        {
+            let infix_function = lambda_parsers
+                .infix_parse_fns
+                .get(&self.peek_token.token_type.clone());
+
+            // change cursor position before calling infix_parse_fn
+            // call it with new position
+            self.next_token();
+
+            // update left
+            left = match infix_function {
+                Some(InfixParseFn(infix_parse_fn)) => infix_parse_fn(self, left.clone()),
+                None => panic!("Cannot find infix function for {:?}", self.peek_token),
+            };
+        }
+
+        Some(left)
+    }
+
+    // Parses binding pattern for `let` and function parameters.
+    // Cursor should be on the first token of the pattern,
+    // it's left on the last one.
+    fn parse_pattern(&mut self) -> Option<token::Pattern> {
+        match self.current_token.token_type.as_ref() {
+            token::IDENT if self.current_token.literal == "_" => {
+                Some(token::Pattern::Wildcard(self.current_token.clone()))
+            }
+            token::IDENT if is_variant_name(&self.current_token.literal) => self
+                .parse_variant_pattern()
+                .map(token::Pattern::VariantPattern),
+            token::IDENT => Some(token::Pattern::Identifier(token::Identifier {
+                token: self.current_token.clone(),
+                value: self.current_token.literal.clone(),
+            })),
+            token::INT => Some(token::Pattern::Literal(LambdaParsers::parse_int_literal(
+                self,
+            ))),
+            // `-` and the number are folded into a single literal.
+            token::MINUS if self.peek_token.token_type == token::INT => Some(
+                token::Pattern::Literal(LambdaParsers::parse_negative_int_literal(self)),
+            ),
+            token::STRING => Some(token::Pattern::Literal(
+                LambdaParsers::parse_string_literal(self),
+            )),
+            token::TRUE | token::FALSE => {
+                Some(token::Pattern::Literal(LambdaParsers::parse_boolean(self)))
+            }
+            token::CHAR => Some(token::Pattern::Literal(LambdaParsers::parse_char_literal(
+                self,
+            ))),
+            token::LBRACKET => self.parse_array_pattern().map(token::Pattern::ArrayPattern),
+            token::LBRACE => self.parse_hash_pattern().map(token::Pattern::HashPattern),
+            _ => {
+                let message = format!(
+                    "expected pattern, got {} instead",
+                    self.current_token.token_type
+                );
+                self.errors.push(message);
+                None
+            }
+        }
+    }
+
+    // Structure: [<pattern>, <pattern>, ...<identifier>]
+    fn parse_array_pattern(&mut self) -> Option<token::ArrayPattern> {
+        let token = self.current_token.clone();
+        let mut elements = Vec::new();
+        let mut rest = None;
+
+        while self.peek_token.token_type != token::RBRACKET {
+            self.next_token(); // skip `[` or `,`
+
+            if self.current_token.token_type == token::ELLIPSIS {
+                rest = Some(self.parse_rest_binding()?);
+                break;
+            }
+
+            elements.push(self.parse_pattern()?);
+
+            if self.peek_token.token_type != token::COMMA {
+                break;
+            }
+            self.next_token(); // set cursor to `,`
+        }
+
+        if self.peek_token.token_type == token::RBRACKET {
+            self.next_token();
+        } else {
+            self.peek_error(token::RBRACKET.to_string());
+            return None;
+        }
+
+        Some(token::ArrayPattern {
+            token,
+            elements,
+            rest,
+        })
+    }
+
+    // Structure: <Variant> | <Variant>(<pattern>, ...)
+    fn parse_variant_pattern(&mut self) -> Option<token::VariantPattern> {
+        let name = token::Identifier {
+            token: self.current_token.clone(),
+            value: self.current_token.literal.clone(),
+        };
+        let mut fields = Vec::new();
+
+        if self.peek_token.token_type != token::LPAREN {
+            return Some(token::VariantPattern { name, fields });
+        }
+        self.next_token(); // set cursor to `(`
+
+        while self.peek_token.token_type != token::RPAREN {
+            self.next_token(); // skip `(` or `,`
+
+            fields.push(self.parse_pattern()?);
+
+            if self.peek_token.token_type != token::COMMA {
+                break;
+            }
+            self.next_token(); // set cursor to `,`
+        }
+
+        if self.peek_token.token_type == token::RPAREN {
+            self.next_token();
+        } else {
+            self.peek_error(token::RPAREN.to_string());
+            return None;
+        }
+
+        Some(token::VariantPattern { name, fields })
+    }
+
+    // Structure: {<key>, <key>: <pattern>, ...<identifier>}
+    fn parse_hash_pattern(&mut self) -> Option<token::HashPattern> {
+        let token = self.current_token.clone();
+        let mut entries = Vec::new();
+        let mut rest = None;
+
+        while self.peek_token.token_type != token::RBRACE {
+            self.next_token(); // skip `{` or `,`
+
+            if self.current_token.token_type == token::ELLIPSIS {
+                rest = Some(self.parse_rest_binding()?);
+                break;
+            }
+
+            if self.current_token.token_type != token::IDENT {
+                let message = format!(
+                    "expected hash pattern key to be {}, got {} instead",
+                    token::IDENT,
+                    self.current_token.token_type
+                );
+                self.errors.push(message);
+                return None;
+            }
+
+            let key = token::Identifier {
+                token: self.current_token.clone(),
+                value: self.current_token.literal.clone(),
+            };
+
+            // `{name}` is a shorthand for `{name: name}`
+            let pattern = if self.peek_token.token_type == token::COLON {
+                self.next_token(); // set cursor to `:`
+                self.next_token(); // skip `:`
+                self.parse_pattern()?
+            } else {
+                token::Pattern::Identifier(key.clone())
+            };
+
+            entries.push((key, pattern));
+
+            if self.peek_token.token_type != token::COMMA {
+                break;
+            }
+            self.next_token(); // set cursor to `,`
+        }
+
+        if self.peek_token.token_type == token::RBRACE {
+            self.next_token();
+        } else {
+            self.peek_error(token::RBRACE.to_string());
+            return None;
+        }
+
+        Some(token::HashPattern {
+            token,
+            entries,
+            rest,
+        })
+    }
+
+    // Structure: ...<identifier>
+    fn parse_rest_binding(&mut self) -> Option<token::Identifier> {
+        if self.peek_token.token_type == token::IDENT {
+            self.next_token();
+        } else {
+            self.peek_error(token::IDENT.to_string());
+            return None;
+        }
+
+        Some(token::Identifier {
+            token: self.current_token.clone(),
+            value: self.current_token.literal.clone(),
+        })
+    }
+
+    // Current token is `(`. Looks for the matching `)` and checks if
+    // it's followed by `=>`. Works on a copy of the lexer, so parser
+    // state stays untouched.
+    fn is_arrow_function_ahead(&self) -> bool {
+        let mut lexer = self.lexer.clone();
+        let mut token = self.peek_token.clone();
+        let mut depth = 1;
+
+        loop {
+            match token.token_type.as_ref() {
+                token::LPAREN | token::PLACEHOLDER_FN => depth += 1,
+                token::RPAREN => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return lexer.next_token().token_type == token::FAT_ARROW;
+                    }
+                }
+                token::EOF => return false,
+                _ => (),
+            }
+            token = lexer.next_token();
+        }
+    }
+
+    // Match without a catch-all arm over variants of a known enum
+    // should mention every variant. Arms with guards or nested
+    // conditions (`Circle(0)`) don't count, they may not match.
+    fn check_match_exhaustiveness(&mut self, arms: &[token::MatchArm]) {
+        let first = arms.iter().find_map(|arm| match &arm.pattern {
+            token::Pattern::VariantPattern(vp) => Some(vp.name.value.clone()),
+            _ => None,
+        });
+
+        let first = match first {
+            Some(first) => first,
+            None => return,
+        };
+
+        let (enum_name, variants) = match self
+            .enums
+            .iter()
+            .find(|(_, variants)| variants.contains(&first))
+        {
+            Some(found) => found,
+            None => return,
+        };
+
+        let covered = arms
+            .iter()
+            .filter_map(|arm| match &arm.pattern {
+                token::Pattern::VariantPattern(vp)
+                    if arm.guard.is_none()
+                        && vp.fields.iter().all(token::Pattern::is_irrefutable) =>
+                {
+                    Some(vp.name.value.clone())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let missing = variants
+            .iter()
+            .filter(|variant| !covered.contains(variant))
+            .map(|variant| format!("`{}`", variant))
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            let message = format!(
+                "non-exhaustive match on {}, missing variants: {}",
+                enum_name,
+                missing.join(", ")
+            );
+            self.warnings.push(message);
+        }
+    }
+
+    fn register_no_prefix_parser_found(&mut self, token_type: token::TokenType) {
+        let message = format!("no prefix parser found for {} token", token_type);
+        self.errors.push(message);
+    }
+
+    fn peek_error(&mut self, token: token::TokenType) {
+        let message = format!(
+            "expected next token to be {expected}, got {got} instead",
+            expected = token,
+            got = self.peek_token.token_type,
+        );
+
+        self.errors.push(message);
+    }
+}
+
+// Variants of enums every program starts with, by enum name.
+static QUIET_HOOK: Once = Once::new();
+
+thread_local! {
+    static PARSING: Cell<bool> = const { Cell::new(false) };
+}
+
+// Whole source of a REPL line, a module or a manifest, `enums` are
+// the ones declared before it. Parser panics on some malformed input,
+// such a panic is reported as one of its errors.
+pub fn parse_source(source: &str, enums: HashMap<String, Vec<String>>) -> (ast::Program, Parser) {
+    let mut parser = Parser::new(lexer::Lexer::new(source.to_string()));
+    parser.enums = enums;
+
+    // The panic is reported by the caller, not printed by the hook.
+    QUIET_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !PARSING.with(Cell::get) {
+                hook(info)
+            }
+        }));
+    });
+    PARSING.with(|parsing| parsing.set(true));
+    let parsed = panic::catch_unwind(panic::AssertUnwindSafe(|| parser.parse_program(&PARSERS)));
+    PARSING.with(|parsing| parsing.set(false));
+    let program = match parsed {
+        Ok(program) => program,
+        Err(payload) => {
+            let message = match payload.downcast::<String>() {
+                Ok(message) => *message,
+                Err(payload) => match payload.downcast::<&str>() {
+                    Ok(message) => message.to_string(),
+                    Err(_) => "cannot parse".to_string(),
+                },
+            };
+            parser.errors.push(message);
+            ast::Program { statements: Vec::new() }
+        }
+    };
+
+    (program, parser)
+}
+
+pub fn builtin_enums() -> HashMap<String, Vec<String>> {
+    core::funcs::BUILTIN_ENUMS
+        .iter()
+        .map(|enum_type| {
+            let variants = enum_type.variants.iter().map(|(tag, _)| tag.clone()).collect();
+            (enum_type.name.clone(), variants)
+        })
+        .collect()
+}
+
+// Enum variants are capitalized, so patterns can tell `Empty`
+// (a variant) from `empty` (a new binding).
+fn is_variant_name(name: &str) -> bool {
+    name.chars().next().is_some_and(char::is_uppercase)
+}
+
+// Decodes source of char literal: a single character or one of
+// `\n`, `\r`, `\t`, `\0`, `\\`, `\'`, `\"` and `\u{XXXX}` escapes.
+fn unescape_char(literal: &str) -> Option<char> {
+    let mut chars = literal.chars();
+
+    let value = match chars.next()? {
+        '\\' => match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            '\\' => '\\',
+            '\'' => '\'',
+            '"' => '"',
+            'u' => {
+                let code = chars.as_str().strip_prefix('{')?.strip_suffix('}')?;
+                return std::char::from_u32(u32::from_str_radix(code, 16).ok()?);
+            }
+            _ => return None,
+        },
+        ch => ch,
+    };
+
+    match chars.next() {
+        None => Some(value),
+        Some(_) => None,
+    }
+}
+
+// Integer literal value: decimal, `0x` hex, `0o` octal or `0b` binary,
+// digits may be separated by single `_`.
+// `negative` is set for the folded `-<literal>`.
+fn parse_integer(literal: &str, negative: bool) -> Result<i32, String> {
+    let (radix, digits) = match literal.get(..2) {
+        Some("0x") | Some("0X") => (16, &literal[2..]),
+        Some("0o") | Some("0O") => (8, &literal[2..]),
+        Some("0b") | Some("0B") => (2, &literal[2..]),
+        _ => (10, literal),
+    };
+
+    if digits.is_empty()
+        || digits.starts_with('_')
+        || digits.ends_with('_')
+        || digits.contains("__")
+        || !digits.chars().all(|c| c == '_' || c.is_digit(radix))
+    {
+        return Err(format!("invalid integer literal `{}`", literal));
+    }
+
+    let out_of_range = || {
+        format!(
+            "integer literal out of range: `{}{}`",
+            if negative { "-" } else { "" },
+            literal
+        )
+    };
+
+    // Every digit is valid here, so the only possible error is overflow.
+    let digits = digits.replace('_', "");
+    let magnitude = u64::from_str_radix(&digits, radix).map_err(|_| out_of_range())?;
+    let value = if negative {
+        -i128::from(magnitude)
+    } else {
+        i128::from(magnitude)
+    };
+
+    i32::try_from(value).map_err(|_| out_of_range())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::Node;
+    use crate::lexer;
+    use crate::parser::LambdaParsers;
+    use crate::parser::PARSERS;
+    use crate::parser::Parser;
+    use crate::token::Expression;
+    use crate::token::Pattern;
+    use crate::token::Statements;
+
+    #[test]
+    fn test_let_statements() {
+        let input = r###"
+          let a = 1;
+          let b = 2;
+          let bebe = 101010;
+        "###
+        .to_string();
+
+        let lexer = lexer::Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let program = parser.parse_program(lambda_parsers);
+
+        // We would like to accumulate every error in program
+        // and later render them to user.
+        if !parser.errors.is_empty() {
+            println!("Parser encountered {} errors", parser.errors.len());
+            for error in parser.errors {
+                println!("parser error: {}", error);
+            }
+            panic!("A few parsing error encountered, see them above.");
+        }
+
+        assert_eq!(program.statements.len(), 3);
+
+        let expected = vec![
+            ("a".to_string(), 1),
+            ("b".to_string(), 2),
+            ("bebe".to_string(), 101010),
+        ];
+
+        program
+            .statements
+            .into_iter()
+            .zip(expected)
+            .for_each(|(statement, (expected_identifier, integer))| {
+                assert_eq!(statement.token_literal(), "let");
+
+                let let_statement = match statement {
+                    Statements::LetStatement(statement) => statement,
+                    _ => panic!("I didn't expected anything besides `let` statement"),
+                };
+
+                let name = match &let_statement.pattern {
+                    Pattern::Identifier(identifier) => identifier,
+                    _ => panic!("I didn't expected anything besides identifier pattern"),
+                };
+
+                assert_eq!(name.value, expected_identifier);
+                assert_eq!(name.token_literal(), expected_identifier);
+                assert_integer_literal(&let_statement.value, integer);
+            });
+    }
+
+    #[test]
+    fn test_destructuring_patterns() {
+        let inputs = [
+            "let [a, b] = c;".to_string(),
+            "let [a, ...rest] = c;".to_string(),
+            "let [...rest] = c;".to_string(),
+            "let {name, age: years} = c;".to_string(),
+            "let {name, ...others} = c;".to_string(),
+            "let [first, {name, tags: [tag]}] = c;".to_string(),
+            "fn([a, b], {c}) { a }".to_string(),
+        ];
+
+        let expected = [
+            "let [a, b] = c;\n".to_string(),
+            "let [a, ...rest] = c;\n".to_string(),
+            "let [...rest] = c;\n".to_string(),
+            "let {name, age: years} = c;\n".to_string(),
+            "let {name, ...others} = c;\n".to_string(),
+            "let [first, {name, tags: [tag]}] = c;\n".to_string(),
+            "fn([a, b],{c}){a}\n".to_string(),
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                if !parser.errors.is_empty() {
+                    println!("Parser encountered {} errors", parser.errors.len());
+                    for error in parser.errors {
+                        println!("parser error: {}", error);
+                    }
+                    panic!("A few parsing error encountered, see them above.");
+                }
+
+                assert_eq!(program.to_string(), *expected);
+            });
+    }
+
+    #[test]
+    fn test_hash_literal() {
+        let inputs = [
+            "{}".to_string(),
+            r###"{"one": 1, "two": 1 + 1}"###.to_string(),
+            r###"{1: [1], true: {"a": b}}"###.to_string(),
+        ];
+
+        let expected = [
+            "{}\n".to_string(),
+            "{one: 1, two: (1 + 1)}\n".to_string(),
+            "{1: [1], true: {a: b}}\n".to_string(),
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                if !parser.errors.is_empty() {
+                    println!("Parser encountered {} errors", parser.errors.len());
+                    for error in parser.errors {
+                        println!("parser error: {}", error);
+                    }
+                    panic!("A few parsing error encountered, see them above.");
+                }
+
+                assert_eq!(program.to_string(), *expected);
+            });
+    }
+
+    #[test]
+    fn test_match_expression() {
+        let inputs = [
+            "match (x) { 1 => a, -1 => b, \"s\" => c, true => d, _ => e }".to_string(),
+            "match (x) { [a, ...rest] if a > 1 => { a }, {name} => name }".to_string(),
+        ];
+
+        let expected = [
+            "match x {1 => a, -1 => b, s => c, true => d, _ => e}\n".to_string(),
+            "match x {[a, ...rest] if (a > 1) => a, {name} => name}\n".to_string(),
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                if !parser.errors.is_empty() {
+                    println!("Parser encountered {} errors", parser.errors.len());
+                    for error in parser.errors {
+                        println!("parser error: {}", error);
+                    }
+                    panic!("A few parsing error encountered, see them above.");
+                }
+
+                assert!(parser.warnings.is_empty());
+                assert_eq!(program.to_string(), *expected);
+            });
+    }
+
+    #[test]
+    fn test_unreachable_match_arm_warning() {
+        let input = "match (x) { y if y > 1 => 1, y => 2, [a] => 3, _ => 4 }".to_string();
+
+        let lexer = lexer::Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        parser.parse_program(lambda_parsers);
+
+        assert!(parser.errors.is_empty());
+        assert_eq!(
+            parser.warnings,
+            vec![
+                "unreachable match arm `[a]`, pattern `y` above matches every value".to_string(),
+                "unreachable match arm `_`, pattern `y` above matches every value".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_function_parameters_and_named_arguments() {
+        let inputs = [
+            "fn(x, y = 10) { x }".to_string(),
+            "fn(first, ...others) { first }".to_string(),
+            "fn([a, b] = [1, 2], {c} = d) { a }".to_string(),
+            "f(1, y: 2, z: 1 + 2)".to_string(),
+            "f(y: 2)".to_string(),
+        ];
+
+        let expected = [
+            "fn(x,y = 10){x}\n".to_string(),
+            "fn(first,...others){first}\n".to_string(),
+            "fn([a, b] = [1, 2],{c} = d){a}\n".to_string(),
+            "f(1, y: 2, z: (1 + 2))\n".to_string(),
+            "f(y: 2)\n".to_string(),
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                if !parser.errors.is_empty() {
+                    println!("Parser encountered {} errors", parser.errors.len());
+                    for error in parser.errors {
+                        println!("parser error: {}", error);
+                    }
+                    panic!("A few parsing error encountered, see them above.");
+                }
+
+                assert_eq!(program.to_string(), *expected);
+            });
+
+        let inputs = [
+            (
+                "fn(...a, b) { b }".to_string(),
+                "rest parameter must be the last one",
+            ),
+            (
+                "fn(x = 1, y) { x + y }".to_string(),
+                "parameter `y` without default follows one with default",
+            ),
+            (
+                "f(x: 1, 2)".to_string(),
+                "positional argument `2` follows named argument",
+            ),
+        ];
+
+        for (input, expected) in inputs.iter() {
+            let lexer = lexer::Lexer::new(input.to_string());
+            let mut parser = Parser::new(lexer);
+
+            let lambda_parsers: &LambdaParsers = &PARSERS;
+
+            parser.parse_program(lambda_parsers);
+
+            assert_eq!(parser.errors, vec![expected.to_string()]);
+        }
+    }
+
+    #[test]
+    fn test_short_function_syntax() {
+        let inputs = [
+            "x => x + 1".to_string(),
+            "(a, b) => a + b".to_string(),
+            "() => 1".to_string(),
+            "([a, b], c = 1) => { a }".to_string(),
+            "map(arr, x => x * 2)".to_string(),
+            "(x) => (y) => x + y".to_string(),
+            "#(_ * 2)".to_string(),
+            "#(_ + _)".to_string(),
+            "#(f(_, #(_ + 1)))".to_string(),
+            "(1 + 2) * 3".to_string(),
+            "match (x) { y if (y) => 1 }".to_string(),
+        ];
+
+        let expected = [
+            "fn(x){(x + 1)}\n".to_string(),
+            "fn(a,b){(a + b)}\n".to_string(),
+            "fn(){1}\n".to_string(),
+            "fn([a, b],c = 1){a}\n".to_string(),
+            "map(arr, fn(x){(x * 2)})\n".to_string(),
+            "fn(x){fn(y){(x + y)}}\n".to_string(),
+            "fn(_1){(_1 * 2)}\n".to_string(),
+            "fn(_1,_2){(_1 + _2)}\n".to_string(),
+            "fn(_1){f(_1, fn(_1){(_1 + 1)})}\n".to_string(),
+            "((1 + 2) * 3)\n".to_string(),
+            "match x {y if y => 1}\n".to_string(),
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                if !parser.errors.is_empty() {
+                    println!("Parser encountered {} errors", parser.errors.len());
+                    for error in parser.errors {
+                        println!("parser error: {}", error);
+                    }
+                    panic!("A few parsing error encountered, see them above.");
+                }
+
+                assert_eq!(program.to_string(), *expected);
+            });
+    }
+
+    #[test]
+    fn test_struct_statement() {
+        let inputs = [
+            "struct Point { x, y }",
+            "struct Unit {}",
+            "struct Pair { first, second, };",
+            "p.x",
+            "a.b.c + p[0].x",
+            "p.x()",
+            "p with { x: 3 }",
+            "p with { x: p.x + 1, y: 2 } == q",
+            "-p with { x: 3 }.x",
+        ];
+
+        let expected = [
+            "struct Point {x, y}\n",
+            "struct Unit {}\n",
+            "struct Pair {first, second}\n",
+            "(p.x)\n",
+            "(((a.b).c) + ((p[0]).x))\n",
+            "(p.x)()\n",
+            "(p with {x: 3})\n",
+            "((p with {x: ((p.x) + 1), y: 2}) == q)\n",
+            "(- ((p with {x: 3}).x))\n",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(program.to_string(), expected.to_string());
+            });
+
+        let inputs = ["struct { x }", "struct Point { x, 1 }", "struct Point { x, x }"];
+        let expected = [
+            "expected next token to be IDENT, got { instead",
+            "expected next token to be IDENT, got INT instead",
+            "duplicate field `x` in struct Point",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                parser.parse_statement(lambda_parsers);
+
+                assert_eq!(parser.errors, vec![expected.to_string()]);
+            });
+
+        // The whole declaration is read, parsing goes on after it.
+        let lexer = lexer::Lexer::new("struct P { x, x, y }; let a = 1;".to_string());
+        let mut parser = Parser::new(lexer);
+
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let program = parser.parse_program(lambda_parsers);
+
+        assert_eq!(parser.errors, vec!["duplicate field `x` in struct P".to_string()]);
+        assert_eq!(program.to_string(), "let a = 1;\n");
+    }
+
+    #[test]
+    fn test_enum_statement() {
+        let inputs = [
+            "enum Shape { Circle(r), Rect(w, h), Empty }",
+            "enum Answer { Yes, No, };",
+            "match (s) { Circle(r) => r, Rect(w, _) => w, Empty => 0 }",
+            "let Circle(r) = c",
+        ];
+
+        let expected = [
+            "enum Shape {Circle(r), Rect(w, h), Empty}\n",
+            "enum Answer {Yes, No}\n",
+            "match s {Circle(r) => r, Rect(w, _) => w, Empty => 0}\n",
+            "let Circle(r) = c;\n",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(program.to_string(), expected.to_string());
+            });
+
+        let inputs = [
+            "enum Shape { circle }",
+            "enum Shape { Circle, Circle }",
+            "enum Shape { Circle(1) }",
+        ];
+        let expected = [
+            "variant `circle` of enum Shape must start with a capital letter",
+            "duplicate variant `Circle` in enum Shape",
+            "expected next token to be IDENT, got INT instead",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                parser.parse_statement(lambda_parsers);
+
+                assert_eq!(parser.errors, vec![expected.to_string()]);
+            });
+
+        let lexer = lexer::Lexer::new("enum E { A, A, b }; let a = 1;".to_string());
+        let mut parser = Parser::new(lexer);
+
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        let program = parser.parse_program(lambda_parsers);
+
+        assert_eq!(
+            parser.errors,
+            vec![
+                "duplicate variant `A` in enum E".to_string(),
+                "variant `b` of enum E must start with a capital letter".to_string(),
+            ]
+        );
+        assert_eq!(program.to_string(), "let a = 1;\n");
+    }
+
+    #[test]
+    fn test_impl_statement() {
+        let inputs = [
+            "impl Point { let norm = fn(self) { self.x + self.y }; let zero = 0 }",
+            "impl Unit {}",
+            "impl Add for Point { let add = fn(a, b) { a } }",
+            "p.norm().abs()",
+            "\"abc\".upper().chars()",
+        ];
+
+        let expected = [
+            "impl Point {let norm = fn(self){((self.x) + (self.y))}; let zero = 0;}\n",
+            "impl Unit {}\n",
+            "impl Add for Point {let add = fn(a,b){a};}\n",
+            "((p.norm)().abs)()\n",
+            "((abc.upper)().chars)()\n",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(program.to_string(), expected.to_string());
+            });
+
+        let inputs = [
+            "impl Point { norm }",
+            "impl Add for { let add = fn(a, b) { a } }",
+            "impl Point { let [a] = 1 }",
+        ];
+        let expected = [
+            "expected next token to be LET, got IDENT instead",
+            "expected next token to be IDENT, got { instead",
+            "expected method name in impl Point, got pattern [a]",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                parser.parse_statement(lambda_parsers);
+
+                assert_eq!(parser.errors, vec![expected.to_string()]);
+            });
+    }
+
+    #[test]
+    fn test_try_expression() {
+        let inputs = [
+            "throw \"boom\"",
+            "throw x + 1",
+            "try { f(x) } catch (e) { e.message }",
+            "try { f(x) } finally { close(x) }",
+            "try { f(x) } catch ({message}) { message } finally { close(x) }",
+            "let x = try { throw 1 } catch (_) { 0 };",
+            "let x = f(y)?.z + g()?;",
+        ];
+
+        let expected = [
+            "throw boom\n",
+            "throw (x + 1)\n",
+            "try {f(x)} catch (e) {(e.message)}\n",
+            "try {f(x)} finally {close(x)}\n",
+            "try {f(x)} catch ({message}) {message} finally {close(x)}\n",
+            "let x = try {throw 1} catch (_) {0};\n",
+            "let x = (((f(y)?).z) + (g()?));\n",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(program.to_string(), expected.to_string());
+            });
+
+        let lexer = lexer::Lexer::new("1;\n  try { f(x) }".to_string());
+        let mut parser = Parser::new(lexer);
+
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        parser.parse_program(lambda_parsers);
+
+        assert_eq!(
+            parser.errors,
+            vec!["expected `catch` or `finally` after try block at 2:3".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_import_export_statements() {
+        let inputs = [
+            "import \"lib/math\" as m; m.square(2)",
+            "export let square = fn(x) { x * x };",
+            "export let [a, b] = [1, 2]",
+            "export struct Point { x, y }",
+            "export enum Shape { Circle(r), Empty }",
+        ];
+
+        let expected = [
+            "import \"lib/math\" as m;\n(m.square)(2)\n",
+            "export let square = fn(x){(x * x)};\n",
+            "export let [a, b] = [1, 2];\n",
+            "export struct Point {x, y}\n",
+            "export enum Shape {Circle(r), Empty}\n",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(program.to_string(), expected.to_string());
+            });
+
+        let inputs = ["import \"lib\" as 1;", "import \"lib\" m;", "export return 1"];
+        let expected = [
+            "expected next token to be IDENT, got INT instead",
+            "expected next token to be AS, got IDENT instead",
+            "only `let`, `struct` and `enum` can be exported, got `return` at 1:8",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                parser.parse_program(lambda_parsers);
+
+                assert_eq!(parser.errors.first(), Some(&expected.to_string()));
+            });
+    }
+
+    #[test]
+    fn test_generators_and_for() {
+        let inputs = [
+            "fn*() { yield 1; yield 2 }",
+            "fn(x) { if (x) { yield x } }",
+            "(x) => { yield x * 2 }",
+            "fn() { fn() { yield 1 }; 2 }",
+            "for ([k, v] in pairs) { k + v }",
+            "for (c in fn*() { yield 'a' }()) { c }",
+        ];
+
+        let expected = [
+            "fn*(){yield 1yield 2}\n",
+            "fn*(x){if x yield x}\n",
+            "fn*(x){yield (x * 2)}\n",
+            "fn(){fn*(){yield 1}2}\n",
+            "for ([k, v] in pairs) (k + v)\n",
+            "for (c in fn*(){yield 'a'}()) c\n",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(program.to_string(), expected.to_string());
+            });
+
+        let lexer = lexer::Lexer::new("let x = 1;\nyield x;".to_string());
+        let mut parser = Parser::new(lexer);
+
+        let lambda_parsers: &LambdaParsers = &PARSERS;
+
+        parser.parse_program(lambda_parsers);
+
+        assert_eq!(
+            parser.errors.first(),
+            Some(&"`yield` outside of a function at 2:1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_macro_literal() {
+        let inputs = [
+            "let unless = macro(cond, body) { quote(if (!unquote(cond)) { unquote(body) }) };",
+            "macro() { quote(1) }",
+        ];
+
+        let expected = [
+            "let unless = macro(cond,body){quote(if (! unquote(cond)) unquote(body))};\n",
+            "macro(){quote(1)}\n",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(program.to_string(), expected.to_string());
+            });
+
+        let inputs = ["macro(a = 1) { a }", "macro([a], ...b) { a }"];
+        let expected = [
+            vec!["macro parameter must be a name, got `a = 1`".to_string()],
+            vec![
+                "macro parameter must be a name, got `[a]`".to_string(),
+                "macro parameter must be a name, got `...b`".to_string(),
+            ],
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
 
-    fn register_no_prefix_parser_found(&mut self, token_type: token::TokenType) {
-        let message = format!("no prefix parser found for {} token", token_type);
-        self.errors.push(message);
-    }
+                let lambda_parsers: &LambdaParsers = &PARSERS;
 
-    fn peek_error(&mut self, token: token::TokenType) {
-        let message = format!(
-            "expected next token to be {expected}, got {got} instead",
-            expected = token,
-            got = self.peek_token.token_type,
-        );
+                parser.parse_program(lambda_parsers);
 
-        self.errors.push(message);
+                assert_eq!(parser.errors, *expected);
+            });
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::ast::Node;
-    use crate::lexer;
-    use crate::parser::LambdaParsers;
-    use crate::parser::Parser;
-    use crate::token::Expression;
-    use crate::token::Statements;
-    use std::collections::HashMap;
 
     #[test]
-    fn test_let_statements() {
-        let input = r###"
-          let a = 1;
-          let b = 2;
-          let bebe = 101010;
-        "###
-        .to_string();
+    fn test_non_exhaustive_match_warning() {
+        let declaration = "enum Shape { Circle(r), Rect(w, h), Empty };";
 
-        let lexer = lexer::Lexer::new(input);
-        let mut parser = Parser::new(lexer);
+        let inputs = [
+            "match (s) { Circle(r) => r, Rect(w, h) => w, Empty => 0 }",
+            "match (s) { Circle(r) => r, _ => 0 }",
+            "match (s) { Circle(r) => r }",
+            "match (s) { Circle(0) => 0, Circle(r) if (r > 1) => r, Empty => 0 }",
+            "match (s) { Unknown(x) => x }",
+            "match (s) { 1 => 1 }",
+            "match (r) { Ok(x) => x, Err(e) => 0 }",
+            "match (o) { Some(x) => x }",
+        ];
 
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
+        let expected = [
+            vec![],
+            vec![],
+            vec!["non-exhaustive match on Shape, missing variants: `Rect`, `Empty`".to_string()],
+            vec!["non-exhaustive match on Shape, missing variants: `Circle`, `Rect`".to_string()],
+            vec![],
+            vec![],
+            vec![],
+            vec!["non-exhaustive match on Option, missing variants: `None`".to_string()],
+        ];
 
-        lambda_parsers.register_parsers();
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(format!("{} {}", declaration, input));
+                let mut parser = Parser::new(lexer);
 
-        let program = parser.parse_program(&lambda_parsers);
+                let lambda_parsers: &LambdaParsers = &PARSERS;
 
-        // We would like to accumulate every error in program
-        // and later render them to user.
-        if !parser.errors.is_empty() {
-            println!("Parser encountered {} errors", parser.errors.len());
-            for error in parser.errors {
-                println!("parser error: {}", error);
-            }
-            panic!("A few parsing error encountered, see them above.");
-        }
+                parser.parse_program(lambda_parsers);
 
-        assert_eq!(program.statements.len(), 3);
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(parser.warnings, *expected);
+            });
+    }
 
-        let expected = vec![
-            ("a".to_string(), 1),
-            ("b".to_string(), 2),
-            ("bebe".to_string(), 101010),
+    #[test]
+    fn test_broken_patterns() {
+        let inputs = [
+            ("let ;".to_string(), "expected pattern, got ; instead"),
+            (
+                "let [a, b c".to_string(),
+                "expected next token to be ], got IDENT instead",
+            ),
+            (
+                "let {1".to_string(),
+                "expected hash pattern key to be IDENT, got INT instead",
+            ),
+            (
+                "let [...1".to_string(),
+                "expected next token to be IDENT, got INT instead",
+            ),
         ];
 
-        program
-            .statements
-            .into_iter()
-            .zip(expected.into_iter())
-            .for_each(|(statement, (expected_identifier, integer))| {
-                assert_eq!(statement.token_literal(), "let");
+        for (input, expected) in inputs.iter() {
+            let lexer = lexer::Lexer::new(input.to_string());
+            let mut parser = Parser::new(lexer);
 
-                let let_statement = match statement {
-                    Statements::LetStatement(statement) => statement,
-                    _ => panic!("I didn't expected anything besides `let` statement"),
-                };
+            let lambda_parsers: &LambdaParsers = &PARSERS;
 
-                assert_eq!(let_statement.name.value, expected_identifier);
-                assert_eq!(let_statement.name.token_literal(), expected_identifier);
-                assert_integer_literal(&let_statement.value, integer);
-            });
+            parser.parse_program(lambda_parsers);
+
+            assert_eq!(parser.errors.first().map(String::as_str), Some(*expected));
+        }
     }
 
     #[test]
@@ -967,14 +3456,9 @@ mod tests {
         let lexer = lexer::Lexer::new(input);
         let mut parser = Parser::new(lexer);
 
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-        parser.parse_program(&lambda_parsers);
+        parser.parse_program(lambda_parsers);
 
         // We would like to accumulate every error in program
         // and later render them to user.
@@ -999,14 +3483,9 @@ mod tests {
         let lexer = lexer::Lexer::new(input);
         let mut parser = Parser::new(lexer);
 
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-        let program = parser.parse_program(&lambda_parsers);
+        let program = parser.parse_program(lambda_parsers);
 
         // We would like to accumulate every error in program
         // and later render them to user.
@@ -1025,7 +3504,7 @@ mod tests {
         program
             .statements
             .into_iter()
-            .zip(expected.into_iter())
+            .zip(expected)
             .for_each(|(statement, expected_identifier)| {
                 assert_eq!(statement.token_literal(), "return");
 
@@ -1048,14 +3527,9 @@ mod tests {
         let lexer = lexer::Lexer::new(input);
         let mut parser = Parser::new(lexer);
 
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-        let program = parser.parse_program(&lambda_parsers);
+        let program = parser.parse_program(lambda_parsers);
 
         // We would like to accumulate every error in program
         // and later render them to user.
@@ -1097,14 +3571,9 @@ mod tests {
         let lexer = lexer::Lexer::new(input);
         let mut parser = Parser::new(lexer);
 
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-        let program = parser.parse_program(&lambda_parsers);
+        let program = parser.parse_program(lambda_parsers);
 
         // We would like to accumulate every error in program
         // and later render them to user.
@@ -1146,14 +3615,9 @@ mod tests {
         let lexer = lexer::Lexer::new(input);
         let mut parser = Parser::new(lexer);
 
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-        let program = parser.parse_program(&lambda_parsers);
+        let program = parser.parse_program(lambda_parsers);
 
         // We would like to accumulate every error in program
         // and later render them to user.
@@ -1185,6 +3649,206 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_integer_literal_syntax() {
+        let inputs = [
+            "0xFF",
+            "0o755",
+            "0b1010",
+            "1_000_000",
+            "2147483647",
+            "-2147483648",
+            "-1",
+        ];
+
+        let expected = [255, 493, 10, 1_000_000, i32::MAX, i32::MIN, 1];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(program.to_string(), format!("{}\n", input.replace("-1", "(- 1)")));
+
+                let integer_literal = match &program.statements[0] {
+                    Statements::ExpressionStatement(statement) => match &statement.expression {
+                        Expression::IntegerLiteral(il) => il.clone(),
+                        Expression::PrefixExpression(pe) => match &pe.right {
+                            Expression::IntegerLiteral(il) => il.clone(),
+                            _ => panic!("expected integer literal, got {}", pe.right),
+                        },
+                        expression => panic!("expected integer literal, got {}", expression),
+                    },
+                    _ => panic!("I didn't expect something besides expression statement"),
+                };
+
+                assert_eq!(integer_literal.value, *expected);
+            });
+    }
+
+    #[test]
+    fn test_char_literal() {
+        let inputs = [r"'a'", r"'\n'", r"'\''", r"'\\'", "'ж'", r"'\u{1F600}'"];
+        let expected = ['a', '\n', '\'', '\\', 'ж', '😀'];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+                assert_eq!(program.to_string(), format!("{}\n", input));
+
+                match &program.statements[0] {
+                    Statements::ExpressionStatement(statement) => match &statement.expression {
+                        Expression::CharLiteral(cl) => assert_eq!(cl.value, *expected),
+                        expression => panic!("expected char literal, got {}", expression),
+                    },
+                    _ => panic!("I didn't expect something besides expression statement"),
+                };
+            });
+
+        let inputs = ["'ab'", r"'\q'", "''", r"'\u{D800}'"];
+        let expected = [
+            "invalid character literal `'ab'` at 1:1",
+            r"invalid character literal `'\q'` at 1:1",
+            "invalid character literal `''` at 1:1",
+            r"invalid character literal `'\u{D800}'` at 1:1",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                parser.parse_program(lambda_parsers);
+
+                assert_eq!(parser.errors, vec![expected.to_string()]);
+            });
+    }
+
+    #[test]
+    fn test_broken_integer_literals() {
+        let inputs = [
+            "let x = 2147483648;",
+            "let x = 0xG;",
+            "1_;",
+            "\n  0b_1",
+            "\n\n  -99999999999",
+            "match (x) { -2147483649 => 1 }",
+            "-0xG",
+        ];
+
+        let expected = [
+            "integer literal out of range: `2147483648` at 1:9",
+            "invalid integer literal `0xG` at 1:9",
+            "invalid integer literal `1_` at 1:1",
+            "invalid integer literal `0b_1` at 2:3",
+            "integer literal out of range: `-99999999999` at 3:3",
+            "integer literal out of range: `-2147483649` at 1:13",
+            "invalid integer literal `0xG` at 1:2",
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                parser.parse_program(lambda_parsers);
+
+                assert_eq!(parser.errors, vec![expected.to_string()]);
+            });
+    }
+
+    #[test]
+    fn test_interpolated_string() {
+        let inputs = [
+            r#""Hello, ${name}!""#.to_string(),
+            r#""${a + 1}${b}""#.to_string(),
+            r#""n=${ {"k": "}"}["k"] }""#.to_string(),
+            r#""outer ${"inner ${x}"}""#.to_string(),
+            r#""costs $5 {not interpolated}""#.to_string(),
+        ];
+
+        let expected = [
+            "Hello, ${name}!\n".to_string(),
+            "${(a + 1)}${b}\n".to_string(),
+            "n=${({k: }}[k])}\n".to_string(),
+            "outer ${inner ${x}}\n".to_string(),
+            "costs $5 {not interpolated}\n".to_string(),
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                let program = parser.parse_program(lambda_parsers);
+
+                if !parser.errors.is_empty() {
+                    println!("Parser encountered {} errors", parser.errors.len());
+                    for error in parser.errors {
+                        println!("parser error: {}", error);
+                    }
+                    panic!("A few parsing error encountered, see them above.");
+                }
+
+                assert_eq!(program.to_string(), *expected);
+            });
+    }
+
+    #[test]
+    fn test_broken_interpolated_string() {
+        let inputs = [
+            r#""${}""#.to_string(),
+            r#""${a b}""#.to_string(),
+        ];
+
+        let expected = [
+            vec!["empty string interpolation `${}`".to_string()],
+            vec!["in string interpolation `${a b}`: unexpected `b` after expression".to_string()],
+        ];
+
+        inputs
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(input, expected)| {
+                let lexer = lexer::Lexer::new(input.to_string());
+                let mut parser = Parser::new(lexer);
+
+                let lambda_parsers: &LambdaParsers = &PARSERS;
+
+                parser.parse_program(lambda_parsers);
+
+                assert_eq!(parser.errors, *expected);
+            });
+    }
+
     #[test]
     fn test_array_literal() {
         let input = r###"
@@ -1195,14 +3859,9 @@ mod tests {
         let lexer = lexer::Lexer::new(input);
         let mut parser = Parser::new(lexer);
 
-        let mut lambda_parsers = LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-
-        lambda_parsers.register_parsers();
+        let lambda_parsers: &LambdaParsers = &PARSERS;
 
-        let program = parser.parse_program(&lambda_parsers);
+        let program = parser.parse_program(lambda_parsers);
 
         // We would like to accumulate every error in program
         // and later render them to user.
@@ -1248,20 +3907,15 @@ mod tests {
 
         // Iterate over every prefix expression and test it individualy
         inputs
-            .into_iter()
-            .zip(expected.into_iter())
+            .iter()
+            .zip(expected)
             .for_each(|(input, token_pair)| {
                 let lexer = lexer::Lexer::new(input.to_string());
                 let mut parser = Parser::new(lexer);
 
-                let mut lambda_parsers = LambdaParsers {
-                    prefix_parse_fns: HashMap::new(),
-                    infix_parse_fns: HashMap::new(),
-                };
-
-                lambda_parsers.register_parsers();
+                let lambda_parsers: &LambdaParsers = &PARSERS;
 
-                let program = parser.parse_program(&lambda_parsers);
+                let program = parser.parse_program(lambda_parsers);
 
                 // We would like to accumulate every error in program
                 // and later render them to user.
@@ -1304,6 +3958,17 @@ mod tests {
             "1 < 1;".to_string(),
             "1 == 1;".to_string(),
             "1 != 1;".to_string(),
+            "1 && 1;".to_string(),
+            "1 || 1;".to_string(),
+            "1 <= 1;".to_string(),
+            "1 >= 1;".to_string(),
+            "1 % 1;".to_string(),
+            "1 ** 1;".to_string(),
+            "1 & 1;".to_string(),
+            "1 | 1;".to_string(),
+            "1 ^ 1;".to_string(),
+            "1 << 1;".to_string(),
+            "1 >> 1;".to_string(),
         ];
 
         let expected = vec![
@@ -1315,22 +3980,28 @@ mod tests {
             (1, "<".to_string(), 1),
             (1, "==".to_string(), 1),
             (1, "!=".to_string(), 1),
+            (1, "&&".to_string(), 1),
+            (1, "||".to_string(), 1),
+            (1, "<=".to_string(), 1),
+            (1, ">=".to_string(), 1),
+            (1, "%".to_string(), 1),
+            (1, "**".to_string(), 1),
+            (1, "&".to_string(), 1),
+            (1, "|".to_string(), 1),
+            (1, "^".to_string(), 1),
+            (1, "<<".to_string(), 1),
+            (1, ">>".to_string(), 1),
         ];
 
         // Iterate over every prefix expression and test it individualy
-        inputs.into_iter().zip(expected.into_iter()).for_each(
+        inputs.iter().zip(expected).for_each(
             |(input, (left_integer, operator, right_integer))| {
                 let lexer = lexer::Lexer::new(input.to_string());
                 let mut parser = Parser::new(lexer);
 
-                let mut lambda_parsers = LambdaParsers {
-                    prefix_parse_fns: HashMap::new(),
-                    infix_parse_fns: HashMap::new(),
-                };
-
-                lambda_parsers.register_parsers();
+                let lambda_parsers: &LambdaParsers = &PARSERS;
 
-                let program = parser.parse_program(&lambda_parsers);
+                let program = parser.parse_program(lambda_parsers);
 
                 // We would like to accumulate every error in program
                 // and later render them to user.
@@ -1369,20 +4040,15 @@ mod tests {
 
         // Iterate over every prefix expression and test it individualy
         inputs
-            .into_iter()
-            .zip(expected.into_iter())
+            .iter()
+            .zip(expected)
             .for_each(|(input, boolean_value)| {
                 let lexer = lexer::Lexer::new(input.to_string());
                 let mut parser = Parser::new(lexer);
 
-                let mut lambda_parsers = LambdaParsers {
-                    prefix_parse_fns: HashMap::new(),
-                    infix_parse_fns: HashMap::new(),
-                };
-
-                lambda_parsers.register_parsers();
+                let lambda_parsers: &LambdaParsers = &PARSERS;
 
-                let program = parser.parse_program(&lambda_parsers);
+                let program = parser.parse_program(lambda_parsers);
 
                 // We would like to accumulate every error in program
                 // and later render them to user.
@@ -1416,30 +4082,43 @@ mod tests {
             "(1 + 2) * 3 + 4;".to_string(),
             "!true == false;".to_string(),
             "a * [1, 2, 3, 4][b * c] * d".to_string(),
+            "a || b && c == d".to_string(),
+            "a && b || !c".to_string(),
+            "2 ** 3 ** 2".to_string(),
+            "-2 ** 2".to_string(),
+            "a % b * c".to_string(),
+            "a | b ^ c & d << e + f".to_string(),
+            "a & b == c <= d".to_string(),
+            "a |> f(b) |> g".to_string(),
+            "a + 1 |> f(b || c)".to_string(),
         ];
 
         let expected = [
             "(((1 + 2) * 3) + 4)\n".to_string(),
             "((! true) == false)\n".to_string(),
             "((a * ([1, 2, 3, 4][(b * c)])) * d)\n".to_string(),
+            "(a || (b && (c == d)))\n".to_string(),
+            "((a && b) || (! c))\n".to_string(),
+            "(2 ** (3 ** 2))\n".to_string(),
+            "(- (2 ** 2))\n".to_string(),
+            "((a % b) * c)\n".to_string(),
+            "(a | (b ^ (c & (d << (e + f)))))\n".to_string(),
+            "((a & b) == (c <= d))\n".to_string(),
+            "((a |> f(b)) |> g)\n".to_string(),
+            "((a + 1) |> f((b || c)))\n".to_string(),
         ];
 
         // Iterate over every prefix expression and test it individualy
         inputs
-            .into_iter()
-            .zip(expected.into_iter())
+            .iter()
+            .zip(expected.iter())
             .for_each(|(input, expected)| {
                 let lexer = lexer::Lexer::new(input.to_string());
                 let mut parser = Parser::new(lexer);
 
-                let mut lambda_parsers = LambdaParsers {
-                    prefix_parse_fns: HashMap::new(),
-                    infix_parse_fns: HashMap::new(),
-                };
-
-                lambda_parsers.register_parsers();
+                let lambda_parsers: &LambdaParsers = &PARSERS;
 
-                let program = parser.parse_program(&lambda_parsers);
+                let program = parser.parse_program(lambda_parsers);
 
                 // We would like to accumulate every error in program
                 // and later render them to user.
@@ -1462,18 +4141,13 @@ mod tests {
         let inputs = ["if (pirozhenka < bulochka) { bulochka }".to_string()];
 
         // Iterate over every prefix expression and test it individualy
-        inputs.into_iter().for_each(|input| {
+        inputs.iter().for_each(|input| {
             let lexer = lexer::Lexer::new(input.to_string());
             let mut parser = Parser::new(lexer);
 
-            let mut lambda_parsers = LambdaParsers {
-                prefix_parse_fns: HashMap::new(),
-                infix_parse_fns: HashMap::new(),
-            };
-
-            lambda_parsers.register_parsers();
+            let lambda_parsers: &LambdaParsers = &PARSERS;
 
-            let program = parser.parse_program(&lambda_parsers);
+            let program = parser.parse_program(lambda_parsers);
 
             // We would like to accumulate every error in program
             // and later render them to user.
@@ -1525,18 +4199,13 @@ mod tests {
         let inputs = ["if (pirozhenka < bulochka) { bulochka } else { pirozhenka }".to_string()];
 
         // Iterate over every prefix expression and test it individualy
-        inputs.into_iter().for_each(|input| {
+        inputs.iter().for_each(|input| {
             let lexer = lexer::Lexer::new(input.to_string());
             let mut parser = Parser::new(lexer);
 
-            let mut lambda_parsers = LambdaParsers {
-                prefix_parse_fns: HashMap::new(),
-                infix_parse_fns: HashMap::new(),
-            };
-
-            lambda_parsers.register_parsers();
+            let lambda_parsers: &LambdaParsers = &PARSERS;
 
-            let program = parser.parse_program(&lambda_parsers);
+            let program = parser.parse_program(lambda_parsers);
 
             // We would like to accumulate every error in program
             // and later render them to user.
@@ -1598,18 +4267,13 @@ mod tests {
         let inputs = ["arr[1 + 1]".to_string()];
 
         // Iterate over every prefix expression and test it individualy
-        inputs.into_iter().for_each(|input| {
+        inputs.iter().for_each(|input| {
             let lexer = lexer::Lexer::new(input.to_string());
             let mut parser = Parser::new(lexer);
 
-            let mut lambda_parsers = LambdaParsers {
-                prefix_parse_fns: HashMap::new(),
-                infix_parse_fns: HashMap::new(),
-            };
-
-            lambda_parsers.register_parsers();
+            let lambda_parsers: &LambdaParsers = &PARSERS;
 
-            let program = parser.parse_program(&lambda_parsers);
+            let program = parser.parse_program(lambda_parsers);
 
             // We would like to accumulate every error in program
             // and later render them to user.
@@ -1651,18 +4315,13 @@ mod tests {
         let inputs = ["fn() { pirozhenka; }".to_string()];
 
         // Iterate over every prefix expression and test it individualy
-        inputs.into_iter().for_each(|input| {
+        inputs.iter().for_each(|input| {
             let lexer = lexer::Lexer::new(input.to_string());
             let mut parser = Parser::new(lexer);
 
-            let mut lambda_parsers = LambdaParsers {
-                prefix_parse_fns: HashMap::new(),
-                infix_parse_fns: HashMap::new(),
-            };
-
-            lambda_parsers.register_parsers();
+            let lambda_parsers: &LambdaParsers = &PARSERS;
 
-            let program = parser.parse_program(&lambda_parsers);
+            let program = parser.parse_program(lambda_parsers);
 
             // We would like to accumulate every error in program
             // and later render them to user.
@@ -1709,18 +4368,13 @@ mod tests {
         let inputs = ["fn(pirozhenka) { pirozhenka; }".to_string()];
 
         // Iterate over every prefix expression and test it individualy
-        inputs.into_iter().for_each(|input| {
+        inputs.iter().for_each(|input| {
             let lexer = lexer::Lexer::new(input.to_string());
             let mut parser = Parser::new(lexer);
 
-            let mut lambda_parsers = LambdaParsers {
-                prefix_parse_fns: HashMap::new(),
-                infix_parse_fns: HashMap::new(),
-            };
-
-            lambda_parsers.register_parsers();
+            let lambda_parsers: &LambdaParsers = &PARSERS;
 
-            let program = parser.parse_program(&lambda_parsers);
+            let program = parser.parse_program(lambda_parsers);
 
             // We would like to accumulate every error in program
             // and later render them to user.
@@ -1749,7 +4403,7 @@ mod tests {
             assert_eq!(fn_literal.parameters.clone().unwrap().len(), 1);
 
             assert_eq!(
-                &fn_literal.parameters.clone().unwrap()[0].value,
+                &fn_literal.parameters.clone().unwrap()[0].to_string(),
                 "pirozhenka"
             );
 
@@ -1772,18 +4426,13 @@ mod tests {
         let inputs = ["fn(pirozhenka, bulochka) { pirozhenka + bulochka; }".to_string()];
 
         // Iterate over every prefix expression and test it individualy
-        inputs.into_iter().for_each(|input| {
+        inputs.iter().for_each(|input| {
             let lexer = lexer::Lexer::new(input.to_string());
             let mut parser = Parser::new(lexer);
 
-            let mut lambda_parsers = LambdaParsers {
-                prefix_parse_fns: HashMap::new(),
-                infix_parse_fns: HashMap::new(),
-            };
-
-            lambda_parsers.register_parsers();
+            let lambda_parsers: &LambdaParsers = &PARSERS;
 
-            let program = parser.parse_program(&lambda_parsers);
+            let program = parser.parse_program(lambda_parsers);
 
             // We would like to accumulate every error in program
             // and later render them to user.
@@ -1812,10 +4461,10 @@ mod tests {
             assert_eq!(fn_literal.parameters.clone().unwrap().len(), 2);
 
             assert_eq!(
-                &fn_literal.parameters.clone().unwrap()[0].value,
+                &fn_literal.parameters.clone().unwrap()[0].to_string(),
                 "pirozhenka"
             );
-            assert_eq!(&fn_literal.parameters.clone().unwrap()[1].value, "bulochka");
+            assert_eq!(&fn_literal.parameters.clone().unwrap()[1].to_string(), "bulochka");
 
             assert_eq!(fn_literal.body.statements.len(), 1);
 
@@ -1841,18 +4490,13 @@ mod tests {
         let inputs = ["sdelay_pirozhenku(muka, sahar, slivki + ricotta)".to_string()];
 
         // Iterate over every prefix expression and test it individualy
-        inputs.into_iter().for_each(|input| {
+        inputs.iter().for_each(|input| {
             let lexer = lexer::Lexer::new(input.to_string());
             let mut parser = Parser::new(lexer);
 
-            let mut lambda_parsers = LambdaParsers {
-                prefix_parse_fns: HashMap::new(),
-                infix_parse_fns: HashMap::new(),
-            };
-
-            lambda_parsers.register_parsers();
+            let lambda_parsers: &LambdaParsers = &PARSERS;
 
-            let program = parser.parse_program(&lambda_parsers);
+            let program = parser.parse_program(lambda_parsers);
 
             // We would like to accumulate every error in program
             // and later render them to user.
@@ -1922,27 +4566,15 @@ mod tests {
         assert_eq!(identifier.value, expected)
     }
 
-    fn assert_boolean_literal(expression: &Expression, expected: bool) {
-        let boolean_expression = match expression {
-            Expression::Boolean(boolean) => boolean,
-            _ => panic!("Expected boolean, got {:?}", expression),
-        };
-
-        assert_eq!(boolean_expression.value, expected);
-        assert_eq!(boolean_expression.token_literal(), expected.to_string())
-    }
-
     enum ExpectedAssertLiteral {
         S(String),
         I(i32),
-        B(bool),
     }
 
     fn assert_literal_expression(expression: &Expression, expected: ExpectedAssertLiteral) {
         match expected {
             ExpectedAssertLiteral::S(v) => assert_identifier(expression, v),
             ExpectedAssertLiteral::I(v) => assert_integer_literal(expression, v),
-            ExpectedAssertLiteral::B(v) => assert_boolean_literal(expression, v),
         };
     }
 
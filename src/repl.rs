@@ -1,15 +1,17 @@
-use crate::lexer;
 use crate::parser;
 use crate::evaluation::evaluator;
 use crate::evaluation::environment;
+use crate::evaluation::macro_expansion;
 use crate::evaluation::object::ObjectT;
 use std::io::{stdin, stdout, Write};
-use std::collections::HashMap;
 
 const PROMPT: &str = "clojurium $ ";
 
 pub fn start() {
     let mut env = environment::Environment::new();
+    let mut macro_env = environment::Environment::new();
+    // Enums declared on previous lines, parser needs them to check `match`.
+    let mut enums = parser::builtin_enums();
 
     loop {
         let mut user_input = String::new();
@@ -34,25 +36,17 @@ pub fn start() {
             _ => 1, // just ignore this for now
         };
 
-        let lexer = lexer::Lexer::new(user_input);
-        let mut parser = parser::Parser::new(lexer);
+        let (mut program, mut parser) = parser::parse_source(&user_input, enums);
 
-
-        // TODO: Very ugly interface to parser.
-        // LambdaParsers is a hack itself, so worth
-        // to change it.
-        let mut lambda_parsers = parser::LambdaParsers {
-            prefix_parse_fns: HashMap::new(),
-            infix_parse_fns: HashMap::new(),
-        };
-
-        lambda_parsers.register_parsers();
-
-        let program = parser.parse_program(&lambda_parsers);
+        enums = std::mem::take(&mut parser.enums);
 
         // We would like to accumulate every error in program
         // and later render them to user.
 
+        for warning in parser.warnings.iter() {
+            println!("parser warning: {}", warning);
+        }
+
         // This function used extensively in parser tests
         if !parser.errors.is_empty() {
             println!("Parser encountered {} errors", parser.errors.len());
@@ -61,8 +55,20 @@ pub fn start() {
                 println!("parser error: {}", error);
             }
         } else {
+            macro_expansion::define_macros(&mut program, &mut macro_env);
+            let program = match macro_expansion::expand_macros(program, &macro_env) {
+                Ok(program) => program,
+                Err(err) => {
+                    println!("macro error: {}", err.inspect());
+                    continue;
+                }
+            };
+
             let evaluated = evaluator::eval(evaluator::WN::P(program), &mut env);
-            println!("{}", evaluated.inspect())
+            match evaluator::display(&evaluated, &env) {
+                Ok(shown) => println!("{}", shown),
+                Err(err) => println!("{}", err.inspect()),
+            }
         }
     }
 }
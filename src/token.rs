@@ -19,6 +19,22 @@ pub fn lookup_ident(ident: String) -> TokenType {
         ("if".to_string(), IF.to_string()),
         ("else".to_string(), ELSE.to_string()),
         ("return".to_string(), RETURN.to_string()),
+        ("match".to_string(), MATCH.to_string()),
+        ("struct".to_string(), STRUCT.to_string()),
+        ("enum".to_string(), ENUM.to_string()),
+        ("impl".to_string(), IMPL.to_string()),
+        ("for".to_string(), FOR.to_string()),
+        ("throw".to_string(), THROW.to_string()),
+        ("try".to_string(), TRY.to_string()),
+        ("catch".to_string(), CATCH.to_string()),
+        ("finally".to_string(), FINALLY.to_string()),
+        ("macro".to_string(), MACRO.to_string()),
+        ("import".to_string(), IMPORT.to_string()),
+        ("export".to_string(), EXPORT.to_string()),
+        ("as".to_string(), AS.to_string()),
+        ("yield".to_string(), YIELD.to_string()),
+        ("in".to_string(), IN.to_string()),
+        ("with".to_string(), WITH.to_string()),
     ]
     .iter()
     .cloned()
@@ -37,24 +53,47 @@ pub const EOF: &str = "EOF";
 pub const IDENT: &str = "IDENT";
 pub const INT: &str = "INT";
 pub const STRING: &str = "STRING";
+pub const CHAR: &str = "CHAR";
+pub const INTERPOLATED_STRING: &str = "INTERPOLATED_STRING";
 
 // Operators
 pub const ASSIGN: &str = "=";
+pub const FAT_ARROW: &str = "=>";
 pub const PLUS: &str = "+";
 pub const MINUS: &str = "-";
 pub const BANG: &str = "!";
 pub const ASTERISK: &str = "*";
 pub const SLASH: &str = "/";
+pub const PERCENT: &str = "%";
+pub const POWER: &str = "**";
 
 pub const LT: &str = "<";
 pub const GT: &str = ">";
+pub const LT_EQ: &str = "<=";
+pub const GT_EQ: &str = ">=";
 
 pub const EQ: &str = "==";
 pub const NOT_EQ: &str = "!=";
 
+pub const AND: &str = "&&";
+pub const OR: &str = "||";
+
+pub const PIPE: &str = "|>";
+
+pub const BIT_AND: &str = "&";
+pub const BIT_OR: &str = "|";
+pub const BIT_XOR: &str = "^";
+pub const SHIFT_LEFT: &str = "<<";
+pub const SHIFT_RIGHT: &str = ">>";
+
 // Delimiters
 pub const COMMA: &str = ",";
 pub const SEMICOLON: &str = ";";
+pub const COLON: &str = ":";
+pub const ELLIPSIS: &str = "...";
+pub const DOT: &str = ".";
+pub const PLACEHOLDER_FN: &str = "#(";
+pub const QUESTION: &str = "?";
 
 pub const LPAREN: &str = "(";
 pub const RPAREN: &str = ")";
@@ -71,16 +110,41 @@ pub const FALSE: &str = "FALSE";
 pub const IF: &str = "IF";
 pub const ELSE: &str = "ELSE";
 pub const RETURN: &str = "RETURN";
+pub const MATCH: &str = "MATCH";
+pub const STRUCT: &str = "STRUCT";
+pub const ENUM: &str = "ENUM";
+pub const IMPL: &str = "IMPL";
+pub const FOR: &str = "FOR";
+pub const THROW: &str = "THROW";
+pub const TRY: &str = "TRY";
+pub const CATCH: &str = "CATCH";
+pub const FINALLY: &str = "FINALLY";
+pub const MACRO: &str = "MACRO";
+pub const IMPORT: &str = "IMPORT";
+pub const EXPORT: &str = "EXPORT";
+pub const AS: &str = "AS";
+pub const YIELD: &str = "YIELD";
+pub const IN: &str = "IN";
+pub const WITH: &str = "WITH";
 
 // Precedences
 pub const LOWEST: u8 = 1;
-pub const EQUALS: u8 = 2; // ==
-pub const LESSGREATER: u8 = 3; // > or <
-pub const SUM: u8 = 4; // +
-pub const PRODUCT: u8 = 5; // *
-pub const PREFIX: u8 = 6; // -B or !B
-pub const CALL: u8 = 7; // do_something()
-pub const INDEX: u8 = 8; // array index `[]`
+pub const PIPELINE: u8 = 2; // |>
+pub const LOGICAL_OR: u8 = 3; // ||
+pub const LOGICAL_AND: u8 = 4; // &&
+pub const EQUALS: u8 = 5; // ==
+pub const LESSGREATER: u8 = 6; // > or <
+pub const BITWISE_OR: u8 = 7; // |
+pub const BITWISE_XOR: u8 = 8; // ^
+pub const BITWISE_AND: u8 = 9; // &
+pub const SHIFT: u8 = 10; // << or >>
+pub const SUM: u8 = 11; // +
+pub const PRODUCT: u8 = 12; // *
+pub const PREFIX: u8 = 13; // -B or !B
+pub const EXPONENT: u8 = 14; // ** (binds tighter than prefix: -2 ** 2 == -4)
+pub const UPDATE: u8 = 15; // point with { x: 1 }
+pub const CALL: u8 = 16; // do_something() or propagation `result?`
+pub const INDEX: u8 = 17; // array index `[]` or field access `.`
 
 // <<--**********************-->>
 // Statements used by AST
@@ -94,10 +158,16 @@ pub const INDEX: u8 = 8; // array index `[]`
 // <<--**********************-->>
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Statements {
     LetStatement(LetStatement),
     ReturnStatement(ReturnStatement),
     ExpressionStatement(ExpressionStatement),
+    StructStatement(StructStatement),
+    EnumStatement(EnumStatement),
+    ImplStatement(ImplStatement),
+    ImportStatement(ImportStatement),
+    ExportStatement(ExportStatement),
 }
 
 impl ast::Node for Statements {
@@ -106,6 +176,25 @@ impl ast::Node for Statements {
             Statements::LetStatement(ls) => ls.token_literal(),
             Statements::ReturnStatement(rs) => rs.token_literal(),
             Statements::ExpressionStatement(es) => es.token_literal(),
+            Statements::StructStatement(ss) => ss.token_literal(),
+            Statements::EnumStatement(es) => es.token_literal(),
+            Statements::ImplStatement(is) => is.token_literal(),
+            Statements::ImportStatement(is) => is.token_literal(),
+            Statements::ExportStatement(es) => es.token_literal(),
+        }
+    }
+}
+
+impl Statements {
+    // Where the statement starts, errors raised by it get this location.
+    pub fn location(&self) -> Option<&str> {
+        match self {
+            Statements::LetStatement(ls) => Some(&ls.location),
+            Statements::ReturnStatement(rs) => Some(&rs.location),
+            Statements::ExpressionStatement(es) => Some(&es.location),
+            Statements::ImportStatement(is) => Some(&is.location),
+            Statements::ExportStatement(es) => es.statement.location(),
+            _ => None,
         }
     }
 }
@@ -116,6 +205,11 @@ impl fmt::Display for Statements {
             Statements::LetStatement(ls) => fmt::Display::fmt(ls, f),
             Statements::ReturnStatement(rs) => fmt::Display::fmt(rs, f),
             Statements::ExpressionStatement(es) => fmt::Display::fmt(es, f),
+            Statements::StructStatement(ss) => fmt::Display::fmt(ss, f),
+            Statements::EnumStatement(es) => fmt::Display::fmt(es, f),
+            Statements::ImplStatement(is) => fmt::Display::fmt(is, f),
+            Statements::ImportStatement(is) => fmt::Display::fmt(is, f),
+            Statements::ExportStatement(es) => fmt::Display::fmt(es, f),
         }
     }
 }
@@ -125,6 +219,7 @@ impl fmt::Display for Statements {
 // <<--**********************-->>
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Expression {
     Identifier(Identifier),
     IntegerLiteral(IntegerLiteral),
@@ -135,8 +230,21 @@ pub enum Expression {
     Boolean(Boolean),
     IfExpression(Box<IfExpression>), // recur
     FunctionLiteral(FunctionLiteral),
+    MacroLiteral(MacroLiteral),
     CallExpression(Box<CallExpression>), // recur
     IndexExpression(Box<IndexExpression>),
+    PipeExpression(Box<PipeExpression>),
+    HashLiteral(HashLiteral),
+    MatchExpression(Box<MatchExpression>),
+    InterpolatedString(InterpolatedString),
+    CharLiteral(CharLiteral),
+    FieldAccess(Box<FieldAccess>),
+    WithExpression(Box<WithExpression>),
+    ThrowExpression(Box<ThrowExpression>),
+    TryExpression(Box<TryExpression>),
+    PropagateExpression(Box<PropagateExpression>),
+    YieldExpression(Box<YieldExpression>),
+    ForExpression(Box<ForExpression>),
 }
 
 impl ast::Node for Expression {
@@ -151,8 +259,21 @@ impl ast::Node for Expression {
             Expression::Boolean(b) => b.token_literal(),
             Expression::IfExpression(ie) => ie.token_literal(),
             Expression::FunctionLiteral(f) => f.token_literal(),
+            Expression::MacroLiteral(m) => m.token_literal(),
             Expression::CallExpression(ce) => ce.token_literal(),
             Expression::IndexExpression(ie) => ie.token_literal(),
+            Expression::PipeExpression(pe) => pe.token_literal(),
+            Expression::HashLiteral(hl) => hl.token_literal(),
+            Expression::MatchExpression(me) => me.token_literal(),
+            Expression::InterpolatedString(is) => is.token_literal(),
+            Expression::CharLiteral(cl) => cl.token_literal(),
+            Expression::FieldAccess(fa) => fa.token_literal(),
+            Expression::WithExpression(we) => we.token_literal(),
+            Expression::ThrowExpression(te) => te.token_literal(),
+            Expression::TryExpression(te) => te.token_literal(),
+            Expression::PropagateExpression(pe) => pe.token_literal(),
+            Expression::YieldExpression(ye) => ye.token_literal(),
+            Expression::ForExpression(fe) => fe.token_literal(),
         }
     }
 }
@@ -169,8 +290,21 @@ impl fmt::Display for Expression {
             Expression::Boolean(b) => fmt::Display::fmt(b, f),
             Expression::IfExpression(ie) => fmt::Display::fmt(ie, f),
             Expression::FunctionLiteral(func) => fmt::Display::fmt(func, f),
+            Expression::MacroLiteral(m) => fmt::Display::fmt(m, f),
             Expression::CallExpression(ce) => fmt::Display::fmt(ce, f),
             Expression::IndexExpression(ie) => fmt::Display::fmt(ie, f),
+            Expression::PipeExpression(pe) => fmt::Display::fmt(pe, f),
+            Expression::HashLiteral(hl) => fmt::Display::fmt(hl, f),
+            Expression::MatchExpression(me) => fmt::Display::fmt(me, f),
+            Expression::InterpolatedString(is) => fmt::Display::fmt(is, f),
+            Expression::CharLiteral(cl) => fmt::Display::fmt(cl, f),
+            Expression::FieldAccess(fa) => fmt::Display::fmt(fa, f),
+            Expression::WithExpression(we) => fmt::Display::fmt(we, f),
+            Expression::ThrowExpression(te) => fmt::Display::fmt(te, f),
+            Expression::TryExpression(te) => fmt::Display::fmt(te, f),
+            Expression::PropagateExpression(pe) => fmt::Display::fmt(pe, f),
+            Expression::YieldExpression(ye) => fmt::Display::fmt(ye, f),
+            Expression::ForExpression(fe) => fmt::Display::fmt(fe, f),
         }
     }
 }
@@ -180,8 +314,9 @@ impl fmt::Display for Expression {
 #[derive(Debug, Clone)]
 pub struct LetStatement {
     pub token: Token,
-    pub name: Identifier,
+    pub pattern: Pattern,
     pub value: Expression,
+    pub location: String,
 }
 
 impl ast::Node for LetStatement {
@@ -197,12 +332,206 @@ impl fmt::Display for LetStatement {
             f,
             "{} {} = {};",
             self.token_literal(),
-            self.name.value,
+            self.pattern,
             self.value
         )
     }
 }
 
+// Struct statement.
+// Declares a record type and binds its constructor to the name.
+//
+// Example: struct Point { x, y }
+//
+// Structure: struct <identifier> { <identifier>, ... }
+#[derive(Debug, Clone)]
+pub struct StructStatement {
+    pub token: Token,
+    pub name: Identifier,
+    pub fields: Vec<Identifier>,
+}
+
+impl ast::Node for StructStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for StructStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = self
+            .fields
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "struct {} {{{}}}", self.name, fields)
+    }
+}
+
+// Enum statement.
+// Declares a tagged union. Variants with fields become constructor
+// functions, variants without fields are plain values.
+//
+// Example: enum Shape { Circle(r), Rect(w, h), Empty }
+//
+// Structure: enum <identifier> { <identifier>[(<identifier>, ...)], ... }
+#[derive(Debug, Clone)]
+pub struct EnumStatement {
+    pub token: Token,
+    pub name: Identifier,
+    pub variants: Vec<EnumVariant>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+    pub name: Identifier,
+    pub fields: Vec<Identifier>,
+}
+
+impl ast::Node for EnumStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for EnumVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.fields.is_empty() {
+            return write!(f, "{}", self.name);
+        }
+
+        let fields = self
+            .fields
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "{}({})", self.name, fields)
+    }
+}
+
+impl fmt::Display for EnumStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let variants = self
+            .variants
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "enum {} {{{}}}", self.name, variants)
+    }
+}
+
+// Impl statement.
+// Attaches methods to a struct or enum. Every method is a function
+// which gets the receiver as its first argument.
+//
+// With `for` it implements a protocol instead, so the type can take
+// part in built-in operations: impl Add for Point { let add = ... }
+//
+// Example: impl Point { let norm = fn(self) { self.x * self.x + self.y * self.y }; }
+//
+// Structure: impl [<protocol> for] <identifier> { <let statement> ... }
+#[derive(Debug, Clone)]
+pub struct ImplStatement {
+    pub token: Token,
+    pub protocol: Option<Identifier>,
+    pub name: Identifier,
+    pub methods: Vec<LetStatement>,
+}
+
+impl ast::Node for ImplStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for ImplStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let methods = self
+            .methods
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match &self.protocol {
+            Some(protocol) => write!(f, "impl {} for {} {{{}}}", protocol, self.name, methods),
+            None => write!(f, "impl {} {{{}}}", self.name, methods),
+        }
+    }
+}
+
+// Import statement.
+// Loads another file as a module and binds it to the name,
+// exported members are reached with field access: `m.f`.
+//
+// Example: import "lib/math" as m;
+//
+// Structure: import <string> as <identifier>;
+#[derive(Debug, Clone)]
+pub struct ImportStatement {
+    pub token: Token,
+    pub path: String,
+    pub name: Identifier,
+    pub location: String,
+}
+
+impl ast::Node for ImportStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for ImportStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "import \"{}\" as {};", self.path, self.name)
+    }
+}
+
+// Export statement.
+// Makes names bound by a top-level `let`, `struct` or `enum`
+// visible to the files importing this one.
+//
+// Example: export let square = fn(x) { x * x };
+//
+// Structure: export <let statement | struct statement | enum statement>
+#[derive(Debug, Clone)]
+pub struct ExportStatement {
+    pub token: Token,
+    pub statement: Box<Statements>,
+}
+
+impl ExportStatement {
+    // Names the exported statement binds, enum variants included.
+    pub fn names(&self) -> Vec<String> {
+        match self.statement.as_ref() {
+            Statements::LetStatement(ls) => ls.pattern.names(),
+            Statements::StructStatement(ss) => vec![ss.name.value.clone()],
+            Statements::EnumStatement(es) => std::iter::once(es.name.value.clone())
+                .chain(es.variants.iter().map(|variant| variant.name.value.clone()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl ast::Node for ExportStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for ExportStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "export {}", self.statement)
+    }
+}
+
 // Include Identifier in Expression group?
 #[derive(Debug, Clone)]
 pub struct Identifier {
@@ -222,6 +551,167 @@ impl fmt::Display for Identifier {
     }
 }
 
+// Binding patterns.
+//
+// Patterns are used on the left side of `let`, in function
+// parameters and in `match` arms to take values apart. A plain
+// identifier is the simplest pattern, arrays and hashes can be
+// nested in each other. Literals only match an equal value and
+// `_` matches anything without binding it. Capitalized names are
+// enum variants (or structs) and never bind: `Circle(r)`, `Empty`.
+//
+// Example: let [first, {name, age: years}, ...others] = people;
+//
+// Structure: <identifier> | _ | <literal> | [<pattern>, ..., ...<identifier>] | {<key>: <pattern>, ..., ...<identifier>} | <Variant>[(<pattern>, ...)]
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Identifier(Identifier),
+    Wildcard(Token),
+    Literal(Expression),
+    ArrayPattern(ArrayPattern),
+    HashPattern(HashPattern),
+    VariantPattern(VariantPattern),
+}
+
+impl Pattern {
+    // Irrefutable patterns match every value.
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Identifier(_) | Pattern::Wildcard(_))
+    }
+
+    // Names the pattern binds, in order of appearance.
+    pub fn names(&self) -> Vec<String> {
+        match self {
+            Pattern::Identifier(i) => vec![i.value.clone()],
+            Pattern::Wildcard(_) | Pattern::Literal(_) => Vec::new(),
+            Pattern::ArrayPattern(ap) => ap
+                .elements
+                .iter()
+                .flat_map(Pattern::names)
+                .chain(ap.rest.iter().map(|rest| rest.value.clone()))
+                .collect(),
+            Pattern::HashPattern(hp) => hp
+                .entries
+                .iter()
+                .flat_map(|(_, pattern)| pattern.names())
+                .chain(hp.rest.iter().map(|rest| rest.value.clone()))
+                .collect(),
+            Pattern::VariantPattern(vp) => vp.fields.iter().flat_map(Pattern::names).collect(),
+        }
+    }
+}
+
+impl ast::Node for Pattern {
+    fn token_literal(&self) -> String {
+        match self {
+            Pattern::Identifier(i) => i.token_literal(),
+            Pattern::Wildcard(t) => t.literal.to_string(),
+            Pattern::Literal(l) => l.token_literal(),
+            Pattern::ArrayPattern(ap) => ap.token.literal.to_string(),
+            Pattern::HashPattern(hp) => hp.token.literal.to_string(),
+            Pattern::VariantPattern(vp) => vp.name.token_literal(),
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pattern::Identifier(i) => fmt::Display::fmt(i, f),
+            Pattern::Wildcard(t) => write!(f, "{}", t.literal),
+            Pattern::Literal(l) => fmt::Display::fmt(l, f),
+            Pattern::ArrayPattern(ap) => fmt::Display::fmt(ap, f),
+            Pattern::HashPattern(hp) => fmt::Display::fmt(hp, f),
+            Pattern::VariantPattern(vp) => fmt::Display::fmt(vp, f),
+        }
+    }
+}
+
+// Array pattern.
+//
+// Matches an array with exactly as many elements as there are
+// patterns, or at least as many if `...rest` is present.
+#[derive(Debug, Clone)]
+pub struct ArrayPattern {
+    pub token: Token,
+    pub elements: Vec<Pattern>,
+    pub rest: Option<Identifier>,
+}
+
+impl fmt::Display for ArrayPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut elems = self
+            .elements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        if let Some(rest) = &self.rest {
+            elems.push(format!("...{}", rest));
+        }
+
+        write!(f, "[{}]", elems.join(", "))
+    }
+}
+
+// Hash pattern.
+//
+// Every key is looked up in the hash as a string, `{name}` is
+// a shorthand for `{name: name}`. Keys missing from the hash are
+// an error, keys missing from the pattern go to `...rest`.
+#[derive(Debug, Clone)]
+pub struct HashPattern {
+    pub token: Token,
+    pub entries: Vec<(Identifier, Pattern)>,
+    pub rest: Option<Identifier>,
+}
+
+impl fmt::Display for HashPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries = self
+            .entries
+            .iter()
+            .map(|(key, pattern)| match pattern {
+                Pattern::Identifier(i) if i.value == key.value => key.to_string(),
+                _ => format!("{}: {}", key, pattern),
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(rest) = &self.rest {
+            entries.push(format!("...{}", rest));
+        }
+
+        write!(f, "{{{}}}", entries.join(", "))
+    }
+}
+
+// Variant pattern.
+//
+// Matches an enum variant (or a struct) with the same name,
+// fields are matched by position.
+#[derive(Debug, Clone)]
+pub struct VariantPattern {
+    pub name: Identifier,
+    pub fields: Vec<Pattern>,
+}
+
+impl fmt::Display for VariantPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.fields.is_empty() {
+            return write!(f, "{}", self.name);
+        }
+
+        let fields = self
+            .fields
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "{}({})", self.name, fields)
+    }
+}
+
 // String literal.
 #[derive(Debug, Clone)]
 pub struct StringLiteral {
@@ -241,6 +731,71 @@ impl fmt::Display for StringLiteral {
     }
 }
 
+// Character literal.
+//
+// Token keeps the source form between quotes, e.g. `\n`.
+//
+// Structure: `'a'`
+#[derive(Debug, Clone)]
+pub struct CharLiteral {
+    pub token: Token,
+    pub value: char,
+}
+
+impl ast::Node for CharLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for CharLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}'", self.token.literal)
+    }
+}
+
+// Piece of string literal as the lexer sees it:
+// plain text or source code of `${...}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Text(String),
+    Code(String),
+}
+
+// String with interpolated expressions.
+//
+// Structure: `"text ${<expression>} text"`
+#[derive(Debug, Clone)]
+pub struct InterpolatedString {
+    pub token: Token,
+    pub parts: Vec<InterpolationPart>,
+}
+
+#[derive(Debug, Clone)]
+pub enum InterpolationPart {
+    Text(String),
+    Expression(Expression),
+}
+
+impl ast::Node for InterpolatedString {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for InterpolatedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for part in &self.parts {
+            match part {
+                InterpolationPart::Text(text) => write!(f, "{}", text)?,
+                InterpolationPart::Expression(expression) => write!(f, "${{{}}}", expression)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
 // Return statement grammar.
 //
 // Structure: `return <expression>`;
@@ -248,6 +803,7 @@ impl fmt::Display for StringLiteral {
 pub struct ReturnStatement {
     pub token: Token,
     pub return_value: Expression,
+    pub location: String,
 }
 
 impl ast::Node for ReturnStatement {
@@ -277,6 +833,7 @@ impl fmt::Display for ReturnStatement {
 pub struct ExpressionStatement {
     pub token: Token,
     pub expression: Expression, // move to expression later
+    pub location: String,
 }
 
 impl ast::Node for ExpressionStatement {
@@ -450,8 +1007,10 @@ impl fmt::Display for BlockStatement {
 #[derive(Debug, Clone)]
 pub struct FunctionLiteral {
     pub token: Token,
-    pub parameters: Option<Vec<Identifier>>,
+    pub parameters: Option<Vec<Parameter>>,
     pub body: BlockStatement,
+    // `fn*`, or a function with `yield` in its body.
+    pub generator: bool,
 }
 
 impl ast::Node for FunctionLiteral {
@@ -472,10 +1031,70 @@ impl fmt::Display for FunctionLiteral {
             None => "".to_string(),
         };
 
+        let star = if self.generator { "*" } else { "" };
+
+        write!(f, "{}{}({}){{{}}}", self.token_literal(), star, params, self.body)
+    }
+}
+
+// Macro literal.
+// Macro gets its arguments unevaluated, as quotes, and returns
+// a quote, which replaces the macro call before evaluation.
+//
+// Example: let unless = macro(cond, body) { quote(if (!unquote(cond)) { unquote(body) }) };
+//
+// Structure: macro(<identifier>, ...) <block statement>
+#[derive(Debug, Clone)]
+pub struct MacroLiteral {
+    pub token: Token,
+    pub parameters: Vec<Identifier>,
+    pub body: BlockStatement,
+}
+
+impl ast::Node for MacroLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for MacroLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ast::Node;
+        let params = self
+            .parameters
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
         write!(f, "{}({}){{{}}}", self.token_literal(), params, self.body)
     }
 }
 
+// Function parameter.
+//
+// A pattern with an optional default value, used when the argument
+// is not passed. Variadic (rest) parameter collects all remaining
+// positional arguments into an array, it has to be the last one.
+//
+// Example: fn(x, y = 10, ...others) {}
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub pattern: Pattern,
+    pub default: Option<Expression>,
+    pub variadic: bool,
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.default {
+            _ if self.variadic => write!(f, "...{}", self.pattern),
+            Some(default) => write!(f, "{} = {}", self.pattern, default),
+            None => write!(f, "{}", self.pattern),
+        }
+    }
+}
+
 // Call expression.
 //
 // Human readable description:
@@ -495,6 +1114,7 @@ pub struct CallExpression {
     pub token: Token,
     pub function: Expression,
     pub arguments: Option<Vec<Expression>>,
+    pub named_arguments: Vec<(Identifier, Expression)>,
 }
 
 impl ast::Node for CallExpression {
@@ -505,16 +1125,16 @@ impl ast::Node for CallExpression {
 
 impl fmt::Display for CallExpression {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let args = match self.arguments.clone() {
-            Some(args) => args
-                .iter()
-                .map(ToString::to_string)
-                .collect::<Vec<_>>()
-                .join(", "),
-            None => "".to_string(),
+        let mut args = match self.arguments.clone() {
+            Some(args) => args.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            None => Vec::new(),
         };
 
-        write!(f, "{}({})", self.function, args)
+        for (name, value) in self.named_arguments.iter() {
+            args.push(format!("{}: {}", name, value));
+        }
+
+        write!(f, "{}({})", self.function, args.join(", "))
     }
 }
 
@@ -548,6 +1168,36 @@ impl fmt::Display for ArrayLiteral {
     }
 }
 
+// Hash literal.
+//
+// Example: {"name": "Julian", 1: [1, 2]}
+//
+// Structure: {<expression>: <expression>, ...}
+#[derive(Debug, Clone)]
+pub struct HashLiteral {
+    pub token: Token,
+    pub pairs: Vec<(Expression, Expression)>,
+}
+
+impl ast::Node for HashLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for HashLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs = self
+            .pairs
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "{{{}}}", pairs)
+    }
+}
+
 // Index expression.
 //
 // Example: [1,2,3,4][1];
@@ -571,3 +1221,279 @@ impl fmt::Display for IndexExpression {
         write!(f, "({}[{}])", self.left, self.index)
     }
 }
+
+// Field access.
+//
+// Example: point.x
+//
+// Structure: <expression>.<identifier>
+#[derive(Debug, Clone)]
+pub struct FieldAccess {
+    pub token: Token,
+    pub left: Expression,
+    pub field: Identifier,
+}
+
+impl ast::Node for FieldAccess {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for FieldAccess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}.{})", self.left, self.field)
+    }
+}
+
+// Functional update of a struct.
+// Builds a copy of the struct on the left with the listed fields replaced.
+//
+// Example: point with { x: 3 }
+//
+// Structure: <expression> with { <identifier>: <expression>, ... }
+#[derive(Debug, Clone)]
+pub struct WithExpression {
+    pub token: Token,
+    pub left: Expression,
+    pub fields: Vec<(Identifier, Expression)>,
+}
+
+impl ast::Node for WithExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for WithExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "({} with {{{}}})", self.left, fields)
+    }
+}
+
+// Yield expression.
+// Gives the next value of a generator and pauses it until
+// the value after it is asked for.
+//
+// Example: fn*(n) { yield n; yield n + 1 }
+//
+// Structure: yield <expression>
+#[derive(Debug, Clone)]
+pub struct YieldExpression {
+    pub token: Token,
+    pub value: Expression,
+    pub location: String,
+}
+
+impl ast::Node for YieldExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for YieldExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "yield {}", self.value)
+    }
+}
+
+// For expression.
+// Runs the body for every element of an array, character of a string
+// or value of a generator. Evaluates to null.
+//
+// Example: for ([key, value] in pairs) { check(key, value) }
+//
+// Structure: for (<pattern> in <expression>) <block>
+#[derive(Debug, Clone)]
+pub struct ForExpression {
+    pub token: Token,
+    pub pattern: Pattern,
+    pub iterable: Expression,
+    pub body: BlockStatement,
+}
+
+impl ast::Node for ForExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for ForExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "for ({} in {}) {}", self.pattern, self.iterable, self.body)
+    }
+}
+
+// Throw expression.
+// Raises an error, which goes up until some `try` catches it.
+// Strings become the message, other values are shown.
+//
+// Example: throw "not found"
+//
+// Structure: throw <expression>
+#[derive(Debug, Clone)]
+pub struct ThrowExpression {
+    pub token: Token,
+    pub value: Expression,
+    pub location: String,
+}
+
+impl ast::Node for ThrowExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for ThrowExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "throw {}", self.value)
+    }
+}
+
+// Try expression.
+// Evaluates to the body, or to the catch block if the body failed.
+// Finally block always runs, its value is dropped.
+//
+// Example: try { risky() } catch (e) { e.message } finally { cleanup() }
+//
+// Structure: try <block> [catch (<pattern>) <block>] [finally <block>]
+#[derive(Debug, Clone)]
+pub struct TryExpression {
+    pub token: Token,
+    pub body: BlockStatement,
+    pub catch: Option<(Pattern, BlockStatement)>,
+    pub finally: Option<BlockStatement>,
+}
+
+impl ast::Node for TryExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for TryExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "try {{{}}}", self.body)?;
+
+        if let Some((pattern, block)) = &self.catch {
+            write!(f, " catch ({}) {{{}}}", pattern, block)?;
+        }
+
+        if let Some(block) = &self.finally {
+            write!(f, " finally {{{}}}", block)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Propagate expression.
+// Unwraps `Ok` or `Some`, on `Err` or `None` returns it
+// from the enclosing function right away.
+//
+// Example: let config = read_config(path)?;
+//
+// Structure: <expression>?
+#[derive(Debug, Clone)]
+pub struct PropagateExpression {
+    pub token: Token,
+    pub value: Expression,
+}
+
+impl ast::Node for PropagateExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for PropagateExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}?)", self.value)
+    }
+}
+
+// Pipe expression.
+//
+// Passes the value on the left as the first argument to the call
+// on the right, so `arr |> push(1)` is the same as `push(arr, 1)`.
+// When the right side is not a call (`arr |> length`) it is called
+// with the piped value as the only argument.
+//
+// Example: [1, 2, 3] |> rest() |> length
+//
+// Structure: <expression> |> <expression>
+#[derive(Debug, Clone)]
+pub struct PipeExpression {
+    pub token: Token,
+    pub left: Expression,
+    pub right: Expression,
+}
+
+impl ast::Node for PipeExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for PipeExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} |> {})", self.left, self.right)
+    }
+}
+
+// Match expression.
+//
+// Arms are tried from top to bottom, the first arm whose pattern
+// matches the value (and whose guard is truthy) is evaluated.
+//
+// Example: match (point) { [0, 0] => "origin", [x, _] if x > 0 => "right", _ => "left" }
+//
+// Structure: match (<expression>) { <pattern> [if <expression>] => <expression|block>, ... }
+#[derive(Debug, Clone)]
+pub struct MatchExpression {
+    pub token: Token,
+    pub subject: Expression,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: BlockStatement,
+}
+
+impl ast::Node for MatchExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+}
+
+impl fmt::Display for MatchArm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.guard {
+            Some(guard) => write!(f, "{} if {} => {}", self.pattern, guard, self.body),
+            None => write!(f, "{} => {}", self.pattern, self.body),
+        }
+    }
+}
+
+impl fmt::Display for MatchExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arms = self
+            .arms
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "match {} {{{}}}", self.subject, arms)
+    }
+}